@@ -4,6 +4,12 @@ import { Project } from './components/ProjectManagement';
 
 const API_BASE_URL = import.meta.env.VITE_API_URL || 'http://localhost:8080';
 
+// Envelope of every list endpoint; pass `next_cursor` back as `?cursor=` for the following page
+export interface Page<T> {
+  items: T[];
+  next_cursor: string | null;
+}
+
 // Helper function for authenticated API calls
 async function authenticatedFetch(url: string, options?: RequestInit) {
   const token = localStorage.getItem('jwt_token'); // Assuming token is stored in localStorage
@@ -31,8 +37,8 @@ async function authenticatedFetch(url: string, options?: RequestInit) {
 }
 
 export const getProposals = async (): Promise<Proposal[]> => {
-  const response = await authenticatedFetch(`${API_BASE_URL}/proposals`);
-  return response.map((prop: any) => ({
+  const response: Page<any> = await authenticatedFetch(`${API_BASE_URL}/proposals`);
+  return response.items.map((prop: any) => ({
     id: prop.id,
     project_id: prop.project_id,
     title: prop.title,
@@ -51,7 +57,8 @@ export const getProposals = async (): Promise<Proposal[]> => {
 
 // User Management APIs
 export const getAllUsers = async (): Promise<User[]> => {
-  return authenticatedFetch(`${API_BASE_URL}/users`);
+  const response: Page<User> = await authenticatedFetch(`${API_BASE_URL}/users`);
+  return response.items;
 };
 
 export const updateUserRole = async (walletAddress: string, role: User['role']): Promise<User> => {
@@ -61,16 +68,17 @@ export const updateUserRole = async (walletAddress: string, role: User['role']):
   });
 };
 
-export const registerUser = async (walletAddress: string, role: User['role']): Promise<User> => {
+export const registerUser = async (walletAddress: string, publicKey: string, role: User['role']): Promise<User> => {
   return authenticatedFetch(`${API_BASE_URL}/users`, {
     method: 'POST',
-    body: JSON.stringify({ wallet_address: walletAddress, role }),
+    body: JSON.stringify({ wallet_address: walletAddress, public_key: publicKey, role }),
   });
 };
 
 // Project Management APIs
 export const getAllProjects = async (): Promise<Project[]> => {
-  return authenticatedFetch(`${API_BASE_URL}/projects`);
+  const response: Page<Project> = await authenticatedFetch(`${API_BASE_URL}/projects`);
+  return response.items;
 };
 
 export const updateProjectStatus = async (projectId: string, status: Project['status']): Promise<Project> => {
@@ -21,6 +21,7 @@ interface User {
 
 interface NewUser {
   wallet_address: string
+  public_key: string
   role: 'platform_owner' | 'project_admin' | 'user'
 }
 
@@ -33,7 +34,7 @@ export function UserManagement() {
   const [currentPage, setCurrentPage] = useState(1)
   const [isLoading, setIsLoading] = useState(true)
   const [isAddDialogOpen, setIsAddDialogOpen] = useState(false)
-  const [newUser, setNewUser] = useState<NewUser>({ wallet_address: '', role: 'user' })
+  const [newUser, setNewUser] = useState<NewUser>({ wallet_address: '', public_key: '', role: 'user' })
 
   // Load users on component mount
   useEffect(() => {
@@ -85,12 +86,16 @@ export function UserManagement() {
       toast.error('Please enter a valid wallet address')
       return
     }
+    if (!newUser.public_key.trim()) {
+      toast.error('Please enter the wallet\'s public key')
+      return
+    }
 
     try {
-      const addedUser = await registerUser(newUser)
+      const addedUser = await registerUser(newUser.wallet_address, newUser.public_key, newUser.role)
       
       setUsers(prev => [addedUser, ...prev])
-      setNewUser({ wallet_address: '', role: 'user' })
+      setNewUser({ wallet_address: '', public_key: '', role: 'user' })
       setIsAddDialogOpen(false)
       
       toast.success('User registered successfully')
@@ -164,6 +169,15 @@ export function UserManagement() {
                       onChange={(e) => setNewUser(prev => ({ ...prev, wallet_address: e.target.value }))}
                     />
                   </div>
+                  <div>
+                    <Label htmlFor="public_key">Public Key</Label>
+                    <Input
+                      id="public_key"
+                      placeholder="0x..."
+                      value={newUser.public_key}
+                      onChange={(e) => setNewUser(prev => ({ ...prev, public_key: e.target.value }))}
+                    />
+                  </div>
                   <div>
                     <Label htmlFor="role">Initial Role</Label>
                     <Select value={newUser.role} onValueChange={(value) => setNewUser(prev => ({ ...prev, role: value as User['role'] }))}>
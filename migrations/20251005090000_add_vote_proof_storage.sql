@@ -0,0 +1,22 @@
+-- Serialized STARK proofs, addressed by the SHA-256 of their bytes
+CREATE TABLE IF NOT EXISTS proofs (
+    proof_hash TEXT PRIMARY KEY,
+    proof_bytes BYTEA NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Databases provisioned before the governance tables were migrated already have submissions;
+-- give them the verification columns. Fresh databases get them from the table definition.
+DO $$
+BEGIN
+    IF to_regclass('submissions') IS NOT NULL THEN
+        ALTER TABLE submissions ADD COLUMN IF NOT EXISTS program_hash TEXT NOT NULL DEFAULT '';
+        ALTER TABLE submissions ADD COLUMN IF NOT EXISTS public_inputs JSONB NOT NULL DEFAULT '[]'::jsonb;
+        ALTER TABLE submissions ADD COLUMN IF NOT EXISTS stack_outputs JSONB NOT NULL DEFAULT '[]'::jsonb;
+        ALTER TABLE submissions ADD COLUMN IF NOT EXISTS failure_reason TEXT;
+        ALTER TABLE submissions ALTER COLUMN program_hash DROP DEFAULT;
+        ALTER TABLE submissions ALTER COLUMN public_inputs DROP DEFAULT;
+        ALTER TABLE submissions ALTER COLUMN stack_outputs DROP DEFAULT;
+    END IF;
+END
+$$;
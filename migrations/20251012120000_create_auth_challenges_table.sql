@@ -0,0 +1,12 @@
+-- Single-use login challenges signed by the wallet's Miden account key
+CREATE TABLE auth_challenges (
+    nonce TEXT PRIMARY KEY,
+    wallet_address TEXT NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    consumed_at TIMESTAMP
+);
+
+CREATE INDEX idx_auth_challenges_expires_at ON auth_challenges (expires_at);
+
+-- Public key the wallet first authenticated with; later logins must use the same key
+ALTER TABLE users ADD COLUMN public_key TEXT;
@@ -0,0 +1,59 @@
+-- Align users with the models: created_at is read as a NaiveDateTime and roles are a closed set
+ALTER TABLE users ALTER COLUMN created_at TYPE TIMESTAMP;
+ALTER TABLE users ALTER COLUMN created_at SET DEFAULT CURRENT_TIMESTAMP;
+ALTER TABLE users ADD CONSTRAINT users_role_check CHECK (role IN ('user', 'project_admin', 'platform_owner'));
+
+CREATE TABLE projects (
+    id UUID PRIMARY KEY,
+    owner TEXT NOT NULL,
+    token_address TEXT NOT NULL,
+    merkle_root TEXT NOT NULL,
+    config JSONB NOT NULL DEFAULT '{}'::jsonb,
+    status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'paused')),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE proposals (
+    id UUID PRIMARY KEY,
+    project_id UUID NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    choices_json JSONB NOT NULL,
+    model_enum TEXT NOT NULL CHECK (model_enum IN ('token-weighted', 'quadratic', 'one-person-one-vote')),
+    quorum DOUBLE PRECISION NOT NULL CHECK (quorum >= 0 AND quorum <= 100),
+    start_ts TIMESTAMP NOT NULL,
+    end_ts TIMESTAMP NOT NULL,
+    state TEXT NOT NULL DEFAULT 'draft' CHECK (state IN ('draft', 'active', 'closed', 'tallied')),
+    revoked BOOLEAN NOT NULL DEFAULT FALSE,
+    finalized BOOLEAN NOT NULL DEFAULT FALSE,
+    CHECK (end_ts > start_ts)
+);
+
+CREATE INDEX idx_proposals_project_id ON proposals (project_id);
+
+CREATE TABLE submissions (
+    id UUID PRIMARY KEY,
+    proposal_id UUID NOT NULL REFERENCES proposals (id) ON DELETE CASCADE,
+    proof_hash TEXT NOT NULL REFERENCES proofs (proof_hash),
+    program_hash TEXT NOT NULL,
+    public_inputs JSONB NOT NULL,
+    stack_outputs JSONB NOT NULL,
+    note_commitment TEXT NOT NULL,
+    nullifier_hash TEXT NOT NULL,
+    verified_bool BOOLEAN NOT NULL DEFAULT FALSE,
+    failure_reason TEXT,
+    verified_at TIMESTAMP
+);
+
+-- One counted ballot per nullifier and proposal. Rejected ballots are kept for auditing
+-- and must not block a corrected resubmission, so the constraint only covers verified rows.
+CREATE UNIQUE INDEX submissions_proposal_nullifier_key ON submissions (proposal_id, nullifier_hash) WHERE verified_bool;
+CREATE INDEX idx_submissions_proposal_id ON submissions (proposal_id);
+CREATE INDEX idx_submissions_nullifier_hash ON submissions (nullifier_hash);
+
+CREATE TABLE tallies (
+    id UUID PRIMARY KEY,
+    proposal_id UUID NOT NULL UNIQUE REFERENCES proposals (id) ON DELETE CASCADE,
+    aggregate_proof_hash TEXT NOT NULL,
+    results_json JSONB NOT NULL,
+    verified_at TIMESTAMP NOT NULL
+);
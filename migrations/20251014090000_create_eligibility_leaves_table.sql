@@ -0,0 +1,10 @@
+-- Current eligibility snapshot of each project; projects.merkle_root is the root over these leaves
+CREATE TABLE eligibility_leaves (
+    project_id UUID NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+    leaf_index INTEGER NOT NULL,
+    address TEXT NOT NULL,
+    weight BIGINT NOT NULL CHECK (weight >= 0),
+    leaf_hash TEXT NOT NULL,
+    PRIMARY KEY (project_id, leaf_index),
+    UNIQUE (project_id, address)
+);
@@ -0,0 +1,3 @@
+-- Voting weight and choice ranking taken from the verified vote program outputs
+ALTER TABLE submissions ADD COLUMN weight BIGINT NOT NULL DEFAULT 0 CHECK (weight >= 0);
+ALTER TABLE submissions ADD COLUMN ballot JSONB NOT NULL DEFAULT '[]'::jsonb;
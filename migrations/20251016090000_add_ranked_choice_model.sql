@@ -0,0 +1,3 @@
+ALTER TABLE proposals DROP CONSTRAINT proposals_model_enum_check;
+ALTER TABLE proposals ADD CONSTRAINT proposals_model_enum_check
+    CHECK (model_enum IN ('token-weighted', 'quadratic', 'one-person-one-vote', 'ranked_choice'));
@@ -0,0 +1,5 @@
+-- Whether quorum counts eligible voters or eligible voting weight
+ALTER TABLE proposals ADD COLUMN quorum_basis TEXT NOT NULL DEFAULT 'voters' CHECK (quorum_basis IN ('voters', 'weight'));
+
+-- Denominator, turnout and threshold the tally was checked against
+ALTER TABLE tallies ADD COLUMN quorum_json JSONB NOT NULL DEFAULT '{}'::jsonb;
@@ -0,0 +1,12 @@
+-- Proposal lifecycle: draft -> scheduled -> active -> closed -> tallied -> finalized, plus revoked
+ALTER TABLE proposals DROP CONSTRAINT proposals_state_check;
+
+UPDATE proposals SET state = 'revoked' WHERE revoked;
+UPDATE proposals SET state = 'finalized' WHERE finalized AND NOT revoked;
+
+ALTER TABLE proposals ADD CONSTRAINT proposals_state_check
+    CHECK (state IN ('draft', 'scheduled', 'active', 'closed', 'tallied', 'finalized', 'revoked'));
+
+-- Used by the scheduler to find proposals whose voting window opens or closes
+CREATE INDEX idx_proposals_state_start_ts ON proposals (state, start_ts);
+CREATE INDEX idx_proposals_state_end_ts ON proposals (state, end_ts);
@@ -0,0 +1,20 @@
+-- Threshold election key of a proposal with encrypted ballots
+CREATE TABLE elections (
+    proposal_id UUID PRIMARY KEY REFERENCES proposals (id) ON DELETE CASCADE,
+    threshold INTEGER NOT NULL CHECK (threshold >= 1),
+    trustee_count INTEGER NOT NULL CHECK (trustee_count >= threshold),
+    public_key TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Trustee public shares (x_i * G) and, once voting has closed, their decryption shares of the aggregate
+CREATE TABLE election_trustees (
+    proposal_id UUID NOT NULL REFERENCES elections (proposal_id) ON DELETE CASCADE,
+    trustee_index INTEGER NOT NULL CHECK (trustee_index >= 1),
+    public_share TEXT NOT NULL,
+    decryption_shares JSONB,
+    shares_posted_at TIMESTAMP,
+    PRIMARY KEY (proposal_id, trustee_index)
+);
+
+ALTER TABLE submissions ADD COLUMN encrypted_ballot JSONB;
@@ -0,0 +1,17 @@
+-- Per-project roles: project admins only manage the projects they are members of
+CREATE TABLE project_members (
+    project_id UUID NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+    wallet_address TEXT NOT NULL REFERENCES users (wallet_address) ON DELETE CASCADE,
+    role TEXT NOT NULL CHECK (role IN ('admin', 'editor')),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (project_id, wallet_address)
+);
+
+CREATE INDEX project_members_wallet_address_idx ON project_members (wallet_address);
+
+-- Project admins keep access to the projects they own
+INSERT INTO project_members (project_id, wallet_address, role)
+SELECT p.id, u.wallet_address, 'admin'
+FROM projects p
+JOIN users u ON u.wallet_address = p.owner
+WHERE u.role = 'project_admin';
@@ -0,0 +1,41 @@
+-- Users get a stable id; wallet addresses move to their own table so one user can link several
+ALTER TABLE users ADD COLUMN id UUID NOT NULL DEFAULT gen_random_uuid();
+
+CREATE TABLE wallets (
+    address TEXT PRIMARY KEY,
+    user_id UUID NOT NULL,
+    public_key TEXT, -- Bound on the wallet's first login
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO wallets (address, user_id, public_key, created_at)
+SELECT wallet_address, id, public_key, created_at FROM users;
+
+-- Re-key project members by user
+ALTER TABLE project_members ADD COLUMN user_id UUID;
+UPDATE project_members m SET user_id = u.id FROM users u WHERE u.wallet_address = m.wallet_address;
+ALTER TABLE project_members DROP COLUMN wallet_address;
+ALTER TABLE project_members ALTER COLUMN user_id SET NOT NULL;
+ALTER TABLE project_members ADD PRIMARY KEY (project_id, user_id);
+
+-- Refresh tokens belong to a user, and to the wallet the session was opened with
+ALTER TABLE refresh_tokens ADD COLUMN user_id UUID;
+UPDATE refresh_tokens t SET user_id = u.id FROM users u WHERE u.wallet_address = t.wallet_address;
+ALTER TABLE refresh_tokens DROP CONSTRAINT refresh_tokens_wallet_address_fkey;
+ALTER TABLE refresh_tokens ALTER COLUMN user_id SET NOT NULL;
+DROP INDEX refresh_tokens_wallet_address_idx;
+
+ALTER TABLE users DROP CONSTRAINT users_pkey;
+ALTER TABLE users ADD PRIMARY KEY (id);
+ALTER TABLE users DROP COLUMN wallet_address;
+ALTER TABLE users DROP COLUMN public_key;
+
+ALTER TABLE wallets ADD FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE;
+ALTER TABLE project_members ADD FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE;
+ALTER TABLE refresh_tokens ADD FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE;
+-- Unlinking a wallet ends the sessions opened with it
+ALTER TABLE refresh_tokens ADD FOREIGN KEY (wallet_address) REFERENCES wallets (address) ON DELETE CASCADE;
+
+CREATE INDEX wallets_user_id_idx ON wallets (user_id);
+CREATE INDEX project_members_user_id_idx ON project_members (user_id);
+CREATE INDEX refresh_tokens_user_id_idx ON refresh_tokens (user_id);
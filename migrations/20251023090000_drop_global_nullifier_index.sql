@@ -0,0 +1,2 @@
+-- Nullifiers are only meaningful per proposal; lookups go through submissions_proposal_nullifier_key
+DROP INDEX idx_submissions_nullifier_hash;
@@ -0,0 +1,12 @@
+-- Position of a counted ballot among its proposal's counted ballots, quoted in the voter's receipt
+ALTER TABLE submissions ADD COLUMN inclusion_index BIGINT;
+
+UPDATE submissions s SET inclusion_index = numbered.idx
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY proposal_id ORDER BY verified_at, id) - 1 AS idx
+    FROM submissions
+    WHERE verified_bool
+) numbered
+WHERE s.id = numbered.id;
+
+CREATE UNIQUE INDEX submissions_inclusion_index_key ON submissions (proposal_id, inclusion_index);
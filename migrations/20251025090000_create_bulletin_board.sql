@@ -0,0 +1,50 @@
+-- Append-only log of counted ballots per proposal. Each entry extends a hash chain and carries the
+-- Merkle root of the log up to and including it.
+CREATE TABLE bulletin_entries (
+    proposal_id UUID NOT NULL REFERENCES proposals (id),
+    entry_index BIGINT NOT NULL, -- The submission's inclusion_index
+    submission_id UUID NOT NULL UNIQUE REFERENCES submissions (id),
+    leaf_hash TEXT NOT NULL,
+    chain_hash TEXT NOT NULL,
+    root TEXT NOT NULL,
+    frontier JSONB NOT NULL, -- Perfect subtree roots, to extend the tree without rereading it
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (proposal_id, entry_index)
+);
+
+-- Roots the operator has committed to. Proofs are only served between published roots.
+CREATE TABLE bulletin_roots (
+    proposal_id UUID NOT NULL REFERENCES proposals (id),
+    tree_size BIGINT NOT NULL,
+    root TEXT NOT NULL,
+    chain_hash TEXT NOT NULL,
+    published_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (proposal_id, tree_size)
+);
+
+CREATE FUNCTION reject_bulletin_change() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'The bulletin board is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER bulletin_entries_append_only BEFORE UPDATE OR DELETE ON bulletin_entries
+    FOR EACH ROW EXECUTE FUNCTION reject_bulletin_change();
+CREATE TRIGGER bulletin_entries_no_truncate BEFORE TRUNCATE ON bulletin_entries
+    FOR EACH STATEMENT EXECUTE FUNCTION reject_bulletin_change();
+CREATE TRIGGER bulletin_roots_append_only BEFORE UPDATE OR DELETE ON bulletin_roots
+    FOR EACH ROW EXECUTE FUNCTION reject_bulletin_change();
+CREATE TRIGGER bulletin_roots_no_truncate BEFORE TRUNCATE ON bulletin_roots
+    FOR EACH STATEMENT EXECUTE FUNCTION reject_bulletin_change();
+
+-- The root a tally was computed against
+ALTER TABLE tallies ADD COLUMN bulletin_size BIGINT;
+ALTER TABLE tallies ADD COLUMN bulletin_root TEXT;
+
+-- Proposals with ballots from before the bulletin board, logged once at startup by bulletin::backfill
+CREATE TABLE bulletin_backfill (
+    proposal_id UUID PRIMARY KEY REFERENCES proposals (id) ON DELETE CASCADE
+);
+
+INSERT INTO bulletin_backfill (proposal_id)
+SELECT DISTINCT proposal_id FROM submissions WHERE verified_bool;
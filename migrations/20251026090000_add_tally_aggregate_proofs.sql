@@ -0,0 +1,10 @@
+-- Tallies carry a STARK proof of the tally program, stored with the ballot proofs. Tallies from
+-- before proofs were required had a placeholder hash and are left without one.
+ALTER TABLE tallies ALTER COLUMN aggregate_proof_hash DROP NOT NULL;
+UPDATE tallies SET aggregate_proof_hash = NULL WHERE aggregate_proof_hash = 'dummy_aggregate_proof_hash';
+ALTER TABLE tallies ADD CONSTRAINT tallies_aggregate_proof_hash_fkey
+    FOREIGN KEY (aggregate_proof_hash) REFERENCES proofs (proof_hash);
+
+ALTER TABLE tallies ADD COLUMN aggregate_program_hash TEXT;
+ALTER TABLE tallies ADD COLUMN aggregate_public_inputs JSONB;
+ALTER TABLE tallies ADD COLUMN aggregate_stack_outputs JSONB;
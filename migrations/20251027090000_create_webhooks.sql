@@ -0,0 +1,33 @@
+-- Per-project webhook subscriptions. Payloads are signed with `secret` (HMAC-SHA256).
+CREATE TABLE webhooks (
+    id UUID PRIMARY KEY,
+    project_id UUID NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events TEXT[] NOT NULL, -- Event names subscribed to, e.g. 'proposal.tallied'
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_webhooks_project_id ON webhooks (project_id);
+
+-- One row per event and webhook, queued in the transaction that raised the event and retried with
+-- exponential backoff until delivered or out of attempts. Doubles as the delivery log.
+CREATE TABLE webhook_deliveries (
+    id UUID PRIMARY KEY,
+    webhook_id UUID NOT NULL REFERENCES webhooks (id) ON DELETE CASCADE,
+    event_id UUID NOT NULL, -- Shared by every delivery of the same event, redeliveries included
+    event TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP, -- Set while pending
+    last_attempt_at TIMESTAMP,
+    response_status INTEGER, -- HTTP status of the last attempt, if the endpoint answered
+    last_error TEXT,
+    redelivery_of UUID REFERENCES webhook_deliveries (id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    delivered_at TIMESTAMP
+);
+
+CREATE INDEX idx_webhook_deliveries_due ON webhook_deliveries (next_attempt_at) WHERE status = 'pending';
+CREATE INDEX idx_webhook_deliveries_webhook_id ON webhook_deliveries (webhook_id, created_at);
@@ -0,0 +1,12 @@
+-- The eligibility snapshot a proposal was opened against, fixed when it becomes active: the root
+-- ballots prove membership against, and the voter count and total weight quorum is measured against.
+ALTER TABLE proposals ADD COLUMN eligibility_root TEXT;
+ALTER TABLE proposals ADD COLUMN eligible_voters BIGINT;
+ALTER TABLE proposals ADD COLUMN eligible_weight BIGINT;
+
+-- Proposals opened before snapshots were kept take the project's current one
+UPDATE proposals SET
+    eligibility_root = (SELECT merkle_root FROM projects WHERE id = proposals.project_id),
+    eligible_voters = (SELECT COUNT(*) FROM eligibility_leaves WHERE project_id = proposals.project_id),
+    eligible_weight = (SELECT COALESCE(SUM(weight), 0)::BIGINT FROM eligibility_leaves WHERE project_id = proposals.project_id)
+WHERE state IN ('active', 'closed', 'tallied', 'finalized');
@@ -0,0 +1,12 @@
+-- The bulletin board is append-only, so a proposal or submission it has logged can't be deleted, nor can
+-- the project that owns it: the cascade from projects stops here. Say so in the constraints instead of
+-- leaving it to whichever check a cascading delete happens to reach first.
+ALTER TABLE bulletin_entries
+    DROP CONSTRAINT bulletin_entries_proposal_id_fkey,
+    ADD CONSTRAINT bulletin_entries_proposal_id_fkey FOREIGN KEY (proposal_id) REFERENCES proposals (id) ON DELETE RESTRICT,
+    DROP CONSTRAINT bulletin_entries_submission_id_fkey,
+    ADD CONSTRAINT bulletin_entries_submission_id_fkey FOREIGN KEY (submission_id) REFERENCES submissions (id) ON DELETE RESTRICT;
+
+ALTER TABLE bulletin_roots
+    DROP CONSTRAINT bulletin_roots_proposal_id_fkey,
+    ADD CONSTRAINT bulletin_roots_proposal_id_fkey FOREIGN KEY (proposal_id) REFERENCES proposals (id) ON DELETE RESTRICT;
@@ -0,0 +1,4 @@
+-- Receipt signature of a counted ballot, made once with the receipt key when it is counted. Ballots
+-- counted before this are signed by receipts::backfill at startup.
+ALTER TABLE submissions ADD COLUMN receipt_kid TEXT;
+ALTER TABLE submissions ADD COLUMN receipt_signature TEXT;
@@ -0,0 +1,6 @@
+-- A closed proposal whose tally misses quorum ends as failed, keeping the quorum report it was judged by
+ALTER TABLE proposals DROP CONSTRAINT proposals_state_check;
+ALTER TABLE proposals ADD CONSTRAINT proposals_state_check
+    CHECK (state IN ('draft', 'scheduled', 'active', 'closed', 'tallied', 'finalized', 'failed', 'revoked'));
+
+ALTER TABLE proposals ADD COLUMN quorum_json JSONB;
@@ -0,0 +1,6 @@
+-- Only counted ballots keep their proof bytes; a rejected ballot records the hash of what it sent
+ALTER TABLE submissions DROP CONSTRAINT IF EXISTS submissions_proof_hash_fkey;
+
+DELETE FROM proofs p
+WHERE NOT EXISTS (SELECT 1 FROM submissions s WHERE s.proof_hash = p.proof_hash AND s.verified_bool = TRUE)
+  AND NOT EXISTS (SELECT 1 FROM tallies t WHERE t.aggregate_proof_hash = p.proof_hash);
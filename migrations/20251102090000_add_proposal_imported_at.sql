@@ -0,0 +1,3 @@
+-- Set on proposals imported from another deployment, whose ballots keep the receipts they were issued
+-- there; receipts::backfill never signs for them
+ALTER TABLE proposals ADD COLUMN imported_at TIMESTAMP;
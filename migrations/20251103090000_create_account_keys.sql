@@ -0,0 +1,7 @@
+-- Auth key commitment of each Miden account a wallet has signed in as, as the account holds it.
+-- Logins check the signing key against it; see accounts::AccountKeys.
+CREATE TABLE account_keys (
+    account_id TEXT PRIMARY KEY,
+    public_key TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,16 @@
+-- Election keys are generated by the trustees themselves (see election_handlers): the key stays unset
+-- until every registered trustee has dealt and checked the shares dealt to them, and each public
+-- share is derived from the qualified dealers' commitments.
+-- Elections set up from dealer-supplied shares keep their key, but have no registered trustees.
+ALTER TABLE elections ALTER COLUMN public_key DROP NOT NULL;
+
+ALTER TABLE election_trustees ALTER COLUMN public_share DROP NOT NULL;
+ALTER TABLE election_trustees
+    ADD COLUMN user_id UUID REFERENCES users (id),
+    ADD COLUMN transport_key TEXT,
+    ADD COLUMN commitments JSONB,
+    ADD COLUMN dealt_shares JSONB,
+    ADD COLUMN dealt_at TIMESTAMP,
+    ADD COLUMN shares_checked_at TIMESTAMP,
+    ADD COLUMN disqualified BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD CONSTRAINT election_trustees_user_key UNIQUE (proposal_id, user_id);
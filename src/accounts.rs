@@ -0,0 +1,83 @@
+use chrono::Utc;
+use serde::Deserialize;
+use sqlx::PgConnection;
+use std::time::Duration;
+
+use crate::error::ApiError;
+
+// How long a key lookup may take before the login fails
+const LOOKUP_TIMEOUT_SECS: u64 = 10;
+
+// AccountKeys: Where the auth key of a Miden account is found. A wallet address is the account's ID,
+// which says nothing about its key, so the key a wallet signs with is checked against the one the
+// account itself holds. Known keys are kept in `account_keys`; the lookup service, when configured,
+// is asked for the account's current key whenever the stored one is missing or doesn't match, since an
+// account can rotate its key on chain.
+//   ACCOUNT_KEYS_URL=<url>   optional, GET <url>/<account_id> answers { "public_key": "0x..." } with
+//                            the auth key commitment in the account's storage, e.g. an indexer
+//                            in front of a Miden node
+#[derive(Debug, Clone, Default)]
+pub struct AccountKeys {
+    lookup_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AccountKeyResponse {
+    public_key: String,
+}
+
+impl AccountKeys {
+    pub fn from_env() -> Self {
+        let lookup_url = std::env::var("ACCOUNT_KEYS_URL").ok().map(|url| url.trim_end_matches('/').to_string());
+        AccountKeys { lookup_url: lookup_url.filter(|url| !url.is_empty()) }
+    }
+
+    /// Checks `public_key` is the auth key of account `account_id`, ignoring hex case.
+    pub async fn check(&self, conn: &mut PgConnection, account_id: &str, public_key: &str) -> Result<(), ApiError> {
+        let stored = sqlx::query_scalar::<_, String>("SELECT public_key FROM account_keys WHERE account_id = $1")
+            .bind(account_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+        if stored.is_some_and(|key| key.eq_ignore_ascii_case(public_key)) {
+            return Ok(());
+        }
+
+        let Some(current) = self.fetch(account_id).await? else {
+            return Err(ApiError::PublicKeyMismatch);
+        };
+        sqlx::query(
+            "INSERT INTO account_keys (account_id, public_key, updated_at) VALUES ($1, $2, $3) \
+             ON CONFLICT (account_id) DO UPDATE SET public_key = EXCLUDED.public_key, updated_at = EXCLUDED.updated_at"
+        )
+        .bind(account_id)
+        .bind(&current)
+        .bind(Utc::now().naive_utc())
+        .execute(&mut *conn)
+        .await?;
+
+        match current.eq_ignore_ascii_case(public_key) {
+            true => Ok(()),
+            false => Err(ApiError::PublicKeyMismatch),
+        }
+    }
+
+    // The account's current key from the lookup service; None without one, or for an unknown account
+    async fn fetch(&self, account_id: &str) -> Result<Option<String>, ApiError> {
+        let Some(lookup_url) = &self.lookup_url else { return Ok(None) };
+        let client = awc::Client::builder().timeout(Duration::from_secs(LOOKUP_TIMEOUT_SECS)).finish();
+        let mut response = client
+            .get(format!("{}/{}", lookup_url, account_id))
+            .send()
+            .await
+            .map_err(|e| ApiError::AccountLookupFailed(e.to_string()))?;
+
+        match response.status().as_u16() {
+            404 => Ok(None),
+            200 => {
+                let body: AccountKeyResponse = response.json().await.map_err(|e| ApiError::AccountLookupFailed(e.to_string()))?;
+                Ok(Some(body.public_key))
+            }
+            status => Err(ApiError::AccountLookupFailed(format!("Lookup answered {}", status))),
+        }
+    }
+}
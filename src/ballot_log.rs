@@ -0,0 +1,328 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+// Hashing and Merkle proofs of the ballot bulletin board, one log per proposal. Every entry extends a
+// hash chain and a Merkle tree over the entries' leaf hashes, following RFC 6962 (Certificate Transparency):
+//   leaf  = SHA-256(0x00 || entry data)      node = SHA-256(0x01 || left || right)
+//   chain = SHA-256(previous chain || leaf), starting from 32 zero bytes
+// Storage and publishing live in the server's `bulletin` module.
+
+pub type Hash = [u8; 32];
+
+pub const GENESIS_CHAIN: Hash = [0u8; 32];
+
+// EntryData: What a leaf commits to. Editing any of these in `submissions` breaks the tally check.
+#[derive(Serialize)]
+pub struct EntryData<'a> {
+    pub index: i64,
+    pub submission_id: Uuid,
+    pub proposal_id: Uuid,
+    pub nullifier_hash: &'a str,
+    pub proof_hash: &'a str,
+    pub weight: i64,
+    pub ballot: &'a serde_json::Value,
+    pub encrypted_ballot: &'a Option<serde_json::Value>,
+}
+
+impl EntryData<'_> {
+    pub fn leaf(&self) -> Option<Hash> {
+        Some(leaf_hash(&serde_json::to_vec(self).ok()?))
+    }
+}
+
+pub fn to_hex(hash: &Hash) -> String {
+    hex::encode(hash)
+}
+
+pub fn from_hex(value: &str) -> Option<Hash> {
+    hex::decode(value).ok()?.try_into().ok()
+}
+
+pub fn leaf_hash(data: &[u8]) -> Hash {
+    Sha256::new().chain_update([0x00]).chain_update(data).finalize().into()
+}
+
+pub fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    Sha256::new().chain_update([0x01]).chain_update(left).chain_update(right).finalize().into()
+}
+
+pub fn chain_hash(previous: &Hash, leaf: &Hash) -> Hash {
+    Sha256::new().chain_update(previous).chain_update(leaf).finalize().into()
+}
+
+// Largest power of two strictly below n (n > 1)
+fn split(n: usize) -> usize {
+    let mut k = 1;
+    while k << 1 < n {
+        k <<= 1;
+    }
+    k
+}
+
+/// Merkle tree hash of a list of leaf hashes.
+pub fn root(leaves: &[Hash]) -> Hash {
+    match leaves.len() {
+        0 => Sha256::digest([]).into(),
+        1 => leaves[0],
+        n => {
+            let k = split(n);
+            node_hash(&root(&leaves[..k]), &root(&leaves[k..]))
+        }
+    }
+}
+
+/// Audit path for leaf `index` in the tree over `leaves`.
+pub fn inclusion_proof(index: usize, leaves: &[Hash]) -> Vec<Hash> {
+    let n = leaves.len();
+    if n <= 1 {
+        return Vec::new();
+    }
+    let k = split(n);
+    if index < k {
+        let mut path = inclusion_proof(index, &leaves[..k]);
+        path.push(root(&leaves[k..]));
+        path
+    } else {
+        let mut path = inclusion_proof(index - k, &leaves[k..]);
+        path.push(root(&leaves[..k]));
+        path
+    }
+}
+
+/// Proof that the tree over the first `old_size` leaves is a prefix of the tree over `leaves`.
+pub fn consistency_proof(old_size: usize, leaves: &[Hash]) -> Vec<Hash> {
+    fn subproof(m: usize, leaves: &[Hash], complete: bool) -> Vec<Hash> {
+        let n = leaves.len();
+        if m == n {
+            return if complete { Vec::new() } else { vec![root(leaves)] };
+        }
+        let k = split(n);
+        if m <= k {
+            let mut proof = subproof(m, &leaves[..k], complete);
+            proof.push(root(&leaves[k..]));
+            proof
+        } else {
+            let mut proof = subproof(m - k, &leaves[k..], false);
+            proof.push(root(&leaves[..k]));
+            proof
+        }
+    }
+
+    if old_size == 0 || old_size >= leaves.len() {
+        return Vec::new();
+    }
+    subproof(old_size, leaves, true)
+}
+
+/// Checks an audit path, as in RFC 9162 section 2.1.3.2.
+pub fn verify_inclusion(index: u64, size: u64, leaf: &Hash, proof: &[Hash], root: &Hash) -> bool {
+    if index >= size {
+        return false;
+    }
+    let (mut f, mut s) = (index, size - 1);
+    let mut r = *leaf;
+    for p in proof {
+        if s == 0 {
+            return false;
+        }
+        if f & 1 == 1 || f == s {
+            r = node_hash(p, &r);
+            while f & 1 == 0 && f != 0 {
+                f >>= 1;
+                s >>= 1;
+            }
+        } else {
+            r = node_hash(&r, p);
+        }
+        f >>= 1;
+        s >>= 1;
+    }
+    s == 0 && &r == root
+}
+
+/// Checks a consistency proof between two tree sizes, as in RFC 9162 section 2.1.4.2.
+pub fn verify_consistency(old_size: u64, new_size: u64, old_root: &Hash, new_root: &Hash, proof: &[Hash]) -> bool {
+    if old_size > new_size {
+        return false;
+    }
+    if old_size == new_size {
+        return proof.is_empty() && old_root == new_root;
+    }
+    if old_size == 0 {
+        return proof.is_empty();
+    }
+
+    let mut path = proof.to_vec();
+    if old_size.is_power_of_two() {
+        path.insert(0, *old_root);
+    }
+    let Some((first, rest)) = path.split_first() else {
+        return false;
+    };
+
+    let (mut f, mut s) = (old_size - 1, new_size - 1);
+    while f & 1 == 1 {
+        f >>= 1;
+        s >>= 1;
+    }
+    let (mut fr, mut sr) = (*first, *first);
+    for c in rest {
+        if s == 0 {
+            return false;
+        }
+        if f & 1 == 1 || f == s {
+            fr = node_hash(c, &fr);
+            sr = node_hash(c, &sr);
+            while f & 1 == 0 && f != 0 {
+                f >>= 1;
+                s >>= 1;
+            }
+        } else {
+            sr = node_hash(&sr, c);
+        }
+        f >>= 1;
+        s >>= 1;
+    }
+    &fr == old_root && &sr == new_root && s == 0
+}
+
+// Roots of the perfect subtrees a log of `size` leaves splits into, largest first. Enough to extend the
+// tree by one leaf without rereading it.
+pub fn extend_frontier(frontier: &mut Vec<Hash>, size: u64, leaf: Hash) {
+    let mut node = leaf;
+    let mut s = size;
+    while s & 1 == 1 {
+        let Some(left) = frontier.pop() else { break };
+        node = node_hash(&left, &node);
+        s >>= 1;
+    }
+    frontier.push(node);
+}
+
+pub fn frontier_root(frontier: &[Hash]) -> Hash {
+    match frontier.split_last() {
+        None => root(&[]),
+        Some((last, rest)) => rest.iter().rev().fold(*last, |acc, left| node_hash(left, &acc)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const N: usize = 33;
+
+    fn leaves(n: usize) -> Vec<Hash> {
+        (0..n).map(|i| leaf_hash(&(i as u64).to_be_bytes())).collect()
+    }
+
+    fn flip(hash: &Hash) -> Hash {
+        let mut hash = *hash;
+        hash[0] ^= 1;
+        hash
+    }
+
+    #[test]
+    fn inclusion_proofs_verify_for_every_leaf_and_size() {
+        let all = leaves(N);
+        for size in 1..=N {
+            let tree = &all[..size];
+            let root = root(tree);
+            for (index, leaf) in tree.iter().enumerate() {
+                let proof = inclusion_proof(index, tree);
+                assert!(verify_inclusion(index as u64, size as u64, leaf, &proof, &root), "leaf {} of {}", index, size);
+            }
+        }
+    }
+
+    #[test]
+    fn tampered_inclusion_proofs_are_rejected() {
+        let all = leaves(N);
+        for size in 2..=N {
+            let tree = &all[..size];
+            let root = root(tree);
+            for (index, leaf) in tree.iter().enumerate() {
+                let proof = inclusion_proof(index, tree);
+                let (i, n) = (index as u64, size as u64);
+                for step in 0..proof.len() {
+                    let mut tampered = proof.clone();
+                    tampered[step] = flip(&tampered[step]);
+                    assert!(!verify_inclusion(i, n, leaf, &tampered, &root), "leaf {} of {}, step {}", index, size, step);
+                }
+                assert!(!verify_inclusion(i, n, leaf, &proof[..proof.len() - 1], &root));
+                assert!(!verify_inclusion(i, n, &flip(leaf), &proof, &root));
+                assert!(!verify_inclusion(i, n, leaf, &proof, &flip(&root)));
+                assert!(!verify_inclusion((index + 1) as u64 % n, n, leaf, &proof, &root));
+                assert!(!verify_inclusion(n, n, leaf, &proof, &root));
+            }
+        }
+    }
+
+    #[test]
+    fn consistency_proofs_verify_between_every_pair_of_sizes() {
+        let all = leaves(N);
+        for new_size in 1..=N {
+            let new_root = root(&all[..new_size]);
+            for old_size in 1..=new_size {
+                let old_root = root(&all[..old_size]);
+                let proof = consistency_proof(old_size, &all[..new_size]);
+                let (m, n) = (old_size as u64, new_size as u64);
+                assert!(verify_consistency(m, n, &old_root, &new_root, &proof), "{} to {}", old_size, new_size);
+
+                for step in 0..proof.len() {
+                    let mut tampered = proof.clone();
+                    tampered[step] = flip(&tampered[step]);
+                    assert!(!verify_consistency(m, n, &old_root, &new_root, &tampered), "{} to {}, step {}", old_size, new_size, step);
+                }
+                assert!(!verify_consistency(m, n, &flip(&old_root), &new_root, &proof));
+                assert!(!verify_consistency(m, n, &old_root, &flip(&new_root), &proof));
+            }
+        }
+    }
+
+    #[test]
+    fn a_rewritten_prefix_is_not_consistent() {
+        let all = leaves(N);
+        let mut rewritten = all.clone();
+        rewritten[3] = flip(&rewritten[3]);
+
+        let old_root = root(&all[..8]);
+        let proof = consistency_proof(8, &rewritten[..N]);
+        assert!(!verify_consistency(8, N as u64, &old_root, &root(&rewritten), &proof));
+    }
+
+    #[test]
+    fn the_frontier_tracks_the_root_as_leaves_are_appended() {
+        let all = leaves(N);
+        let mut frontier = Vec::new();
+        assert_eq!(frontier_root(&frontier), root(&[]));
+        for (size, leaf) in all.iter().enumerate() {
+            extend_frontier(&mut frontier, size as u64, *leaf);
+            assert_eq!(frontier_root(&frontier), root(&all[..=size]), "size {}", size + 1);
+            assert_eq!(frontier.len() as u32, (size as u64 + 1).count_ones());
+        }
+    }
+
+    #[test]
+    fn the_chain_commits_to_every_leaf_in_order() {
+        let all = leaves(4);
+        let chain = |leaves: &[Hash]| leaves.iter().fold(GENESIS_CHAIN, |previous, leaf| chain_hash(&previous, leaf));
+
+        let head = chain(&all);
+        assert_eq!(head, chain_hash(&chain(&all[..3]), &all[3]));
+        assert_ne!(head, chain(&[all[1], all[0], all[2], all[3]]));
+        assert_ne!(head, chain(&all[..3]));
+        assert_ne!(chain(&all[..1]), all[0]);
+    }
+
+    #[test]
+    fn leaves_and_nodes_are_domain_separated() {
+        let (a, b) = (leaf_hash(b"a"), leaf_hash(b"b"));
+        let mut concatenated = a.to_vec();
+        concatenated.extend_from_slice(&b);
+        assert_ne!(leaf_hash(&concatenated), node_hash(&a, &b));
+        assert_eq!(from_hex(&to_hex(&a)), Some(a));
+        assert_eq!(from_hex("abcd"), None);
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::HashSet;
+use std::process::ExitCode;
+
+use miden_crypto::hash::rpo::Rpo256;
+use miden_voting_backend::ballot_log::{self, EntryData};
+use miden_voting_backend::bundle::{Bundle, SubmissionRecord};
+use miden_voting_backend::election::{self, Ciphertext, EncryptedBallot};
+use miden_voting_backend::merkle::{self, MerkleTree};
+use miden_voting_backend::tally::{self, Ballot};
+use miden_voting_backend::verifier::{self, ProgramProof, TallyPublicInputs, VoteOutputs, VotePublicInputs};
+
+// miden-vote-verify: Re-checks an exported proposal bundle without trusting the server that produced it.
+// Every submission proof, nullifier and eligibility root is checked, and the results are recomputed with
+// the server's own tally engine. Proofs only mean something for a known program, so the vote and tally
+// program hashes must be pinned. Prints one line per check; exits 1 if any check fails.
+const USAGE: &str = "Usage: miden-vote-verify <bundle.json | -> --vote-program-hash HASH --tally-program-hash HASH";
+
+struct Options {
+    path: String,
+    vote_program_hash: String,
+    tally_program_hash: String,
+}
+
+fn parse_args() -> Option<Options> {
+    let mut args = std::env::args().skip(1);
+    let (mut path, mut vote_program_hash, mut tally_program_hash) = (None, None, None);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--vote-program-hash" => vote_program_hash = Some(args.next()?),
+            "--tally-program-hash" => tally_program_hash = Some(args.next()?),
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => return None,
+        }
+    }
+    Some(Options { path: path?, vote_program_hash: vote_program_hash?, tally_program_hash: tally_program_hash? })
+}
+
+fn read_bundle(path: &str) -> Result<Bundle, String> {
+    let json = if path == "-" {
+        std::io::read_to_string(std::io::stdin()).map_err(|e| e.to_string())?
+    } else {
+        std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?
+    };
+    let bundle: Bundle = serde_json::from_str(&json).map_err(|e| format!("Not a proposal bundle: {}", e))?;
+    Bundle::check_version(&bundle.format, bundle.version)?;
+    Ok(bundle)
+}
+
+// Report: Outcome of each check, printed as it is recorded
+#[derive(Default)]
+struct Report {
+    failures: usize,
+}
+
+impl Report {
+    fn check(&mut self, name: &str, outcome: Result<String, String>) {
+        match outcome {
+            Ok(detail) => println!("PASS  {}: {}", name, detail),
+            Err(reason) => {
+                self.failures += 1;
+                println!("FAIL  {}: {}", name, reason);
+            }
+        }
+    }
+
+    fn skip(&self, name: &str, reason: &str) {
+        println!("SKIP  {}: {}", name, reason);
+    }
+}
+
+fn expect_program(expected: &str, actual: &str) -> Result<(), String> {
+    match expected.eq_ignore_ascii_case(actual) {
+        true => Ok(()),
+        false => Err(format!("generated for program {}, expected {}", actual, expected)),
+    }
+}
+
+fn check_eligibility(bundle: &Bundle) -> Result<String, String> {
+    let mut leaves = bundle.eligibility.clone();
+    leaves.sort_by_key(|l| l.leaf_index);
+    if leaves.iter().enumerate().any(|(i, l)| l.leaf_index as usize != i) {
+        return Err("leaf indices are not contiguous from 0".to_string());
+    }
+
+    let hashes = leaves.iter().map(|l| merkle::leaf_hash(&l.address, l.weight as u64)).collect();
+    let root = MerkleTree::new(hashes).root();
+    let expected = merkle::digest_from_hex(bundle.eligibility_root());
+    if expected.map(|r| merkle::digest_to_ints(&r)) != Some(merkle::digest_to_ints(&root)) {
+        return Err(format!(
+            "{} leaves hash to {}, the proposal was opened against {}",
+            leaves.len(),
+            merkle::digest_to_hex(&root),
+            bundle.eligibility_root()
+        ));
+    }
+    Ok(format!("{} leaves hash to eligibility root {}", leaves.len(), merkle::digest_to_hex(&root)))
+}
+
+// Checks one submission as submit_vote did; returns whether its proof verifies
+fn check_submission(bundle: &Bundle, submission: &SubmissionRecord, vote_program_hash: &str) -> Result<bool, String> {
+    if submission.proposal_id != bundle.proposal.id {
+        return Err("belongs to another proposal".to_string());
+    }
+    let inputs = VotePublicInputs::parse(&submission.public_inputs).ok_or("public inputs do not match the vote program layout")?;
+    if inputs.proposal_id != verifier::proposal_id_word(&bundle.proposal.id) {
+        return Err("proof is not bound to this proposal".to_string());
+    }
+    let nullifier_hash = merkle::digest_to_hex(&merkle::digest_from_ints(inputs.nullifier));
+    if !nullifier_hash.eq_ignore_ascii_case(&submission.nullifier_hash) {
+        return Err(format!("nullifier {} is not the one the proof outputs ({})", submission.nullifier_hash, nullifier_hash));
+    }
+    match merkle::digest_from_hex(bundle.eligibility_root()) {
+        Some(root) if merkle::digest_to_ints(&root) == inputs.merkle_root => {}
+        _ => return Err("proof is not against the eligibility snapshot".to_string()),
+    }
+
+    // The recorded weight and ballot are only as good as the proof outputs they were read from
+    let outputs = VoteOutputs::parse(&submission.stack_outputs).ok_or("stack outputs do not match the vote program layout")?;
+    if i64::try_from(outputs.weight).ok() != Some(submission.weight) {
+        return Err(format!("weight {} is not the one the proof outputs ({})", submission.weight, outputs.weight));
+    }
+    if serde_json::from_value::<Vec<u64>>(submission.ballot.clone()).ok().as_ref() != Some(&outputs.choices) {
+        return Err(format!("ballot {} is not the one the proof outputs ({:?})", submission.ballot, outputs.choices));
+    }
+    if let Some(encrypted) = &submission.encrypted_ballot {
+        let ciphertexts: Option<Vec<Ciphertext>> = serde_json::from_value::<EncryptedBallot>(encrypted.clone())
+            .ok()
+            .and_then(|ballot| ballot.choices.iter().map(|c| Ciphertext::parse(&c.ciphertext)).collect());
+        let commitment = ciphertexts.map(|cts| merkle::digest_to_ints(&Rpo256::hash(&election::ballot_bytes(&cts))));
+        if commitment != Some(inputs.ballot_commitment) {
+            return Err("proof does not commit to the encrypted ballot".to_string());
+        }
+    }
+
+    expect_program(vote_program_hash, &submission.program_hash)?;
+    let proof_bytes = bundle
+        .proof_bytes(&submission.proof_hash)
+        .ok_or_else(|| format!("proof {} is missing or does not match its hash", submission.proof_hash))?;
+    let verification = verifier::verify_proof(&ProgramProof {
+        proof_bytes: &proof_bytes,
+        program_hash: &submission.program_hash,
+        public_inputs: &submission.public_inputs,
+        stack_outputs: &submission.stack_outputs,
+    });
+
+    match (verification.verified, submission.verified_bool) {
+        (true, false) => Err("proof verifies but the server rejected it".to_string()),
+        (false, true) => Err(format!(
+            "counted, but {}",
+            verification.failure_reason.unwrap_or_else(|| "the proof does not verify".to_string())
+        )),
+        (verified, _) => Ok(verified),
+    }
+}
+
+fn check_nullifiers(counted: &[&SubmissionRecord]) -> Result<String, String> {
+    let mut seen = HashSet::new();
+    for submission in counted {
+        if !seen.insert(submission.nullifier_hash.to_lowercase()) {
+            return Err(format!("nullifier {} is counted more than once", submission.nullifier_hash));
+        }
+    }
+    Ok(format!("{} counted ballots, all nullifiers distinct", counted.len()))
+}
+
+fn check_bulletin(bundle: &Bundle, counted: &[&SubmissionRecord]) -> Result<Option<[u8; 32]>, String> {
+    let Some(tally) = &bundle.tally else { return Ok(None) };
+    let (Some(size), Some(root)) = (tally.bulletin_size, &tally.bulletin_root) else { return Ok(None) };
+
+    let mut leaves = Vec::with_capacity(counted.len());
+    for (i, submission) in counted.iter().enumerate() {
+        if submission.inclusion_index != Some(i as i64) {
+            return Err(format!("submission {} is out of inclusion order", submission.id));
+        }
+        let entry = EntryData {
+            index: i as i64,
+            submission_id: submission.id,
+            proposal_id: submission.proposal_id,
+            nullifier_hash: &submission.nullifier_hash,
+            proof_hash: &submission.proof_hash,
+            weight: submission.weight,
+            ballot: &submission.ballot,
+            encrypted_ballot: &submission.encrypted_ballot,
+        };
+        leaves.push(entry.leaf().ok_or("ballot can't be encoded")?);
+    }
+
+    let computed = ballot_log::root(&leaves);
+    if leaves.len() as i64 != size || ballot_log::to_hex(&computed) != *root {
+        return Err(format!(
+            "{} ballots hash to {}, tally committed to {} ballots under {}",
+            leaves.len(),
+            ballot_log::to_hex(&computed),
+            size,
+            root
+        ));
+    }
+    Ok(Some(computed))
+}
+
+fn main() -> ExitCode {
+    let Some(options) = parse_args() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+    let bundle = match read_bundle(&options.path) {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    println!("Proposal {} ({})", bundle.proposal.id, bundle.proposal.title);
+    let mut report = Report::default();
+
+    if bundle.proposal.project_id != bundle.project.id {
+        report.check("bundle", Err("proposal belongs to another project".to_string()));
+    }
+    report.check("eligibility", check_eligibility(&bundle));
+
+    let mut counted: Vec<&SubmissionRecord> = Vec::new();
+    for submission in &bundle.submissions {
+        match check_submission(&bundle, submission, &options.vote_program_hash) {
+            Ok(true) => counted.push(submission),
+            Ok(false) => {}
+            Err(reason) => report.check(&format!("submission {}", submission.id), Err(reason)),
+        }
+    }
+    report.check(
+        "submissions",
+        Ok(format!("{} checked, {} proofs verify", bundle.submissions.len(), counted.len())),
+    );
+    counted.sort_by_key(|s| s.inclusion_index);
+    report.check("nullifiers", check_nullifiers(&counted));
+
+    let Some(tally) = &bundle.tally else {
+        report.skip("tally", "proposal has not been tallied");
+        return finish(report);
+    };
+    if tally.proposal_id != bundle.proposal.id {
+        report.check("tally", Err("belongs to another proposal".to_string()));
+        return finish(report);
+    }
+
+    let bulletin_root = match check_bulletin(&bundle, &counted) {
+        Ok(Some(root)) => {
+            report.check("bulletin", Ok(format!("{} ballots hash to root {}", counted.len(), ballot_log::to_hex(&root))));
+            Some(root)
+        }
+        Ok(None) => {
+            report.skip("bulletin", "tally predates the bulletin board");
+            None
+        }
+        Err(reason) => {
+            report.check("bulletin", Err(reason));
+            None
+        }
+    };
+
+    // Recount exactly as tally_handlers does
+    let ballots: Vec<Ballot> = counted
+        .iter()
+        .map(|s| Ballot {
+            weight: s.weight as u64,
+            ranking: serde_json::from_value(s.ballot.clone()).unwrap_or_default(),
+        })
+        .collect();
+
+    let (eligible_voters, eligible_weight) = bundle.eligible_totals();
+    match tally::compute_quorum(&bundle.proposal.quorum_basis, bundle.proposal.quorum, eligible_voters, eligible_weight, &ballots) {
+        Some(quorum) if serde_json::to_value(&quorum).ok().as_ref() == Some(&tally.quorum_json) => {
+            report.check("quorum", Ok(format!("turnout {} of {} eligible {}", quorum.turnout, quorum.eligible, quorum.basis)))
+        }
+        Some(quorum) => report.check("quorum", Err(format!("recomputed {}", serde_json::json!(quorum)))),
+        None => report.check("quorum", Err(format!("unknown quorum basis {}", bundle.proposal.quorum_basis))),
+    }
+
+    let engine = tally::engine_for(&bundle.proposal.model_enum);
+    let choices = tally::parse_choices(&bundle.proposal.choices_json);
+    let results = match (engine, choices) {
+        // Secret ballots are counted from trustee decryptions the bundle doesn't carry
+        _ if counted.iter().any(|s| s.encrypted_ballot.is_some()) => {
+            report.skip("results", "secret ballots can only be counted by the election trustees");
+            None
+        }
+        (Some(engine), Some(choices)) => {
+            let results = engine.tally(&choices, &ballots);
+            if serde_json::to_value(&results).ok().as_ref() == Some(&tally.results_json) {
+                report.check("results", Ok(format!("{} ballots recount to the published results", results.total_votes)));
+            } else {
+                report.check("results", Err(format!("recount gives {}", serde_json::json!(results))));
+            }
+            Some(results)
+        }
+        (None, _) => {
+            report.check("results", Err(format!("unknown voting model {}", bundle.proposal.model_enum)));
+            None
+        }
+        (_, None) => {
+            report.check("results", Err("proposal choices are invalid".to_string()));
+            None
+        }
+    };
+
+    match (&tally.aggregate_proof_hash, &tally.aggregate_program_hash, bulletin_root) {
+        (Some(proof_hash), Some(program_hash), Some(root)) => {
+            let public_inputs = TallyPublicInputs::new(&bundle.proposal.id, &root, counted.len() as u64).to_ints();
+            // Without a recount, the proof can still bind the ballots to the outputs the server recorded
+            let stack_outputs = match &results {
+                Some(results) => verifier::results_commitment(results).to_vec(),
+                None => tally.aggregate_stack_outputs.clone().unwrap_or_default(),
+            };
+            let outcome = expect_program(&options.tally_program_hash, program_hash).and_then(|_| {
+                let proof_bytes = bundle
+                    .proof_bytes(proof_hash)
+                    .ok_or_else(|| format!("proof {} is missing or does not match its hash", proof_hash))?;
+                let verification = verifier::verify_proof(&ProgramProof {
+                    proof_bytes: &proof_bytes,
+                    program_hash,
+                    public_inputs: &public_inputs,
+                    stack_outputs: &stack_outputs,
+                });
+                match verification.verified {
+                    true => Ok(format!("verifies for program {}", program_hash)),
+                    false => Err(verification.failure_reason.unwrap_or_else(|| "does not verify".to_string())),
+                }
+            });
+            report.check("aggregate proof", outcome);
+        }
+        (Some(_), _, None) => report.skip("aggregate proof", "no verified bulletin root to check it against"),
+        _ => report.skip("aggregate proof", "tally predates aggregate proofs"),
+    }
+
+    finish(report)
+}
+
+fn finish(report: Report) -> ExitCode {
+    if report.failures == 0 {
+        println!("RESULT: PASS");
+        ExitCode::SUCCESS
+    } else {
+        println!("RESULT: FAIL ({} failed)", report.failures);
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,294 @@
+use chrono::Utc;
+use sqlx::{PgConnection, PgPool};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::ballot_log::{self, chain_hash, extend_frontier, frontier_root, from_hex, to_hex, EntryData, Hash, GENESIS_CHAIN};
+use crate::models::{BulletinEntry, BulletinRoot, Submission};
+
+// Append-only bulletin board of counted ballots, one log per proposal, hashed as in `ballot_log`.
+// Roots are published periodically and at tally time; inclusion and consistency proofs are served
+// against published roots only, so the operator can't rewrite history it has already committed to.
+// Nothing the log references can be deleted: once a proposal has an entry or a published root, neither
+// it nor its project can be removed.
+
+// How often grown logs get a new published root
+const PUBLISH_INTERVAL_SECS: u64 = 60;
+
+/// The leaf a counted submission is logged under; `None` for rejected ballots.
+pub fn submission_leaf(submission: &Submission) -> Option<Hash> {
+    let data = EntryData {
+        index: submission.inclusion_index?,
+        submission_id: submission.id,
+        proposal_id: submission.proposal_id,
+        nullifier_hash: &submission.nullifier_hash,
+        proof_hash: &submission.proof_hash,
+        weight: submission.weight,
+        ballot: &submission.ballot,
+        encrypted_ballot: &submission.encrypted_ballot,
+    };
+    data.leaf()
+}
+
+fn frontier_from_json(value: &serde_json::Value) -> Vec<Hash> {
+    serde_json::from_value::<Vec<String>>(value.clone())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|h| from_hex(h))
+        .collect()
+}
+
+/// Appends a counted submission to its proposal's log. Runs in the submitting transaction, which holds
+/// the proposal row lock, so entries are appended strictly in `inclusion_index` order.
+pub async fn append(conn: &mut PgConnection, submission: &Submission) -> Result<Option<BulletinEntry>, sqlx::Error> {
+    let (Some(index), Some(leaf)) = (submission.inclusion_index, submission_leaf(submission)) else {
+        return Ok(None);
+    };
+
+    let previous = sqlx::query_as::<_, BulletinEntry>("SELECT * FROM bulletin_entries WHERE proposal_id = $1 AND entry_index = $2")
+        .bind(submission.proposal_id)
+        .bind(index - 1)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    let (previous_chain, mut frontier) = match &previous {
+        Some(p) => (from_hex(&p.chain_hash).unwrap_or(GENESIS_CHAIN), frontier_from_json(&p.frontier)),
+        None => (GENESIS_CHAIN, Vec::new()),
+    };
+    extend_frontier(&mut frontier, index as u64, leaf);
+
+    let entry = sqlx::query_as::<_, BulletinEntry>(
+        "INSERT INTO bulletin_entries (proposal_id, entry_index, submission_id, leaf_hash, chain_hash, root, frontier, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *"
+    )
+    .bind(submission.proposal_id)
+    .bind(index)
+    .bind(submission.id)
+    .bind(to_hex(&leaf))
+    .bind(to_hex(&chain_hash(&previous_chain, &leaf)))
+    .bind(to_hex(&frontier_root(&frontier)))
+    .bind(serde_json::json!(frontier.iter().map(to_hex).collect::<Vec<_>>()))
+    .bind(Utc::now().naive_utc())
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(Some(entry))
+}
+
+/// Leaf hashes of the first `size` entries of a proposal's log.
+pub async fn leaves(conn: &mut PgConnection, proposal_id: Uuid, size: i64) -> Result<Vec<Hash>, sqlx::Error> {
+    let hashes = sqlx::query_scalar::<_, String>("SELECT leaf_hash FROM bulletin_entries WHERE proposal_id = $1 AND entry_index < $2 ORDER BY entry_index")
+        .bind(proposal_id)
+        .bind(size)
+        .fetch_all(&mut *conn)
+        .await?;
+    Ok(hashes.iter().filter_map(|h| from_hex(h)).collect())
+}
+
+/// Publishes the current head of a proposal's log, if it isn't already, and returns it.
+pub async fn publish(conn: &mut PgConnection, proposal_id: Uuid) -> Result<BulletinRoot, sqlx::Error> {
+    let head = sqlx::query_as::<_, BulletinEntry>("SELECT * FROM bulletin_entries WHERE proposal_id = $1 ORDER BY entry_index DESC LIMIT 1")
+        .bind(proposal_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    let (size, root, chain) = match head {
+        Some(e) => (e.entry_index + 1, e.root, e.chain_hash),
+        None => (0, to_hex(&ballot_log::root(&[])), to_hex(&GENESIS_CHAIN)),
+    };
+
+    sqlx::query("INSERT INTO bulletin_roots (proposal_id, tree_size, root, chain_hash, published_at) VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING")
+        .bind(proposal_id)
+        .bind(size)
+        .bind(&root)
+        .bind(&chain)
+        .bind(Utc::now().naive_utc())
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query_as::<_, BulletinRoot>("SELECT * FROM bulletin_roots WHERE proposal_id = $1 AND tree_size = $2")
+        .bind(proposal_id)
+        .bind(size)
+        .fetch_one(&mut *conn)
+        .await
+}
+
+/// Publishes a root for every log that grew since its last published root.
+pub async fn publish_grown(pool: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO bulletin_roots (proposal_id, tree_size, root, chain_hash, published_at) \
+         SELECT e.proposal_id, e.entry_index + 1, e.root, e.chain_hash, $1 FROM bulletin_entries e \
+         WHERE e.entry_index = (SELECT MAX(entry_index) FROM bulletin_entries WHERE proposal_id = e.proposal_id) \
+         ON CONFLICT DO NOTHING"
+    )
+    .bind(Utc::now().naive_utc())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Logs the counted ballots of proposals that predate the bulletin board, queued by its migration.
+/// Must finish before the server accepts ballots, which would otherwise be appended to an empty log.
+pub async fn backfill(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let proposal_ids = sqlx::query_scalar::<_, Uuid>("SELECT proposal_id FROM bulletin_backfill")
+        .fetch_all(pool)
+        .await?;
+
+    for proposal_id in proposal_ids {
+        let mut transaction = pool.begin().await?;
+        sqlx::query("SELECT 1 FROM proposals WHERE id = $1 FOR UPDATE")
+            .bind(proposal_id)
+            .execute(&mut *transaction)
+            .await?;
+        let submissions = sqlx::query_as::<_, Submission>(
+            "SELECT * FROM submissions WHERE proposal_id = $1 AND verified_bool = TRUE ORDER BY inclusion_index"
+        )
+        .bind(proposal_id)
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        for submission in &submissions {
+            append(&mut transaction, submission).await?;
+        }
+        publish(&mut transaction, proposal_id).await?;
+
+        sqlx::query("DELETE FROM bulletin_backfill WHERE proposal_id = $1")
+            .bind(proposal_id)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+    }
+    Ok(())
+}
+
+pub async fn run_publisher(pool: PgPool) {
+    let mut interval = actix_web::rt::time::interval(Duration::from_secs(PUBLISH_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        if let Err(e) = publish_grown(&pool).await {
+            log::error!("Failed to publish bulletin roots: {}", e);
+        }
+    }
+}
+
+/// Checks the counted submissions, in `inclusion_index` order, are exactly the ballots logged up to
+/// `published`: none added, removed, reordered or edited since.
+pub fn matches(submissions: &[Submission], published: &BulletinRoot) -> bool {
+    let leaves: Option<Vec<Hash>> = submissions.iter().map(submission_leaf).collect();
+    match leaves {
+        Some(leaves) => leaves.len() as i64 == published.tree_size && to_hex(&ballot_log::root(&leaves)) == published.root,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A project with one active proposal, and a counted ballot on it for each inclusion index
+    async fn create_ballots(pool: &PgPool, count: i64) -> (Uuid, Uuid, Vec<Submission>) {
+        let (project_id, proposal_id) = (Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query("INSERT INTO projects (id, owner, token_address, merkle_root) VALUES ($1, '0xowner', '0xtoken', '0x00')")
+            .bind(project_id)
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO proposals (id, project_id, title, choices_json, model_enum, quorum, start_ts, end_ts, state) \
+             VALUES ($1, $2, 'Proposal', '[\"yes\", \"no\"]', 'one-person-one-vote', 0, '2020-01-01', '2099-01-01', 'active')",
+        )
+        .bind(proposal_id)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO proofs (proof_hash, proof_bytes) VALUES ('proof', '')").execute(pool).await.unwrap();
+
+        let mut submissions = Vec::new();
+        for index in 0..count {
+            let submission = sqlx::query_as::<_, Submission>(
+                "INSERT INTO submissions (id, proposal_id, proof_hash, program_hash, public_inputs, stack_outputs, note_commitment, \
+                 nullifier_hash, weight, ballot, verified_bool, verified_at, inclusion_index) \
+                 VALUES ($1, $2, 'proof', '0x00', '[]', '[]', 'note', $3, $4, $5, TRUE, $6, $7) RETURNING *",
+            )
+            .bind(Uuid::new_v4())
+            .bind(proposal_id)
+            .bind(format!("nullifier-{}", index))
+            .bind(index + 1)
+            .bind(serde_json::json!([index % 2]))
+            .bind(Utc::now().naive_utc())
+            .bind(index)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+            submissions.push(submission);
+        }
+        (project_id, proposal_id, submissions)
+    }
+
+    #[sqlx::test]
+    async fn appended_entries_extend_the_chain_and_the_tree(pool: PgPool) {
+        let (_, proposal_id, submissions) = create_ballots(&pool, 7).await;
+        let mut conn = pool.acquire().await.unwrap();
+
+        let mut chain = GENESIS_CHAIN;
+        let mut logged = Vec::new();
+        for submission in &submissions {
+            let entry = append(&mut conn, submission).await.unwrap().unwrap();
+            let leaf = submission_leaf(submission).unwrap();
+            chain = chain_hash(&chain, &leaf);
+            logged.push(leaf);
+
+            assert_eq!(entry.leaf_hash, to_hex(&leaf));
+            assert_eq!(entry.chain_hash, to_hex(&chain));
+            assert_eq!(entry.root, to_hex(&ballot_log::root(&logged)));
+        }
+        assert_eq!(leaves(&mut conn, proposal_id, 4).await.unwrap(), logged[..4]);
+
+        let published = publish(&mut conn, proposal_id).await.unwrap();
+        assert_eq!(published.tree_size, 7);
+        assert_eq!(published.chain_hash, to_hex(&chain));
+        assert!(matches(&submissions, &published));
+
+        // Any edit, omission or reordering of the counted ballots breaks the match
+        let mut edited = submissions.clone();
+        edited[2].weight += 1;
+        assert!(!matches(&edited, &published));
+        assert!(!matches(&submissions[..6], &published));
+        let mut reordered = submissions.clone();
+        reordered.swap(0, 1);
+        assert!(!matches(&reordered, &published));
+    }
+
+    #[sqlx::test]
+    async fn rejected_ballots_are_not_logged(pool: PgPool) {
+        let (_, _, mut submissions) = create_ballots(&pool, 1).await;
+        submissions[0].inclusion_index = None;
+        let mut conn = pool.acquire().await.unwrap();
+
+        assert!(append(&mut conn, &submissions[0]).await.unwrap().is_none());
+    }
+
+    #[sqlx::test]
+    async fn logged_ballots_cannot_be_removed(pool: PgPool) {
+        let (project_id, proposal_id, submissions) = create_ballots(&pool, 2).await;
+        let mut conn = pool.acquire().await.unwrap();
+        for submission in &submissions {
+            append(&mut conn, submission).await.unwrap();
+        }
+        publish(&mut conn, proposal_id).await.unwrap();
+
+        for statement in [
+            "DELETE FROM bulletin_entries WHERE proposal_id = $1",
+            "UPDATE bulletin_entries SET leaf_hash = '00' WHERE proposal_id = $1",
+            "DELETE FROM bulletin_roots WHERE proposal_id = $1",
+            "DELETE FROM submissions WHERE proposal_id = $1",
+            "DELETE FROM proposals WHERE id = $1",
+        ] {
+            let result = sqlx::query(statement).bind(proposal_id).execute(&mut *conn).await;
+            assert!(result.is_err(), "{}", statement);
+        }
+        let result = sqlx::query("DELETE FROM projects WHERE id = $1").bind(project_id).execute(&mut *conn).await;
+        assert!(result.is_err());
+
+        assert_eq!(leaves(&mut conn, proposal_id, 2).await.unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,114 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+// Proposal bundles are exported as canonical JSON: object keys sorted, no insignificant whitespace,
+// arrays in a fixed order (eligibility by leaf index, counted submissions by inclusion index, then
+// rejected ones by id). Readers must reject a version they don't know.
+pub const FORMAT: &str = "miden-voting-bundle";
+pub const VERSION: u32 = 1;
+
+// Bundle: A proposal with everything needed to re-check its outcome offline: the eligibility snapshot
+// voters proved against, every submission, the tally, and the proofs it counted. Records carry the
+// fields of the server's JSON for each row that verification reads; any others are ignored.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Bundle {
+    pub format: String,
+    pub version: u32,
+    pub project: ProjectRecord,
+    pub proposal: ProposalRecord,
+    pub eligibility: Vec<EligibilityRecord>,
+    pub submissions: Vec<SubmissionRecord>,
+    pub tally: Option<TallyRecord>,
+    pub proofs: BTreeMap<String, String>, // Hex-encoded proof bytes, keyed by their SHA-256 as in the `proofs` table
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProjectRecord {
+    pub id: Uuid,
+    pub merkle_root: String, // Root of the eligibility snapshot
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProposalRecord {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub title: String,
+    pub choices_json: serde_json::Value,
+    pub model_enum: String,
+    pub quorum: f64,
+    pub quorum_basis: String,
+    // Fixed when the proposal opened; absent in bundles of proposals opened before they were kept
+    pub eligibility_root: Option<String>,
+    pub eligible_voters: Option<i64>,
+    pub eligible_weight: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EligibilityRecord {
+    pub leaf_index: i32,
+    pub address: String,
+    pub weight: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubmissionRecord {
+    pub id: Uuid,
+    pub proposal_id: Uuid,
+    pub proof_hash: String,
+    pub program_hash: String,
+    pub public_inputs: Vec<u64>,
+    pub stack_outputs: Vec<u64>,
+    pub nullifier_hash: String,
+    pub weight: i64,
+    pub ballot: serde_json::Value,
+    pub encrypted_ballot: Option<serde_json::Value>,
+    pub verified_bool: bool,
+    pub inclusion_index: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TallyRecord {
+    pub proposal_id: Uuid,
+    pub results_json: serde_json::Value,
+    pub quorum_json: serde_json::Value,
+    pub bulletin_size: Option<i64>,
+    pub bulletin_root: Option<String>,
+    pub aggregate_proof_hash: Option<String>,
+    pub aggregate_program_hash: Option<String>,
+    pub aggregate_public_inputs: Option<Vec<u64>>,
+    pub aggregate_stack_outputs: Option<Vec<u64>>,
+}
+
+impl Bundle {
+    /// Checks the bundle is in a format this build reads.
+    pub fn check_version(format: &str, version: u32) -> Result<(), String> {
+        if format != FORMAT {
+            return Err(format!("Not a proposal bundle (format {:?})", format));
+        }
+        if version != VERSION {
+            return Err(format!("Unsupported bundle version {}, expected {}", version, VERSION));
+        }
+        Ok(())
+    }
+
+    /// Root of the snapshot ballots were proved against: the one the proposal opened with, else the project's.
+    pub fn eligibility_root(&self) -> &str {
+        self.proposal.eligibility_root.as_deref().unwrap_or(&self.project.merkle_root)
+    }
+
+    /// Voter count and total weight quorum is measured against, from the proposal's snapshot when it
+    /// has one, else from the eligibility leaves.
+    pub fn eligible_totals(&self) -> (u64, u64) {
+        match (self.proposal.eligible_voters, self.proposal.eligible_weight) {
+            (Some(voters), Some(weight)) => (voters as u64, weight as u64),
+            _ => (self.eligibility.len() as u64, self.eligibility.iter().map(|l| l.weight as u64).sum()),
+        }
+    }
+
+    /// Bytes of a referenced proof, if the bundle carries it under its correct content address.
+    pub fn proof_bytes(&self, proof_hash: &str) -> Option<Vec<u8>> {
+        let bytes = hex::decode(self.proofs.get(proof_hash)?.trim_start_matches("0x")).ok()?;
+        (crate::verifier::proof_hash(&bytes) == proof_hash).then_some(bytes)
+    }
+}
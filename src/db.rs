@@ -1,6 +1,11 @@
 use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
 use std::env;
 
+use crate::models::{
+    AuthChallenge, BulletinEntry, BulletinRoot, Election, ElectionTrustee, EligibilityLeaf, Project, ProjectMember, Proposal,
+    RefreshToken, RevokedToken, Submission, Tally, TableSchema, User, Wallet, Webhook, WebhookDelivery,
+};
+
 pub type DbPool = Pool<Postgres>;
 
 pub async fn init_db() -> Result<DbPool, sqlx::Error> {
@@ -18,5 +23,62 @@ pub async fn init_db() -> Result<DbPool, sqlx::Error> {
         .run(&pool)
         .await?;
 
+    // Refuse to start if the migrated schema can't be decoded into the models
+    let mut mismatches = Vec::new();
+    mismatches.extend(check_table::<User>(&pool).await?);
+    mismatches.extend(check_table::<Wallet>(&pool).await?);
+    mismatches.extend(check_table::<AuthChallenge>(&pool).await?);
+    mismatches.extend(check_table::<RefreshToken>(&pool).await?);
+    mismatches.extend(check_table::<RevokedToken>(&pool).await?);
+    mismatches.extend(check_table::<Project>(&pool).await?);
+    mismatches.extend(check_table::<ProjectMember>(&pool).await?);
+    mismatches.extend(check_table::<EligibilityLeaf>(&pool).await?);
+    mismatches.extend(check_table::<Proposal>(&pool).await?);
+    mismatches.extend(check_table::<Submission>(&pool).await?);
+    mismatches.extend(check_table::<BulletinEntry>(&pool).await?);
+    mismatches.extend(check_table::<BulletinRoot>(&pool).await?);
+    mismatches.extend(check_table::<Election>(&pool).await?);
+    mismatches.extend(check_table::<ElectionTrustee>(&pool).await?);
+    mismatches.extend(check_table::<Tally>(&pool).await?);
+    mismatches.extend(check_table::<Webhook>(&pool).await?);
+    mismatches.extend(check_table::<WebhookDelivery>(&pool).await?);
+
+    if !mismatches.is_empty() {
+        return Err(sqlx::Error::Configuration(
+            format!("Database schema does not match models: {}", mismatches.join("; ")).into(),
+        ));
+    }
+
     Ok(pool)
 }
+
+async fn check_table<T: TableSchema>(pool: &DbPool) -> Result<Vec<String>, sqlx::Error> {
+    let columns: Vec<(String, String, String)> = sqlx::query_as(
+        "SELECT column_name::TEXT, data_type::TEXT, is_nullable::TEXT FROM information_schema.columns WHERE table_schema = current_schema() AND table_name = $1"
+    )
+    .bind(T::TABLE)
+    .fetch_all(pool)
+    .await?;
+
+    if columns.is_empty() {
+        return Ok(vec![format!("table {} is missing", T::TABLE)]);
+    }
+
+    let mut mismatches = Vec::new();
+    for (name, data_type, nullable) in T::COLUMNS {
+        match columns.iter().find(|(column, _, _)| column == name) {
+            None => mismatches.push(format!("{}.{} is missing", T::TABLE, name)),
+            Some((_, actual_type, _)) if actual_type != data_type => mismatches.push(format!(
+                "{}.{} is {} but the model expects {}",
+                T::TABLE, name, actual_type, data_type
+            )),
+            Some((_, _, is_nullable)) if (is_nullable == "YES") != *nullable => mismatches.push(format!(
+                "{}.{} nullability differs from the model",
+                T::TABLE, name
+            )),
+            _ => {}
+        }
+    }
+
+    Ok(mismatches)
+}
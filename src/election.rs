@@ -0,0 +1,597 @@
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT as G;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+// Exponential ElGamal over Ristretto with a t-of-n threshold election key.
+// A ballot encrypts a 0/1 selection per choice; the server only ever decrypts
+// weighted sums of ballots, never an individual ballot.
+// The key comes from a Feldman key generation among the trustees: each deals shares of a random
+// polynomial to the others, so no one, the server included, ever holds the election's secret.
+
+// Weighted totals are kept in fixed point so non-integer voting power (quadratic) can be summed
+pub const POWER_SCALE: f64 = 1000.0;
+
+// Decryption recovers m from m*G by baby-step giant-step, so totals must stay below 2^40
+const BABY_STEPS: u64 = 1 << 18;
+const GIANT_STEPS: u64 = 1 << 22;
+pub const MAX_TOTAL: u64 = BABY_STEPS * GIANT_STEPS - 1;
+
+// Every model's voting power is at most max(weight, 1), so ballots whose weights, each counted as at
+// least 1, sum to no more than this keep every aggregate (scaled power included) decryptable
+pub const MAX_WEIGHT_TOTAL: u64 = MAX_TOTAL / POWER_SCALE as u64;
+
+// Ciphertexts aggregated per choice, in this order
+pub const AGGREGATES_PER_CHOICE: usize = 3; // votes, raw weight, scaled voting power
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CiphertextHex {
+    pub a: String,
+    pub b: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitProof {
+    pub c0: String,
+    pub c1: String,
+    pub z0: String,
+    pub z1: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DleqProof {
+    pub c: String,
+    pub z: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedChoice {
+    #[serde(flatten)]
+    pub ciphertext: CiphertextHex,
+    pub proof: BitProof, // The choice encrypts 0 or 1
+}
+
+// EncryptedBallot: Stored as Submission.encrypted_ballot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedBallot {
+    pub choices: Vec<EncryptedChoice>,
+    pub sum_proof: DleqProof, // Exactly one choice encrypts 1
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecryptionShare {
+    pub d: String,
+    pub proof: DleqProof, // log_G(public_share) == log_A(d)
+}
+
+// EncryptedShare: A share f(j) dealt to trustee j, encrypted to their transport key E_j as
+// (r * G, f(j) + H(r * E_j))
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedShare {
+    pub r: String,
+    pub c: String,
+}
+
+impl EncryptedShare {
+    pub fn parse(&self) -> Option<(RistrettoPoint, Scalar)> {
+        Some((point_from_hex(&self.r)?, scalar_from_hex(&self.c)?))
+    }
+}
+
+// ShareDisclosure: The pad key of a share dealt to a trustee, disclosed to show the dealer cheated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareDisclosure {
+    pub key: String,
+    pub proof: DleqProof, // log_G(transport_key) == log_R(key)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ciphertext {
+    pub a: RistrettoPoint,
+    pub b: RistrettoPoint,
+}
+
+impl Ciphertext {
+    fn zero() -> Self {
+        Ciphertext { a: RistrettoPoint::identity(), b: RistrettoPoint::identity() }
+    }
+
+    fn add(&self, other: &Ciphertext) -> Self {
+        Ciphertext { a: self.a + other.a, b: self.b + other.b }
+    }
+
+    fn scale(&self, k: u64) -> Self {
+        let k = Scalar::from(k);
+        Ciphertext { a: self.a * k, b: self.b * k }
+    }
+
+    pub fn parse(hex: &CiphertextHex) -> Option<Self> {
+        Some(Ciphertext { a: point_from_hex(&hex.a)?, b: point_from_hex(&hex.b)? })
+    }
+
+    pub fn to_hex(&self) -> CiphertextHex {
+        CiphertextHex { a: point_to_hex(&self.a), b: point_to_hex(&self.b) }
+    }
+}
+
+pub fn point_to_hex(point: &RistrettoPoint) -> String {
+    hex::encode(point.compress().as_bytes())
+}
+
+pub fn point_from_hex(value: &str) -> Option<RistrettoPoint> {
+    let bytes = hex::decode(value).ok()?;
+    CompressedRistretto::from_slice(&bytes).ok()?.decompress()
+}
+
+fn scalar_from_hex(value: &str) -> Option<Scalar> {
+    let bytes: [u8; 32] = hex::decode(value).ok()?.try_into().ok()?;
+    Option::from(Scalar::from_canonical_bytes(bytes))
+}
+
+/// What a ballot's proofs are bound to: the proposal it's cast on and the voter's nullifier there, so
+/// the proofs can't be replayed with another proposal's or voter's ballot.
+pub fn ballot_context(proposal_id: &Uuid, nullifier: &[u64; 4]) -> Vec<u8> {
+    let mut context = proposal_id.as_bytes().to_vec();
+    for limb in nullifier {
+        context.extend(limb.to_be_bytes());
+    }
+    context
+}
+
+fn challenge(domain: &[u8], context: &[u8], points: &[&RistrettoPoint]) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(domain);
+    hasher.update(context);
+    for p in points {
+        hasher.update(p.compress().as_bytes());
+    }
+    Scalar::from_bytes_mod_order_wide(&hasher.finalize().into())
+}
+
+/// Verifies a Chaum-Pedersen proof that log_g1(x) == log_g2(y), made for `context`.
+fn verify_dleq(domain: &[u8], context: &[u8], g1: &RistrettoPoint, x: &RistrettoPoint, g2: &RistrettoPoint, y: &RistrettoPoint, proof: &DleqProof) -> bool {
+    let (c, z) = match (scalar_from_hex(&proof.c), scalar_from_hex(&proof.z)) {
+        (Some(c), Some(z)) => (c, z),
+        _ => return false,
+    };
+    let t1 = g1 * z - x * c;
+    let t2 = g2 * z - y * c;
+    challenge(domain, context, &[g1, x, g2, y, &t1, &t2]) == c
+}
+
+/// Verifies a disjunctive proof that `ct` encrypts 0 or 1 under `public_key`, made for `context`.
+fn verify_bit(public_key: &RistrettoPoint, ct: &Ciphertext, context: &[u8], proof: &BitProof) -> bool {
+    let scalars = [&proof.c0, &proof.c1, &proof.z0, &proof.z1].map(|s| scalar_from_hex(s));
+    let [Some(c0), Some(c1), Some(z0), Some(z1)] = scalars else {
+        return false;
+    };
+
+    let b_minus_g = ct.b - G;
+    let a0 = G * z0 - ct.a * c0;
+    let b0 = public_key * z0 - ct.b * c0;
+    let a1 = G * z1 - ct.a * c1;
+    let b1 = public_key * z1 - b_minus_g * c1;
+
+    challenge(b"miden-vote/bit", context, &[public_key, &ct.a, &ct.b, &a0, &b0, &a1, &b1]) == c0 + c1
+}
+
+/// Parses a ballot and checks every choice is 0/1 and exactly one is selected, with proofs made for
+/// `context` (see `ballot_context`).
+pub fn verify_ballot(public_key: &RistrettoPoint, ballot: &EncryptedBallot, choice_count: usize, context: &[u8]) -> Result<Vec<Ciphertext>, String> {
+    if ballot.choices.len() != choice_count {
+        return Err(format!("Encrypted ballot must have {} choices", choice_count));
+    }
+
+    let mut ciphertexts = Vec::with_capacity(choice_count);
+    for choice in &ballot.choices {
+        let ct = Ciphertext::parse(&choice.ciphertext).ok_or("Malformed ciphertext")?;
+        if !verify_bit(public_key, &ct, context, &choice.proof) {
+            return Err("Invalid choice proof".to_string());
+        }
+        ciphertexts.push(ct);
+    }
+
+    let sum = ciphertexts.iter().fold(Ciphertext::zero(), |acc, ct| acc.add(ct));
+    if !verify_dleq(b"miden-vote/sum", context, &G, &sum.a, public_key, &(sum.b - G), &ballot.sum_proof) {
+        return Err("Invalid ballot sum proof".to_string());
+    }
+
+    Ok(ciphertexts)
+}
+
+/// Canonical bytes of a ballot's ciphertexts, committed to by the vote proof.
+pub fn ballot_bytes(ciphertexts: &[Ciphertext]) -> Vec<u8> {
+    ciphertexts
+        .iter()
+        .flat_map(|ct| [ct.a.compress().to_bytes(), ct.b.compress().to_bytes()])
+        .flatten()
+        .collect()
+}
+
+/// Homomorphically sums ballots into AGGREGATES_PER_CHOICE ciphertexts per choice.
+/// Each entry is (raw weight, scaled voting power, per-choice ciphertexts).
+pub fn aggregate(choice_count: usize, ballots: &[(u64, u64, Vec<Ciphertext>)]) -> Vec<Ciphertext> {
+    let mut totals = vec![Ciphertext::zero(); choice_count * AGGREGATES_PER_CHOICE];
+    for (weight, power, ciphertexts) in ballots {
+        for (j, ct) in ciphertexts.iter().enumerate() {
+            let base = j * AGGREGATES_PER_CHOICE;
+            totals[base] = totals[base].add(ct);
+            totals[base + 1] = totals[base + 1].add(&ct.scale(*weight));
+            totals[base + 2] = totals[base + 2].add(&ct.scale(*power));
+        }
+    }
+    totals
+}
+
+fn lagrange_at(x: u64, index: u64, indices: &[u64]) -> Scalar {
+    indices
+        .iter()
+        .filter(|&&j| j != index)
+        .fold(Scalar::ONE, |acc, &j| {
+            let num = Scalar::from(x) - Scalar::from(j);
+            let den = Scalar::from(index) - Scalar::from(j);
+            acc * num * den.invert()
+        })
+}
+
+/// What a trustee's dealing is bound to: the proposal's key generation and the dealer's index.
+pub fn dealing_context(proposal_id: &Uuid, dealer: u64) -> Vec<u8> {
+    let mut context = proposal_id.as_bytes().to_vec();
+    context.extend(dealer.to_be_bytes());
+    context
+}
+
+/// Commitment to f(x) of a dealing polynomial from its Feldman commitments a_k * G.
+pub fn commitment_at(commitments: &[RistrettoPoint], x: u64) -> RistrettoPoint {
+    let x = Scalar::from(x);
+    commitments.iter().rev().fold(RistrettoPoint::identity(), |acc, c| acc * x + c)
+}
+
+/// Checks a dealer's proof of knowing a_0 for their constant-term commitment, so no dealer can pick
+/// theirs to cancel out the others'.
+pub fn verify_dealing(commitments: &[RistrettoPoint], proof: &DleqProof, context: &[u8]) -> bool {
+    commitments
+        .first()
+        .is_some_and(|c0| verify_dleq(b"miden-vote/dealing", context, &G, c0, &G, c0, proof))
+}
+
+/// Checks a dealt share f(index) against the dealer's commitments.
+pub fn verify_dealt_share(commitments: &[RistrettoPoint], index: u64, share: &Scalar) -> bool {
+    G * share == commitment_at(commitments, index)
+}
+
+fn share_pad(key: &RistrettoPoint, context: &[u8]) -> Scalar {
+    challenge(b"miden-vote/deal", context, &[key])
+}
+
+/// Opens a dealt share with the pad key its recipient disclosed, if the disclosure proves the key
+/// was derived with `transport_key`.
+pub fn disclosed_share(transport_key: &RistrettoPoint, share: &EncryptedShare, disclosure: &ShareDisclosure, context: &[u8]) -> Option<Scalar> {
+    let (r, c) = share.parse()?;
+    let key = point_from_hex(&disclosure.key)?;
+    verify_dleq(b"miden-vote/disclosure", context, &G, transport_key, &r, &key, &disclosure.proof).then(|| c - share_pad(&key, context))
+}
+
+/// The election key: the sum of the qualified dealers' constant-term commitments.
+pub fn election_key(dealings: &[Vec<RistrettoPoint>]) -> RistrettoPoint {
+    dealings.iter().filter_map(|c| c.first()).sum()
+}
+
+/// Trustee `index`'s public share x_i * G, where x_i is the sum of the shares the qualified dealers
+/// dealt them.
+pub fn public_share(dealings: &[Vec<RistrettoPoint>], index: u64) -> RistrettoPoint {
+    dealings.iter().map(|c| commitment_at(c, index)).sum()
+}
+
+/// Checks a trustee's decryption share of `ct` against their public share.
+pub fn verify_share(public_share: &RistrettoPoint, ct: &Ciphertext, share: &DecryptionShare) -> Option<RistrettoPoint> {
+    let d = point_from_hex(&share.d)?;
+    verify_dleq(b"miden-vote/share", &[], &G, public_share, &ct.a, &d, &share.proof).then_some(d)
+}
+
+/// Combines verified shares from at least `threshold` trustees and decrypts every aggregate.
+/// `shares` maps trustee index to that trustee's share of each aggregate, in order.
+pub fn decrypt_aggregates(aggregates: &[Ciphertext], shares: &[(u64, Vec<RistrettoPoint>)]) -> Result<Vec<u64>, String> {
+    let indices: Vec<u64> = shares.iter().map(|(i, _)| *i).collect();
+    let table = baby_steps();
+
+    aggregates
+        .iter()
+        .enumerate()
+        .map(|(k, ct)| {
+            let combined: RistrettoPoint = shares
+                .iter()
+                .map(|(i, d)| d[k] * lagrange_at(0, *i, &indices))
+                .sum();
+            discrete_log(&table, &(ct.b - combined)).ok_or_else(|| "Aggregate total is out of the decryptable range".to_string())
+        })
+        .collect()
+}
+
+fn baby_steps() -> HashMap<[u8; 32], u64> {
+    let mut table = HashMap::with_capacity(BABY_STEPS as usize);
+    let mut point = RistrettoPoint::identity();
+    for j in 0..BABY_STEPS {
+        table.insert(point.compress().to_bytes(), j);
+        point += G;
+    }
+    table
+}
+
+fn discrete_log(table: &HashMap<[u8; 32], u64>, target: &RistrettoPoint) -> Option<u64> {
+    let giant = G * Scalar::from(BABY_STEPS);
+    let mut point = *target;
+    for i in 0..GIANT_STEPS {
+        if let Some(j) = table.get(&point.compress().to_bytes()) {
+            return Some(i * BABY_STEPS + j);
+        }
+        point -= giant;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+    use std::sync::OnceLock;
+
+    fn random_scalar() -> Scalar {
+        let mut bytes = [0u8; 64];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        Scalar::from_bytes_mod_order_wide(&bytes)
+    }
+
+    fn scalar_hex(s: &Scalar) -> String {
+        hex::encode(s.as_bytes())
+    }
+
+    fn table() -> &'static HashMap<[u8; 32], u64> {
+        static TABLE: OnceLock<HashMap<[u8; 32], u64>> = OnceLock::new();
+        TABLE.get_or_init(baby_steps)
+    }
+
+    fn encrypt(public_key: &RistrettoPoint, m: u64, r: &Scalar) -> Ciphertext {
+        Ciphertext { a: G * r, b: public_key * r + G * Scalar::from(m) }
+    }
+
+    // Proves log_g1(x) == log_g2(y) == secret
+    fn prove_dleq(domain: &[u8], context: &[u8], g1: &RistrettoPoint, g2: &RistrettoPoint, secret: &Scalar) -> DleqProof {
+        let w = random_scalar();
+        let (x, y) = (g1 * secret, g2 * secret);
+        let c = challenge(domain, context, &[g1, &x, g2, &y, &(g1 * w), &(g2 * w)]);
+        DleqProof { c: scalar_hex(&c), z: scalar_hex(&(w + c * secret)) }
+    }
+
+    // Proves `ct` = encrypt(m, r) holds 0 or 1, simulating the branch it doesn't
+    fn prove_bit(public_key: &RistrettoPoint, ct: &Ciphertext, m: u64, r: &Scalar, context: &[u8]) -> BitProof {
+        let (w, c_sim, z_sim) = (random_scalar(), random_scalar(), random_scalar());
+        let b_minus_g = ct.b - G;
+        let (a0, b0, a1, b1) = if m == 0 {
+            (G * w, public_key * w, G * z_sim - ct.a * c_sim, public_key * z_sim - b_minus_g * c_sim)
+        } else {
+            (G * z_sim - ct.a * c_sim, public_key * z_sim - ct.b * c_sim, G * w, public_key * w)
+        };
+        let c = challenge(b"miden-vote/bit", context, &[public_key, &ct.a, &ct.b, &a0, &b0, &a1, &b1]);
+        let (c_real, z_real) = (c - c_sim, w + (c - c_sim) * r);
+        let [c0, c1, z0, z1] = if m == 0 { [c_real, c_sim, z_real, z_sim] } else { [c_sim, c_real, z_sim, z_real] };
+        BitProof { c0: scalar_hex(&c0), c1: scalar_hex(&c1), z0: scalar_hex(&z0), z1: scalar_hex(&z1) }
+    }
+
+    fn encrypt_ballot(public_key: &RistrettoPoint, selection: &[u64], context: &[u8]) -> EncryptedBallot {
+        let randomness: Vec<Scalar> = selection.iter().map(|_| random_scalar()).collect();
+        let choices = selection
+            .iter()
+            .zip(&randomness)
+            .map(|(&m, r)| {
+                let ct = encrypt(public_key, m, r);
+                EncryptedChoice { ciphertext: ct.to_hex(), proof: prove_bit(public_key, &ct, m, r, context) }
+            })
+            .collect();
+        let total_r: Scalar = randomness.iter().sum();
+        EncryptedBallot { choices, sum_proof: prove_dleq(b"miden-vote/sum", context, &G, public_key, &total_r) }
+    }
+
+    fn evaluate(coefficients: &[Scalar], x: u64) -> Scalar {
+        coefficients.iter().rev().fold(Scalar::ZERO, |acc, c| acc * Scalar::from(x) + c)
+    }
+
+    // Shares f(1..=n) of a random degree t-1 polynomial, and f(0)
+    fn deal(threshold: usize, trustees: u64) -> (Scalar, Vec<Scalar>) {
+        let coefficients: Vec<Scalar> = (0..threshold).map(|_| random_scalar()).collect();
+        (evaluate(&coefficients, 0), (1..=trustees).map(|x| evaluate(&coefficients, x)).collect())
+    }
+
+    fn encrypt_share(transport_key: &RistrettoPoint, share: &Scalar, context: &[u8]) -> EncryptedShare {
+        let r = random_scalar();
+        EncryptedShare { r: point_to_hex(&(G * r)), c: scalar_hex(&(share + share_pad(&(transport_key * r), context))) }
+    }
+
+    fn disclose(transport_secret: &Scalar, share: &EncryptedShare, context: &[u8]) -> ShareDisclosure {
+        let r = point_from_hex(&share.r).unwrap();
+        ShareDisclosure { key: point_to_hex(&(r * transport_secret)), proof: prove_dleq(b"miden-vote/disclosure", context, &G, &r, transport_secret) }
+    }
+
+    fn context(nullifier: u64) -> Vec<u8> {
+        ballot_context(&Uuid::from_u128(7), &[nullifier, 0, 0, 0])
+    }
+
+    #[test]
+    fn dleq_proofs_verify_only_for_their_statement_and_context() {
+        let secret = random_scalar();
+        let g2 = G * random_scalar();
+        let proof = prove_dleq(b"test", b"ctx", &G, &g2, &secret);
+        let (x, y) = (G * secret, g2 * secret);
+
+        assert!(verify_dleq(b"test", b"ctx", &G, &x, &g2, &y, &proof));
+        assert!(!verify_dleq(b"test", b"other", &G, &x, &g2, &y, &proof));
+        assert!(!verify_dleq(b"other", b"ctx", &G, &x, &g2, &y, &proof));
+        assert!(!verify_dleq(b"test", b"ctx", &G, &x, &g2, &(y + G), &proof));
+        let garbled = DleqProof { c: proof.c.clone(), z: "zz".to_string() };
+        assert!(!verify_dleq(b"test", b"ctx", &G, &x, &g2, &y, &garbled));
+    }
+
+    #[test]
+    fn bit_proofs_accept_zero_and_one_only() {
+        let public_key = G * random_scalar();
+        for m in [0, 1] {
+            let r = random_scalar();
+            let ct = encrypt(&public_key, m, &r);
+            assert!(verify_bit(&public_key, &ct, b"ctx", &prove_bit(&public_key, &ct, m, &r, b"ctx")));
+        }
+
+        // An honest prover's transcript for 2 can't satisfy either branch
+        let r = random_scalar();
+        let two = encrypt(&public_key, 2, &r);
+        assert!(!verify_bit(&public_key, &two, b"ctx", &prove_bit(&public_key, &two, 1, &r, b"ctx")));
+
+        // Nor does a valid proof carry over to another ciphertext or context
+        let ct = encrypt(&public_key, 1, &r);
+        let proof = prove_bit(&public_key, &ct, 1, &r, b"ctx");
+        assert!(!verify_bit(&public_key, &encrypt(&public_key, 1, &random_scalar()), b"ctx", &proof));
+        assert!(!verify_bit(&public_key, &ct, b"other", &proof));
+    }
+
+    #[test]
+    fn ballots_are_bound_to_their_proposal_and_nullifier() {
+        let public_key = G * random_scalar();
+        let ballot = encrypt_ballot(&public_key, &[0, 1, 0], &context(1));
+
+        assert_eq!(verify_ballot(&public_key, &ballot, 3, &context(1)).map(|c| c.len()), Ok(3));
+        assert!(verify_ballot(&public_key, &ballot, 3, &context(2)).is_err());
+        let other_proposal = ballot_context(&Uuid::from_u128(8), &[1, 0, 0, 0]);
+        assert!(verify_ballot(&public_key, &ballot, 3, &other_proposal).is_err());
+        assert!(verify_ballot(&public_key, &ballot, 4, &context(1)).is_err());
+    }
+
+    #[test]
+    fn ballots_must_select_exactly_one_choice() {
+        let public_key = G * random_scalar();
+        for selection in [[0, 0, 0], [1, 1, 0]] {
+            let ballot = encrypt_ballot(&public_key, &selection, &context(1));
+            assert_eq!(verify_ballot(&public_key, &ballot, 3, &context(1)).err().as_deref(), Some("Invalid ballot sum proof"));
+        }
+    }
+
+    #[test]
+    fn lagrange_coefficients_interpolate_any_threshold_subset() {
+        let (secret, shares) = deal(3, 5);
+        for subset in [vec![1u64, 2, 3], vec![2, 4, 5], vec![5, 1, 3]] {
+            let recovered: Scalar = subset.iter().map(|&i| shares[i as usize - 1] * lagrange_at(0, i, &subset)).sum();
+            assert_eq!(recovered, secret);
+        }
+        // Too few shares land elsewhere
+        let pair = [1u64, 2];
+        let partial: Scalar = pair.iter().map(|&i| shares[i as usize - 1] * lagrange_at(0, i, &pair)).sum();
+        assert_ne!(partial, secret);
+    }
+
+    #[test]
+    fn key_generation_shares_the_sum_of_the_dealt_secrets() {
+        let (threshold, trustees) = (2, 4u64);
+        let polynomials: Vec<Vec<Scalar>> = (0..trustees).map(|_| (0..threshold).map(|_| random_scalar()).collect()).collect();
+        let dealings: Vec<Vec<RistrettoPoint>> = polynomials.iter().map(|p| p.iter().map(|a| G * a).collect()).collect();
+
+        for (p, commitments) in polynomials.iter().zip(&dealings) {
+            for j in 1..=trustees {
+                assert!(verify_dealt_share(commitments, j, &evaluate(p, j)));
+            }
+            assert!(!verify_dealt_share(commitments, 1, &(evaluate(p, 1) + Scalar::ONE)));
+        }
+
+        let secret: Scalar = polynomials.iter().map(|p| p[0]).sum();
+        assert_eq!(election_key(&dealings), G * secret);
+
+        // Each trustee's share of the key sums what they were dealt, and any t of them recover it
+        let shares: Vec<Scalar> = (1..=trustees).map(|j| polynomials.iter().map(|p| evaluate(p, j)).sum()).collect();
+        for j in 1..=trustees {
+            assert_eq!(public_share(&dealings, j), G * shares[j as usize - 1]);
+        }
+        let subset = [2u64, 4];
+        let recovered: Scalar = subset.iter().map(|&i| shares[i as usize - 1] * lagrange_at(0, i, &subset)).sum();
+        assert_eq!(recovered, secret);
+    }
+
+    #[test]
+    fn dealings_prove_their_constant_term() {
+        let context = dealing_context(&Uuid::from_u128(7), 1);
+        let a0 = random_scalar();
+        let commitments = vec![G * a0, G * random_scalar()];
+        let proof = prove_dleq(b"miden-vote/dealing", &context, &G, &G, &a0);
+
+        assert!(verify_dealing(&commitments, &proof, &context));
+        assert!(!verify_dealing(&commitments, &proof, &dealing_context(&Uuid::from_u128(7), 2)));
+        // Nor does it carry over to a constant term chosen to cancel out another dealer's
+        let rogue = vec![G * random_scalar() - commitments[0], commitments[1]];
+        assert!(!verify_dealing(&rogue, &proof, &context));
+        assert!(!verify_dealing(&[], &proof, &context));
+    }
+
+    #[test]
+    fn only_the_recipient_can_disclose_a_dealt_share() {
+        let context = dealing_context(&Uuid::from_u128(7), 3);
+        let transport_secret = random_scalar();
+        let transport_key = G * transport_secret;
+        let dealt = random_scalar();
+        let share = encrypt_share(&transport_key, &dealt, &context);
+
+        assert_eq!(disclosed_share(&transport_key, &share, &disclose(&transport_secret, &share, &context), &context), Some(dealt));
+
+        // Someone else's key opens nothing, and neither does the right key without its proof
+        let other = random_scalar();
+        assert_eq!(disclosed_share(&transport_key, &share, &disclose(&other, &share, &context), &context), None);
+        let mut forged = disclose(&transport_secret, &share, &context);
+        forged.key = point_to_hex(&(point_from_hex(&forged.key).unwrap() + G));
+        assert_eq!(disclosed_share(&transport_key, &share, &forged, &context), None);
+    }
+
+    #[test]
+    fn threshold_decryption_recovers_aggregate_totals() {
+        let (secret, shares) = deal(2, 3);
+        let public_key = G * secret;
+        let ballots: Vec<(u64, u64, Vec<Ciphertext>)> = [(0usize, 5u64), (1, 3), (1, 9)]
+            .iter()
+            .map(|&(choice, weight)| {
+                let cts = (0..2).map(|j| encrypt(&public_key, (j == choice) as u64, &random_scalar())).collect();
+                (weight, weight * 1000, cts)
+            })
+            .collect();
+        let aggregates = aggregate(2, &ballots);
+
+        // Trustees 1 and 3 post verified shares
+        let trustee_shares: Vec<(u64, Vec<RistrettoPoint>)> = [1u64, 3]
+            .iter()
+            .map(|&i| {
+                let sk = shares[i as usize - 1];
+                let decryption = aggregates
+                    .iter()
+                    .map(|ct| {
+                        let share = DecryptionShare { d: point_to_hex(&(ct.a * sk)), proof: prove_dleq(b"miden-vote/share", &[], &G, &ct.a, &sk) };
+                        verify_share(&(G * sk), ct, &share).expect("Share verifies")
+                    })
+                    .collect();
+                (i, decryption)
+            })
+            .collect();
+
+        assert_eq!(decrypt_aggregates(&aggregates, &trustee_shares), Ok(vec![1, 5, 5000, 2, 12, 12000]));
+    }
+
+    #[test]
+    fn baby_step_giant_step_recovers_small_logs() {
+        for m in [0, 1, BABY_STEPS - 1, BABY_STEPS, 3 * BABY_STEPS + 12_345, 40 * BABY_STEPS + 7] {
+            assert_eq!(discrete_log(table(), &(G * Scalar::from(m))), Some(m));
+        }
+    }
+
+    #[test]
+    fn weight_bound_keeps_scaled_power_decryptable() {
+        assert_eq!(MAX_TOTAL, (1 << 40) - 1);
+        assert!(MAX_WEIGHT_TOTAL * POWER_SCALE as u64 <= MAX_TOTAL);
+        assert!((MAX_WEIGHT_TOTAL + 1) * POWER_SCALE as u64 > MAX_TOTAL);
+    }
+}
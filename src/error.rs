@@ -0,0 +1,288 @@
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use serde::Serialize;
+use std::fmt;
+
+use crate::lifecycle::{ProposalState, TransitionError};
+use crate::models::Submission;
+use crate::permissions::Permission;
+use crate::tally::QuorumReport;
+
+// ApiError: Every handler error, rendered as { code, message, details? } with a stable code
+#[derive(Debug)]
+pub enum ApiError {
+    // 400
+    Validation(String),
+    UnknownVotingModel(String),
+    MalformedProof(String),
+    InvalidBallot(String),
+    InvalidDecryptionShare,
+    // 401: missing or bad credentials
+    Unauthenticated,
+    InvalidToken,
+    TokenRevoked,
+    InvalidRefreshToken,
+    InvalidChallenge,
+    InvalidSignature(String),
+    PublicKeyMismatch,
+    // 403: authenticated, but not allowed
+    Forbidden(Permission),
+    EligibilityRootMismatch,
+    NotTrustee,
+    // 404
+    ProjectNotFound,
+    ProposalNotFound,
+    UserNotFound,
+    MemberNotFound,
+    WalletNotFound,
+    ReceiptNotFound,
+    BulletinRootNotFound,
+    TallyProofNotFound,
+    ElectionNotFound,
+    TrusteeNotFound,
+    WebhookNotFound,
+    WebhookDeliveryNotFound,
+    NotEligible,
+    // 409
+    UserExists,
+    WalletInUse,
+    LastWallet,
+    ElectionExists,
+    NullifierReused,
+    VotingClosed(ProposalState),
+    InvalidState { action: &'static str, state: ProposalState },
+    InvalidTransition { from: ProposalState, to: ProposalState },
+    DecryptionSharesPending { posted: usize, required: usize },
+    BulletinMismatch,
+    SnapshotLocked,
+    ImportConflict(String),
+    KeyGenerationConflict(String),
+    // 422
+    ProofRejected(Box<Submission>),
+    TallyProofRejected(String),
+    QuorumNotReached(QuorumReport),
+    // 429
+    TooManyChallenges,
+    // 502: the cause is logged, never sent to the client
+    AccountLookupFailed(String),
+    // 500: the cause is logged, never sent to the client
+    Database(sqlx::Error),
+    Internal(String),
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    code: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<serde_json::Value>,
+}
+
+impl ApiError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::Validation(_) => "VALIDATION_FAILED",
+            ApiError::UnknownVotingModel(_) => "UNKNOWN_VOTING_MODEL",
+            ApiError::MalformedProof(_) => "MALFORMED_PROOF",
+            ApiError::InvalidBallot(_) => "INVALID_BALLOT",
+            ApiError::InvalidDecryptionShare => "INVALID_DECRYPTION_SHARE",
+            ApiError::Unauthenticated => "UNAUTHENTICATED",
+            ApiError::InvalidToken => "INVALID_TOKEN",
+            ApiError::TokenRevoked => "TOKEN_REVOKED",
+            ApiError::InvalidRefreshToken => "INVALID_REFRESH_TOKEN",
+            ApiError::InvalidChallenge => "INVALID_CHALLENGE",
+            ApiError::InvalidSignature(_) => "INVALID_SIGNATURE",
+            ApiError::PublicKeyMismatch => "PUBLIC_KEY_MISMATCH",
+            ApiError::Forbidden(_) => "FORBIDDEN",
+            ApiError::EligibilityRootMismatch => "ELIGIBILITY_ROOT_MISMATCH",
+            ApiError::NotTrustee => "NOT_TRUSTEE",
+            ApiError::ProjectNotFound => "PROJECT_NOT_FOUND",
+            ApiError::ProposalNotFound => "PROPOSAL_NOT_FOUND",
+            ApiError::UserNotFound => "USER_NOT_FOUND",
+            ApiError::MemberNotFound => "MEMBER_NOT_FOUND",
+            ApiError::WalletNotFound => "WALLET_NOT_FOUND",
+            ApiError::ReceiptNotFound => "RECEIPT_NOT_FOUND",
+            ApiError::BulletinRootNotFound => "BULLETIN_ROOT_NOT_FOUND",
+            ApiError::TallyProofNotFound => "TALLY_PROOF_NOT_FOUND",
+            ApiError::ElectionNotFound => "ELECTION_NOT_FOUND",
+            ApiError::TrusteeNotFound => "TRUSTEE_NOT_FOUND",
+            ApiError::WebhookNotFound => "WEBHOOK_NOT_FOUND",
+            ApiError::WebhookDeliveryNotFound => "WEBHOOK_DELIVERY_NOT_FOUND",
+            ApiError::NotEligible => "NOT_ELIGIBLE",
+            ApiError::UserExists => "USER_EXISTS",
+            ApiError::WalletInUse => "WALLET_IN_USE",
+            ApiError::LastWallet => "LAST_WALLET",
+            ApiError::ElectionExists => "ELECTION_EXISTS",
+            ApiError::NullifierReused => "NULLIFIER_REUSED",
+            ApiError::VotingClosed(_) => "VOTING_CLOSED",
+            ApiError::InvalidState { .. } => "INVALID_PROPOSAL_STATE",
+            ApiError::InvalidTransition { .. } => "INVALID_STATE_TRANSITION",
+            ApiError::DecryptionSharesPending { .. } => "DECRYPTION_SHARES_PENDING",
+            ApiError::BulletinMismatch => "BULLETIN_MISMATCH",
+            ApiError::SnapshotLocked => "SNAPSHOT_LOCKED",
+            ApiError::ImportConflict(_) => "IMPORT_CONFLICT",
+            ApiError::KeyGenerationConflict(_) => "KEY_GENERATION_CONFLICT",
+            ApiError::ProofRejected(_) => "PROOF_REJECTED",
+            ApiError::TallyProofRejected(_) => "TALLY_PROOF_REJECTED",
+            ApiError::QuorumNotReached(_) => "QUORUM_NOT_REACHED",
+            ApiError::TooManyChallenges => "TOO_MANY_CHALLENGES",
+            ApiError::AccountLookupFailed(_) => "ACCOUNT_LOOKUP_FAILED",
+            ApiError::Database(_) | ApiError::Internal(_) => "INTERNAL_ERROR",
+        }
+    }
+
+    fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            ApiError::ProofRejected(submission) => serde_json::to_value(submission).ok(),
+            ApiError::QuorumNotReached(quorum) => serde_json::to_value(quorum).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Validation(reason) | ApiError::MalformedProof(reason) | ApiError::InvalidBallot(reason) => f.write_str(reason),
+            ApiError::TallyProofRejected(reason) | ApiError::ImportConflict(reason) => f.write_str(reason),
+            ApiError::KeyGenerationConflict(reason) => f.write_str(reason),
+            ApiError::UnknownVotingModel(model) => write!(f, "Unknown voting model: {}", model),
+            ApiError::InvalidDecryptionShare => f.write_str("Invalid decryption share proof"),
+            ApiError::Unauthenticated => f.write_str("Authentication required"),
+            ApiError::InvalidToken => f.write_str("Invalid or expired token"),
+            ApiError::TokenRevoked => f.write_str("Token has been revoked"),
+            ApiError::InvalidRefreshToken => f.write_str("Invalid, expired or reused refresh token"),
+            ApiError::InvalidChallenge => f.write_str("Invalid or expired challenge"),
+            ApiError::InvalidSignature(reason) => f.write_str(reason),
+            ApiError::PublicKeyMismatch => f.write_str("Public key does not match this wallet"),
+            ApiError::Forbidden(permission) => write!(f, "Missing permission: {}", permission),
+            ApiError::EligibilityRootMismatch => f.write_str("Proof does not commit to the project's current eligibility root"),
+            ApiError::NotTrustee => f.write_str("Only a trustee registered for this election can do this"),
+            ApiError::ProjectNotFound => f.write_str("Project not found"),
+            ApiError::ProposalNotFound => f.write_str("Proposal not found"),
+            ApiError::UserNotFound => f.write_str("User not found"),
+            ApiError::MemberNotFound => f.write_str("User is not a member of this project"),
+            ApiError::WalletNotFound => f.write_str("Wallet is not linked to this user"),
+            ApiError::ReceiptNotFound => f.write_str("No counted ballot with this nullifier"),
+            ApiError::BulletinRootNotFound => f.write_str("No published bulletin root of this size"),
+            ApiError::TallyProofNotFound => f.write_str("Proposal has no proven tally"),
+            ApiError::ElectionNotFound => f.write_str("Proposal has no election"),
+            ApiError::TrusteeNotFound => f.write_str("Unknown trustee"),
+            ApiError::WebhookNotFound => f.write_str("Webhook not found"),
+            ApiError::WebhookDeliveryNotFound => f.write_str("Webhook delivery not found"),
+            ApiError::NotEligible => f.write_str("Address is not eligible in this project"),
+            ApiError::UserExists => f.write_str("User already exists"),
+            ApiError::WalletInUse => f.write_str("Wallet is already linked to another user"),
+            ApiError::LastWallet => f.write_str("Cannot unlink a user's last wallet"),
+            ApiError::ElectionExists => f.write_str("Proposal already has an election"),
+            ApiError::NullifierReused => f.write_str("Nullifier hash already used (double voting detected)"),
+            ApiError::VotingClosed(state) => write!(f, "Proposal is not open for voting (state: {})", state),
+            ApiError::InvalidState { action, state } => write!(f, "Proposal cannot be {} in state {}", action, state),
+            ApiError::InvalidTransition { from, to } => write!(f, "Cannot move proposal from {} to {}", from, to),
+            ApiError::DecryptionSharesPending { posted, required } => {
+                write!(f, "Waiting for decryption shares: {} of {} required trustees have posted", posted, required)
+            }
+            ApiError::BulletinMismatch => f.write_str("Ballots do not match the published bulletin board"),
+            ApiError::SnapshotLocked => f.write_str("The eligibility snapshot can't change while a proposal is scheduled or active"),
+            ApiError::ProofRejected(submission) => {
+                f.write_str(submission.failure_reason.as_deref().unwrap_or("Proof verification failed"))
+            }
+            ApiError::QuorumNotReached(q) => write!(
+                f,
+                "Quorum not reached: turnout {} of {} eligible {} ({:.2}%), threshold {}%",
+                q.turnout, q.eligible, q.basis, q.turnout_percentage, q.threshold_percentage
+            ),
+            ApiError::TooManyChallenges => f.write_str("Too many open login challenges for this wallet; use one or wait for it to expire"),
+            ApiError::AccountLookupFailed(_) => f.write_str("Could not look up the account's key"),
+            ApiError::Database(_) | ApiError::Internal(_) => f.write_str("Internal server error"),
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::Validation(_)
+            | ApiError::UnknownVotingModel(_)
+            | ApiError::MalformedProof(_)
+            | ApiError::InvalidBallot(_)
+            | ApiError::InvalidDecryptionShare => StatusCode::BAD_REQUEST,
+            ApiError::Unauthenticated
+            | ApiError::InvalidToken
+            | ApiError::TokenRevoked
+            | ApiError::InvalidRefreshToken
+            | ApiError::InvalidChallenge
+            | ApiError::InvalidSignature(_)
+            | ApiError::PublicKeyMismatch => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) | ApiError::EligibilityRootMismatch | ApiError::NotTrustee => StatusCode::FORBIDDEN,
+            ApiError::ProjectNotFound
+            | ApiError::ProposalNotFound
+            | ApiError::UserNotFound
+            | ApiError::MemberNotFound
+            | ApiError::WalletNotFound
+            | ApiError::ReceiptNotFound
+            | ApiError::BulletinRootNotFound
+            | ApiError::TallyProofNotFound
+            | ApiError::ElectionNotFound
+            | ApiError::TrusteeNotFound
+            | ApiError::WebhookNotFound
+            | ApiError::WebhookDeliveryNotFound
+            | ApiError::NotEligible => StatusCode::NOT_FOUND,
+            ApiError::UserExists
+            | ApiError::WalletInUse
+            | ApiError::LastWallet
+            | ApiError::ElectionExists
+            | ApiError::NullifierReused
+            | ApiError::VotingClosed(_)
+            | ApiError::InvalidState { .. }
+            | ApiError::InvalidTransition { .. }
+            | ApiError::DecryptionSharesPending { .. }
+            | ApiError::BulletinMismatch
+            | ApiError::SnapshotLocked
+            | ApiError::ImportConflict(_)
+            | ApiError::KeyGenerationConflict(_) => StatusCode::CONFLICT,
+            ApiError::ProofRejected(_) | ApiError::TallyProofRejected(_) | ApiError::QuorumNotReached(_) => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            ApiError::TooManyChallenges => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::AccountLookupFailed(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Database(_) | ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            ApiError::Database(e) => log::error!("Database error: {}", e),
+            ApiError::Internal(e) => log::error!("Internal error: {}", e),
+            ApiError::AccountLookupFailed(e) => log::error!("Account key lookup failed: {}", e),
+            _ => {}
+        }
+
+        HttpResponse::build(self.status_code()).json(ErrorBody {
+            code: self.code(),
+            message: self.to_string(),
+            details: self.details(),
+        })
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(e: sqlx::Error) -> Self {
+        ApiError::Database(e)
+    }
+}
+
+impl From<TransitionError> for ApiError {
+    fn from(e: TransitionError) -> Self {
+        match e {
+            TransitionError::NotFound => ApiError::ProposalNotFound,
+            TransitionError::Invalid { from, to } => ApiError::InvalidTransition { from, to },
+            TransitionError::Database(e) => ApiError::Database(e),
+        }
+    }
+}
+
+/// Maps malformed JSON bodies, paths and queries to VALIDATION_FAILED instead of actix's plain-text errors.
+pub fn extractor_error(err: impl fmt::Display) -> actix_web::Error {
+    ApiError::Validation(err.to_string()).into()
+}
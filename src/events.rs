@@ -0,0 +1,132 @@
+use actix_web::rt::time::{interval_at, Instant, Interval};
+use actix_web::web::Bytes;
+use chrono::{NaiveDateTime, Utc};
+use futures_util::Stream;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError, Receiver, Sender};
+use uuid::Uuid;
+
+use crate::lifecycle::ProposalState;
+
+// In-process bus of proposal events, fanned out to the server-sent event streams at
+// GET /proposals/{id}/events. Handlers publish only after the change an event describes has committed.
+
+// Events buffered per subscriber; one that falls further behind is told to resync
+const CAPACITY: usize = 1024;
+// Comment lines sent on idle streams so proxies don't time them out
+const KEEP_ALIVE_SECS: u64 = 15;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventKind {
+    // First event on every stream, so clients needn't fetch the proposal separately
+    Snapshot { state: ProposalState, ballots_counted: i64 },
+    SubmissionAccepted { submission_id: Uuid, inclusion_index: i64, ballots_counted: i64 },
+    // Opened, closed, tallied, finalized or revoked
+    StateChanged { state: ProposalState },
+}
+
+impl EventKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::Snapshot { .. } => "snapshot",
+            EventKind::SubmissionAccepted { .. } => "submission_accepted",
+            EventKind::StateChanged { .. } => "state_changed",
+        }
+    }
+
+    // Nothing follows a terminal state, so its stream ends there
+    fn is_last(&self) -> bool {
+        match self {
+            EventKind::Snapshot { state, .. } | EventKind::StateChanged { state } => state.is_terminal(),
+            EventKind::SubmissionAccepted { .. } => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProposalEvent {
+    pub proposal_id: Uuid,
+    #[serde(flatten)]
+    pub kind: EventKind,
+    pub at: NaiveDateTime,
+}
+
+impl ProposalEvent {
+    pub fn new(proposal_id: Uuid, kind: EventKind) -> ProposalEvent {
+        ProposalEvent { proposal_id, kind, at: Utc::now().naive_utc() }
+    }
+
+    // One `text/event-stream` message, named after the event's type
+    fn frame(&self) -> Bytes {
+        let data = serde_json::to_string(self).unwrap_or_default();
+        Bytes::from(format!("event: {}\ndata: {}\n\n", self.kind.name(), data))
+    }
+}
+
+#[derive(Clone)]
+pub struct EventBus {
+    sender: Sender<ProposalEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        EventBus { sender }
+    }
+}
+
+impl EventBus {
+    pub fn publish(&self, proposal_id: Uuid, kind: EventKind) {
+        // Fails only when nobody is listening
+        let _ = self.sender.send(ProposalEvent::new(proposal_id, kind));
+    }
+
+    pub fn subscribe(&self) -> Receiver<ProposalEvent> {
+        self.sender.subscribe()
+    }
+}
+
+struct Subscription {
+    proposal_id: Uuid,
+    receiver: Receiver<ProposalEvent>,
+    keep_alive: Interval,
+    pending: Option<ProposalEvent>,
+}
+
+/// The event stream of one proposal: `snapshot`, then its events as they're published, until it reaches
+/// a terminal state. A subscriber that lags behind the bus gets a `resync` event and the stream ends;
+/// reconnecting yields a fresh snapshot. `receiver` must be subscribed before `snapshot` is read.
+pub fn stream(proposal_id: Uuid, receiver: Receiver<ProposalEvent>, snapshot: ProposalEvent) -> impl Stream<Item = Result<Bytes, actix_web::Error>> {
+    let period = Duration::from_secs(KEEP_ALIVE_SECS);
+    let subscription = Subscription {
+        proposal_id,
+        receiver,
+        keep_alive: interval_at(Instant::now() + period, period),
+        pending: Some(snapshot),
+    };
+
+    futures_util::stream::unfold(Some(subscription), |subscription| async move {
+        let mut subscription = subscription?;
+        let event = match subscription.pending.take() {
+            Some(event) => event,
+            None => loop {
+                tokio::select! {
+                    _ = subscription.keep_alive.tick() => {
+                        return Some((Ok(Bytes::from_static(b": keep-alive\n\n")), Some(subscription)));
+                    }
+                    received = subscription.receiver.recv() => match received {
+                        Ok(event) if event.proposal_id == subscription.proposal_id => break event,
+                        Ok(_) => continue,
+                        Err(RecvError::Lagged(_)) => return Some((Ok(Bytes::from_static(b"event: resync\ndata: {}\n\n")), None)),
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            },
+        };
+
+        let next = if event.kind.is_last() { None } else { Some(subscription) };
+        Some((Ok(event.frame()), next))
+    })
+}
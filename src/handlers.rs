@@ -1,4 +0,0 @@
-pub mod project_handlers;
-pub mod proposal_handlers;
-pub mod submission_handlers;
-pub mod tally_handlers;
@@ -1,74 +1,198 @@
-use actix_web::{web, HttpResponse, Responder};
-use jsonwebtoken::{encode, Header, EncodingKey, Algorithm};
+use actix_web::{web, HttpResponse};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{Utc, Duration};
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 
-use crate::main::Claims;
-use crate::models::User;
+use crate::accounts::AccountKeys;
+use crate::error::ApiError;
+use crate::keyring::Keyring;
+use crate::models::{AuthChallenge, User, Wallet};
+use crate::permissions::Role;
+use crate::tokens;
+use crate::verifier;
+use crate::AuthExtractor;
+
+// How long a login challenge stays valid after being issued
+const CHALLENGE_TTL_MINUTES: i64 = 5;
+// Unused, unexpired challenges a wallet address may hold at once
+const MAX_OPEN_CHALLENGES: i64 = 5;
 
 #[derive(Debug, Deserialize)]
-pub struct LoginRequest {
+pub struct ChallengeQuery {
     pub wallet_address: String,
-    pub signed_message: String,
 }
 
 #[derive(Debug, Serialize)]
-pub struct AuthResponse {
-    pub token: String,
+pub struct ChallengeResponse {
+    pub nonce: String,
+    pub message: String,
+    pub expires_at: chrono::NaiveDateTime,
 }
 
-pub async fn login(req: web::Json<LoginRequest>, pool: web::Data<PgPool>) -> impl Responder {
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    pub wallet_address: String,
+    pub nonce: String,
+    pub public_key: String,
+    pub signed_message: String, // Hex-encoded Falcon signature over the challenge message
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogoutRequest {
+    pub refresh_token: Option<String>, // Also ends the session this refresh token belongs to
+}
+
+pub async fn challenge(query: web::Query<ChallengeQuery>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let challenge = AuthChallenge {
+        nonce: Uuid::new_v4().simple().to_string(),
+        wallet_address: query.wallet_address.clone(),
+        expires_at: (Utc::now() + Duration::minutes(CHALLENGE_TTL_MINUTES)).naive_utc(),
+        consumed_at: None,
+    };
+
+    let mut transaction = pool.begin().await?;
+
+    // Serialize challenges per address so concurrent requests can't get past the cap together
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(&challenge.wallet_address)
+        .execute(&mut *transaction)
+        .await?;
+    let open = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM auth_challenges WHERE wallet_address = $1 AND consumed_at IS NULL AND expires_at > $2"
+    )
+    .bind(&challenge.wallet_address)
+    .bind(Utc::now().naive_utc())
+    .fetch_one(&mut *transaction)
+    .await?;
+    if open >= MAX_OPEN_CHALLENGES {
+        return Err(ApiError::TooManyChallenges);
+    }
+
+    let c = sqlx::query_as::<_, AuthChallenge>("INSERT INTO auth_challenges (nonce, wallet_address, expires_at, consumed_at) VALUES ($1, $2, $3, $4) RETURNING *")
+        .bind(&challenge.nonce)
+        .bind(&challenge.wallet_address)
+        .bind(challenge.expires_at)
+        .bind(challenge.consumed_at)
+        .fetch_one(&mut *transaction)
+        .await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().json(ChallengeResponse {
+        message: verifier::challenge_message(&c.wallet_address, &c.nonce),
+        nonce: c.nonce,
+        expires_at: c.expires_at,
+    }))
+}
+
+/// Consumes the challenge `req` answers and checks the wallet signed it with its account's auth key.
+/// Callers commit even when this fails, so a nonce stays burned after a failed attempt.
+pub(crate) async fn verify_challenge(conn: &mut PgConnection, accounts: &AccountKeys, req: &LoginRequest) -> Result<(), ApiError> {
+    // Consume the challenge up front so a nonce can never be used twice, even by concurrent requests
+    let challenge = sqlx::query_as::<_, AuthChallenge>(
+        "UPDATE auth_challenges SET consumed_at = $1 WHERE nonce = $2 AND wallet_address = $3 AND consumed_at IS NULL AND expires_at > $1 RETURNING *"
+    )
+    .bind(Utc::now().naive_utc())
+    .bind(&req.nonce)
+    .bind(&req.wallet_address)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(ApiError::InvalidChallenge)?;
+
+    let message = verifier::challenge_message(&challenge.wallet_address, &challenge.nonce);
+    verifier::verify_wallet_signature(&req.public_key, &message, &req.signed_message).map_err(ApiError::InvalidSignature)?;
+
+    // A valid signature only proves control of the key; the key must also be the account's own
+    accounts.check(conn, &req.wallet_address, &req.public_key).await
+}
+
+pub async fn login(
+    req: web::Json<LoginRequest>,
+    pool: web::Data<PgPool>,
+    keyring: web::Data<Keyring>,
+    accounts: web::Data<AccountKeys>,
+) -> Result<HttpResponse, ApiError> {
     let wallet_address = req.wallet_address.clone();
-    let _signed_message = req.signed_message.clone(); // Placeholder for actual signature verification
 
-    // TODO: Implement actual Miden signature verification here
-    // For now, we'll assume the signature is valid if present.
-    // if !verify_miden_signature(&wallet_address, &signed_message) {
-    //     return HttpResponse::Unauthorized().body("Invalid signature");
-    // }
+    let mut transaction = pool.begin().await?;
 
-    // Look up user by wallet_address, or create if not exists
-    let user = match sqlx::query_as::<_, User>("SELECT * FROM users WHERE wallet_address = $1")
+    if let Err(e) = verify_challenge(&mut transaction, &accounts, &req).await {
+        transaction.commit().await?;
+        return Err(e);
+    }
+
+    // Look up the user owning this wallet, or create both if the wallet is new
+    let user = match sqlx::query_as::<_, Wallet>("SELECT * FROM wallets WHERE address = $1")
         .bind(&wallet_address)
-        .fetch_optional(pool.get_ref())
-        .await
+        .fetch_optional(&mut *transaction)
+        .await?
     {
-        Ok(Some(u)) => u,
-        Ok(None) => {
-            // Create new user with default role if not found
-            let new_user = User {
-                wallet_address: wallet_address.clone(),
-                role: "user".to_string(),
-                created_at: Utc::now().naive_utc(),
-            };
-            match sqlx::query_as::<_, User>("INSERT INTO users (wallet_address, role, created_at) VALUES ($1, $2, $3) RETURNING *")
-                .bind(&new_user.wallet_address)
-                .bind(&new_user.role)
-                .bind(new_user.created_at)
-                .fetch_one(pool.get_ref())
-                .await
-            {
-                Ok(u) => u,
-                Err(e) => return HttpResponse::InternalServerError().body(e.to_string()),
+        Some(wallet) => {
+            // Keys are never bound here: the check above already tied the key to this wallet's account
+            if wallet.public_key.as_deref().is_some_and(|key| key != req.public_key) {
+                transaction.commit().await?;
+                return Err(ApiError::PublicKeyMismatch);
             }
+
+            sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+                .bind(wallet.user_id)
+                .fetch_one(&mut *transaction)
+                .await?
         }
-        Err(e) => return HttpResponse::InternalServerError().body(e.to_string()),
-    };
+        None => {
+            // Create new user with default role if not found
+            let now = Utc::now().naive_utc();
+            let user = sqlx::query_as::<_, User>("INSERT INTO users (id, role, created_at) VALUES ($1, $2, $3) RETURNING *")
+                .bind(Uuid::new_v4())
+                .bind(Role::User)
+                .bind(now)
+                .fetch_one(&mut *transaction)
+                .await?;
 
-    let expiration = Utc::now() + Duration::hours(24);
-    let claims = Claims {
-        user_id: Uuid::parse_str(&user.wallet_address).unwrap_or_else(|_| Uuid::new_v4()), // Convert wallet_address to Uuid
-        role: user.role,
-        exp: expiration.timestamp() as usize,
-    };
+            sqlx::query("INSERT INTO wallets (address, user_id, public_key, created_at) VALUES ($1, $2, $3, $4)")
+                .bind(&wallet_address)
+                .bind(user.id)
+                .bind(&req.public_key)
+                .bind(now)
+                .execute(&mut *transaction)
+                .await?;
 
-    let secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set");
-    let token = match encode(&Header::new(Algorithm::Hs256), &claims, &EncodingKey::from_secret(secret.as_ref())) {
-        Ok(t) => t,
-        Err(_) => return HttpResponse::InternalServerError().body("Could not create JWT"),
+            user
+        }
     };
 
-    HttpResponse::Ok().json(AuthResponse { token })
+    let tokens = tokens::issue(&mut transaction, &keyring, &user, &wallet_address).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().json(tokens))
+}
+
+pub async fn refresh(req: web::Json<RefreshRequest>, pool: web::Data<PgPool>, keyring: web::Data<Keyring>) -> Result<HttpResponse, ApiError> {
+    // Not a transaction: a reused token's family must stay revoked even though the request fails
+    let mut conn = pool.acquire().await?;
+    let tokens = tokens::rotate(&mut conn, &keyring, &req.refresh_token).await?;
+
+    Ok(HttpResponse::Ok().json(tokens))
+}
+
+pub async fn logout(req: Option<web::Json<LogoutRequest>>, pool: web::Data<PgPool>, auth: AuthExtractor) -> Result<HttpResponse, ApiError> {
+    let mut transaction = pool.begin().await?;
+
+    tokens::deny(&mut transaction, auth.jti, auth.exp).await?;
+    if let Some(refresh_token) = req.as_ref().and_then(|r| r.refresh_token.as_deref()) {
+        tokens::revoke_family(&mut transaction, refresh_token, auth.user_id).await?;
+    }
+
+    transaction.commit().await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// Public keys other services verify access tokens with
+pub async fn jwks(keyring: web::Data<Keyring>) -> HttpResponse {
+    HttpResponse::Ok().json(keyring.jwks())
 }
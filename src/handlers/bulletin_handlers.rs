@@ -0,0 +1,171 @@
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::ballot_log;
+use crate::bulletin;
+use crate::error::ApiError;
+use crate::models::{BulletinEntry, BulletinRoot};
+
+// DTOs for queries
+#[derive(Deserialize)]
+pub struct InclusionQuery {
+    pub leaf_index: i64,
+    pub tree_size: i64,
+}
+
+#[derive(Deserialize)]
+pub struct ConsistencyQuery {
+    pub from: i64,
+    pub to: i64,
+}
+
+// DTOs for responses
+#[derive(Serialize)]
+pub struct BulletinResponse {
+    pub tree_size: i64, // Current head, published or not
+    pub root: String,
+    pub chain_hash: String,
+    pub published: Vec<BulletinRoot>,
+}
+
+#[derive(Serialize)]
+pub struct InclusionResponse {
+    pub leaf_index: i64,
+    pub tree_size: i64,
+    pub leaf_hash: String,
+    pub root: String,
+    pub proof: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ConsistencyResponse {
+    pub from: i64,
+    pub to: i64,
+    pub from_root: String,
+    pub to_root: String,
+    pub proof: Vec<String>,
+}
+
+async fn published_root(conn: &mut PgConnection, proposal_id: Uuid, tree_size: i64) -> Result<BulletinRoot, ApiError> {
+    sqlx::query_as::<_, BulletinRoot>("SELECT * FROM bulletin_roots WHERE proposal_id = $1 AND tree_size = $2")
+        .bind(proposal_id)
+        .bind(tree_size)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(ApiError::BulletinRootNotFound)
+}
+
+// Handlers
+pub async fn get_bulletin(pool: web::Data<PgPool>, proposal_id: web::Path<Uuid>) -> Result<HttpResponse, ApiError> {
+    let proposal_id = proposal_id.into_inner();
+
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM proposals WHERE id = $1)")
+        .bind(proposal_id)
+        .fetch_one(pool.get_ref())
+        .await?;
+    if !exists {
+        return Err(ApiError::ProposalNotFound);
+    }
+
+    let head = sqlx::query_as::<_, BulletinEntry>("SELECT * FROM bulletin_entries WHERE proposal_id = $1 ORDER BY entry_index DESC LIMIT 1")
+        .bind(proposal_id)
+        .fetch_optional(pool.get_ref())
+        .await?;
+    let published = sqlx::query_as::<_, BulletinRoot>("SELECT * FROM bulletin_roots WHERE proposal_id = $1 ORDER BY tree_size")
+        .bind(proposal_id)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    let response = match head {
+        Some(e) => BulletinResponse { tree_size: e.entry_index + 1, root: e.root, chain_hash: e.chain_hash, published },
+        None => BulletinResponse {
+            tree_size: 0,
+            root: ballot_log::to_hex(&ballot_log::root(&[])),
+            chain_hash: ballot_log::to_hex(&ballot_log::GENESIS_CHAIN),
+            published,
+        },
+    };
+    Ok(HttpResponse::Ok().json(response))
+}
+
+pub async fn list_entries(pool: web::Data<PgPool>, proposal_id: web::Path<Uuid>) -> Result<HttpResponse, ApiError> {
+    let entries = sqlx::query_as::<_, BulletinEntry>("SELECT * FROM bulletin_entries WHERE proposal_id = $1 ORDER BY entry_index")
+        .bind(proposal_id.into_inner())
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(entries))
+}
+
+/// Audit path from a logged ballot to a published root. A voter's receipt gives the leaf index.
+pub async fn get_inclusion_proof(
+    pool: web::Data<PgPool>,
+    proposal_id: web::Path<Uuid>,
+    query: web::Query<InclusionQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let proposal_id = proposal_id.into_inner();
+    if query.leaf_index < 0 || query.leaf_index >= query.tree_size {
+        return Err(ApiError::Validation("leaf_index must be below tree_size".to_string()));
+    }
+
+    let mut conn = pool.acquire().await?;
+    let published = published_root(&mut conn, proposal_id, query.tree_size).await?;
+    let leaves = bulletin::leaves(&mut conn, proposal_id, query.tree_size).await?;
+    let index = query.leaf_index as usize;
+    let proof = ballot_log::inclusion_proof(index, &leaves);
+
+    // Never serve a proof that doesn't check out against what was published
+    let root = ballot_log::from_hex(&published.root).ok_or(ApiError::BulletinMismatch)?;
+    let size = query.tree_size as u64;
+    if leaves.len() as u64 != size || !ballot_log::verify_inclusion(index as u64, size, &leaves[index], &proof, &root) {
+        return Err(ApiError::BulletinMismatch);
+    }
+
+    Ok(HttpResponse::Ok().json(InclusionResponse {
+        leaf_index: query.leaf_index,
+        tree_size: query.tree_size,
+        leaf_hash: ballot_log::to_hex(&leaves[index]),
+        root: published.root,
+        proof: proof.iter().map(ballot_log::to_hex).collect(),
+    }))
+}
+
+/// Proof that the log at one published root is a prefix of the log at a later one, so nothing
+/// published was rewritten. `from=0`, the empty log, is a prefix of every log.
+pub async fn get_consistency_proof(
+    pool: web::Data<PgPool>,
+    proposal_id: web::Path<Uuid>,
+    query: web::Query<ConsistencyQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let proposal_id = proposal_id.into_inner();
+    if query.from < 0 || query.from > query.to {
+        return Err(ApiError::Validation("from must not exceed to".to_string()));
+    }
+
+    let mut conn = pool.acquire().await?;
+    let from_root = match query.from {
+        0 => ballot_log::to_hex(&ballot_log::root(&[])),
+        from => published_root(&mut conn, proposal_id, from).await?.root,
+    };
+    let to_root = published_root(&mut conn, proposal_id, query.to).await?;
+    let leaves = bulletin::leaves(&mut conn, proposal_id, query.to).await?;
+    let proof = ballot_log::consistency_proof(query.from as usize, &leaves);
+
+    let old_root = ballot_log::from_hex(&from_root).ok_or(ApiError::BulletinMismatch)?;
+    let new_root = ballot_log::from_hex(&to_root.root).ok_or(ApiError::BulletinMismatch)?;
+    if leaves.len() as i64 != query.to
+        || !ballot_log::verify_consistency(query.from as u64, query.to as u64, &old_root, &new_root, &proof)
+    {
+        return Err(ApiError::BulletinMismatch);
+    }
+
+    Ok(HttpResponse::Ok().json(ConsistencyResponse {
+        from: query.from,
+        to: query.to,
+        from_root,
+        to_root: to_root.root,
+        proof: proof.iter().map(ballot_log::to_hex).collect(),
+    }))
+}
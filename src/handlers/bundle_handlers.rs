@@ -0,0 +1,512 @@
+use actix_web::http::header;
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use miden_crypto::hash::rpo::Rpo256;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, PgPool};
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
+
+use crate::bulletin;
+use crate::bundle::{self, Bundle};
+use crate::election::{self, EncryptedBallot};
+use crate::error::ApiError;
+use crate::handlers::tally_handlers;
+use crate::lifecycle::ProposalState;
+use crate::merkle;
+use crate::models::{Election, ElectionTrustee, EligibilityLeaf, Project, Proposal, Submission, Tally};
+use crate::tally::{self, Ballot};
+use crate::verifier::{self, ProgramHashes, ProgramProof, VoteOutputs, VotePublicInputs};
+
+// ProposalBundle: A proposal and its evidence, as exported and imported between deployments. Rows are
+// serialized exactly as the API returns them, so `miden-vote-verify` reads the same file through
+// `bundle::Bundle`.
+#[derive(Serialize, Deserialize)]
+pub struct ProposalBundle {
+    pub format: String,
+    pub version: u32,
+    pub project: Project,
+    pub proposal: Proposal,
+    pub eligibility: Vec<EligibilityLeaf>,
+    pub submissions: Vec<Submission>,
+    pub tally: Option<Tally>,
+    pub election: Option<Election>,
+    pub trustees: Vec<ElectionTrustee>,
+    pub proofs: BTreeMap<String, String>, // Hex-encoded proof bytes, keyed by their SHA-256
+}
+
+impl ProposalBundle {
+    // Proofs referenced by the counted submissions and the tally
+    fn proof_hashes(&self) -> impl Iterator<Item = &String> {
+        self.submissions
+            .iter()
+            .filter(|s| s.verified_bool)
+            .map(|s| &s.proof_hash)
+            .chain(self.tally.iter().filter_map(|t| t.aggregate_proof_hash.as_ref()))
+    }
+}
+
+async fn load_bundle(conn: &mut PgConnection, proposal_id: Uuid) -> Result<ProposalBundle, ApiError> {
+    let proposal = sqlx::query_as::<_, Proposal>("SELECT * FROM proposals WHERE id = $1")
+        .bind(proposal_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(ApiError::ProposalNotFound)?;
+    let project = sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE id = $1")
+        .bind(proposal.project_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    let eligibility = sqlx::query_as::<_, EligibilityLeaf>("SELECT * FROM eligibility_leaves WHERE project_id = $1 ORDER BY leaf_index")
+        .bind(proposal.project_id)
+        .fetch_all(&mut *conn)
+        .await?;
+    let submissions = sqlx::query_as::<_, Submission>(
+        "SELECT * FROM submissions WHERE proposal_id = $1 ORDER BY inclusion_index NULLS LAST, id"
+    )
+    .bind(proposal_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let tally = sqlx::query_as::<_, Tally>("SELECT * FROM tallies WHERE proposal_id = $1")
+        .bind(proposal_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    let election = sqlx::query_as::<_, Election>("SELECT * FROM elections WHERE proposal_id = $1")
+        .bind(proposal_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    let trustees = sqlx::query_as::<_, ElectionTrustee>("SELECT * FROM election_trustees WHERE proposal_id = $1 ORDER BY trustee_index")
+        .bind(proposal_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let mut bundle = ProposalBundle {
+        format: bundle::FORMAT.to_string(),
+        version: bundle::VERSION,
+        project,
+        proposal,
+        eligibility,
+        submissions,
+        tally,
+        election,
+        trustees,
+        proofs: BTreeMap::new(),
+    };
+
+    let hashes: Vec<String> = bundle.proof_hashes().cloned().collect();
+    let proofs = sqlx::query_as::<_, (String, Vec<u8>)>("SELECT proof_hash, proof_bytes FROM proofs WHERE proof_hash = ANY($1)")
+        .bind(&hashes)
+        .fetch_all(&mut *conn)
+        .await?;
+    bundle.proofs = proofs.into_iter().map(|(hash, bytes)| (hash, hex::encode(bytes))).collect();
+
+    Ok(bundle)
+}
+
+// Re-checks a counted ballot as submit_vote did, against this deployment's vote program
+fn check_counted(
+    bundle: &ProposalBundle,
+    submission: &Submission,
+    proof_bytes: &[u8],
+    election_key: Option<&RistrettoPoint>,
+    programs: &ProgramHashes,
+) -> Result<(), String> {
+    if !submission.program_hash.eq_ignore_ascii_case(&programs.vote) {
+        return Err(format!("proved with program {}, this deployment runs {}", submission.program_hash, programs.vote));
+    }
+    let public_inputs: Vec<u64> = serde_json::from_value(submission.public_inputs.clone()).map_err(|e| e.to_string())?;
+    let stack_outputs: Vec<u64> = serde_json::from_value(submission.stack_outputs.clone()).map_err(|e| e.to_string())?;
+
+    let inputs = VotePublicInputs::parse(&public_inputs).ok_or("public inputs do not match the vote program layout")?;
+    let root = bundle.proposal.eligibility_root.as_deref().and_then(merkle::digest_from_hex);
+    if root.map(|r| merkle::digest_to_ints(&r)) != Some(inputs.merkle_root) {
+        return Err("proof is not against the proposal's eligibility snapshot".to_string());
+    }
+    if inputs.proposal_id != verifier::proposal_id_word(&bundle.proposal.id) {
+        return Err("proof is not bound to this proposal".to_string());
+    }
+    if merkle::digest_to_hex(&merkle::digest_from_ints(inputs.nullifier)) != submission.nullifier_hash {
+        return Err("nullifier is not the one the proof outputs".to_string());
+    }
+
+    let outputs = VoteOutputs::parse(&stack_outputs).ok_or("stack outputs do not match the vote program layout")?;
+    let ranking: Vec<u64> = serde_json::from_value(submission.ballot.clone()).map_err(|e| e.to_string())?;
+    if i64::try_from(outputs.weight).ok() != Some(submission.weight) || ranking != outputs.choices {
+        return Err("weight or ballot is not the one the proof outputs".to_string());
+    }
+
+    let choice_count = tally::parse_choices(&bundle.proposal.choices_json).map_or(0, |c| c.len());
+    match (election_key, &submission.encrypted_ballot) {
+        (Some(public_key), Some(encrypted)) => {
+            let encrypted: EncryptedBallot = serde_json::from_value(encrypted.clone()).map_err(|e| e.to_string())?;
+            let context = election::ballot_context(&bundle.proposal.id, &inputs.nullifier);
+            let ciphertexts = election::verify_ballot(public_key, &encrypted, choice_count, &context)?;
+            if merkle::digest_to_ints(&Rpo256::hash(&election::ballot_bytes(&ciphertexts))) != inputs.ballot_commitment {
+                return Err("proof does not commit to the encrypted ballot".to_string());
+            }
+        }
+        (None, None) => {
+            let engine = tally::engine_for(&bundle.proposal.model_enum).ok_or("unknown voting model")?;
+            engine.validate(&Ballot { weight: outputs.weight, ranking: ranking.iter().map(|&c| c as usize).collect() }, choice_count)?;
+        }
+        _ => return Err("ballot encryption does not match the proposal's election".to_string()),
+    }
+
+    let verification = verifier::verify_proof(&ProgramProof {
+        proof_bytes,
+        program_hash: &programs.vote,
+        public_inputs: &public_inputs,
+        stack_outputs: &stack_outputs,
+    });
+    match verification.verified {
+        true => Ok(()),
+        false => Err(verification.failure_reason.unwrap_or_else(|| "proof does not verify".to_string())),
+    }
+}
+
+// The election key and public shares must be the ones the trustees' qualified dealings generate
+fn check_election(bundle: &ProposalBundle) -> Result<Option<RistrettoPoint>, String> {
+    let Some(e) = &bundle.election else { return Ok(None) };
+    if !bundle.trustees.iter().map(|t| t.trustee_index).eq(1..=e.trustee_count) {
+        return Err("Election trustees must be numbered from 1 to the trustee count".to_string());
+    }
+    let public_key = e.public_key.as_deref().and_then(election::point_from_hex).ok_or("Election key is invalid")?;
+
+    let mut dealings = Vec::new();
+    for trustee in bundle.trustees.iter().filter(|t| !t.disqualified) {
+        let commitments: Vec<String> = trustee.commitments.clone().and_then(|c| serde_json::from_value(c).ok()).unwrap_or_default();
+        let commitments: Option<Vec<_>> = commitments.iter().map(|c| election::point_from_hex(c)).collect();
+        let commitments = commitments
+            .filter(|c| c.len() == e.threshold as usize)
+            .ok_or_else(|| format!("Trustee {} has no valid dealing", trustee.trustee_index))?;
+        dealings.push(commitments);
+    }
+    if dealings.is_empty() || election::election_key(&dealings) != public_key {
+        return Err("Election key is not the one its trustees' dealings generate".to_string());
+    }
+    for trustee in &bundle.trustees {
+        let public_share = trustee.public_share.as_deref().and_then(election::point_from_hex);
+        if public_share != Some(election::public_share(&dealings, trustee.trustee_index as u64)) {
+            return Err(format!("Public share of trustee {} is not the one the dealings generate", trustee.trustee_index));
+        }
+    }
+    Ok(Some(public_key))
+}
+
+// Re-checks every counted ballot in the bundle; nothing the exporter claims is taken on its word
+fn check_counted_ballots(bundle: &ProposalBundle, proofs: &BTreeMap<String, Vec<u8>>, programs: &ProgramHashes) -> Result<(), String> {
+    let election_key = check_election(bundle)?;
+
+    let mut nullifiers = HashSet::new();
+    for submission in bundle.submissions.iter().filter(|s| s.verified_bool) {
+        let proof_bytes = proofs.get(&submission.proof_hash).map(Vec::as_slice).unwrap_or_default();
+        check_counted(bundle, submission, proof_bytes, election_key.as_ref(), programs)
+            .map_err(|reason| format!("Counted submission {}: {}", submission.id, reason))?;
+        if !nullifiers.insert(&submission.nullifier_hash) {
+            return Err(format!("Nullifier {} is counted more than once", submission.nullifier_hash));
+        }
+    }
+    Ok(())
+}
+
+// Handlers
+/// Exports a proposal as a canonical bundle, the input of `miden-vote-verify`. The ETag is the SHA-256
+/// of the body, so identical state always exports to the same bytes and hash.
+pub async fn export_proposal(pool: web::Data<PgPool>, proposal_id: web::Path<Uuid>) -> Result<HttpResponse, ApiError> {
+    let proposal_id = proposal_id.into_inner();
+
+    // One snapshot, so the tally and the ballots it counted can't be read at different times
+    let mut transaction = pool.begin().await?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+        .execute(&mut *transaction)
+        .await?;
+    let bundle = load_bundle(&mut transaction, proposal_id).await?;
+    transaction.commit().await?;
+
+    // Serializing through Value sorts object keys
+    let body = serde_json::to_value(&bundle)
+        .and_then(|value| serde_json::to_vec(&value))
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .insert_header((header::ETAG, format!("\"{}\"", hex::encode(Sha256::digest(&body)))))
+        .insert_header((header::CONTENT_DISPOSITION, format!("attachment; filename=\"proposal-{}.json\"", proposal_id)))
+        .body(body))
+}
+
+/// Imports a bundle exported by another deployment, keeping every id. The project and its eligibility
+/// snapshot are created unless they already exist with the same root. Every counted ballot is verified
+/// again against this deployment's programs, logged on the bulletin board again, and recounted; the
+/// bulletin root, quorum, results and aggregate proof must all match what the bundle's tally recorded.
+/// Receipts are kept as the origin issued them and never signed here.
+pub async fn import_proposal(
+    pool: web::Data<PgPool>,
+    req: web::Json<ProposalBundle>,
+    programs: web::Data<ProgramHashes>,
+) -> Result<HttpResponse, ApiError> {
+    let bundle = req.into_inner();
+    Bundle::check_version(&bundle.format, bundle.version).map_err(ApiError::Validation)?;
+
+    let proposal_id = bundle.proposal.id;
+    let consistent = bundle.proposal.project_id == bundle.project.id
+        && bundle.eligibility.iter().all(|l| l.project_id == bundle.project.id)
+        && bundle.submissions.iter().all(|s| s.proposal_id == proposal_id)
+        && bundle.tally.iter().all(|t| t.proposal_id == proposal_id)
+        && bundle.election.iter().all(|e| e.proposal_id == proposal_id)
+        && bundle.trustees.iter().all(|t| t.proposal_id == proposal_id);
+    if !consistent {
+        return Err(ApiError::Validation("Bundle rows belong to different proposals or projects".to_string()));
+    }
+
+    let mut proofs = BTreeMap::new();
+    for hash in bundle.proof_hashes() {
+        let bytes = bundle
+            .proofs
+            .get(hash)
+            .and_then(|p| hex::decode(p.trim_start_matches("0x")).ok())
+            .filter(|bytes| &verifier::proof_hash(bytes) == hash)
+            .ok_or_else(|| ApiError::Validation(format!("Proof {} is missing or does not match its hash", hash)))?;
+        proofs.insert(hash.clone(), bytes);
+    }
+
+    // Verifying the proofs is CPU-bound, so keep it off the async workers
+    let (bundle, proofs, checked) = web::block({
+        let programs = programs.clone().into_inner();
+        move || {
+            let checked = check_counted_ballots(&bundle, &proofs, &programs);
+            (bundle, proofs, checked)
+        }
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    checked.map_err(ApiError::Validation)?;
+
+    let mut transaction = pool.begin().await?;
+
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM proposals WHERE id = $1)")
+        .bind(proposal_id)
+        .fetch_one(&mut *transaction)
+        .await?;
+    if exists {
+        return Err(ApiError::ImportConflict(format!("Proposal {} already exists", proposal_id)));
+    }
+
+    let project = &bundle.project;
+    let merkle_root = sqlx::query_scalar::<_, String>("SELECT merkle_root FROM projects WHERE id = $1 FOR UPDATE")
+        .bind(project.id)
+        .fetch_optional(&mut *transaction)
+        .await?;
+    match merkle_root {
+        Some(root) if root != project.merkle_root => {
+            return Err(ApiError::ImportConflict(format!(
+                "Project {} already exists with a different eligibility snapshot",
+                project.id
+            )));
+        }
+        Some(_) => {}
+        None => {
+            sqlx::query("INSERT INTO projects (id, owner, token_address, merkle_root, config, status, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7)")
+                .bind(project.id)
+                .bind(&project.owner)
+                .bind(&project.token_address)
+                .bind(&project.merkle_root)
+                .bind(&project.config)
+                .bind(&project.status)
+                .bind(project.created_at)
+                .execute(&mut *transaction)
+                .await?;
+
+            for leaf in &bundle.eligibility {
+                sqlx::query("INSERT INTO eligibility_leaves (project_id, leaf_index, address, weight, leaf_hash) VALUES ($1, $2, $3, $4, $5)")
+                    .bind(leaf.project_id)
+                    .bind(leaf.leaf_index)
+                    .bind(&leaf.address)
+                    .bind(leaf.weight)
+                    .bind(&leaf.leaf_hash)
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+        }
+    }
+
+    let proposal = &bundle.proposal;
+    let imported = sqlx::query_as::<_, Proposal>(
+        "INSERT INTO proposals (id, project_id, title, choices_json, model_enum, quorum, quorum_basis, start_ts, end_ts, state, revoked, finalized, eligibility_root, eligible_voters, eligible_weight, quorum_json, imported_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17) RETURNING *"
+    )
+    .bind(proposal.id)
+    .bind(proposal.project_id)
+    .bind(&proposal.title)
+    .bind(&proposal.choices_json)
+    .bind(&proposal.model_enum)
+    .bind(proposal.quorum)
+    .bind(&proposal.quorum_basis)
+    .bind(proposal.start_ts)
+    .bind(proposal.end_ts)
+    .bind(proposal.state)
+    .bind(proposal.revoked)
+    .bind(proposal.finalized)
+    .bind(&proposal.eligibility_root)
+    .bind(proposal.eligible_voters)
+    .bind(proposal.eligible_weight)
+    .bind(&proposal.quorum_json)
+    .bind(Utc::now().naive_utc())
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    if let Some(election) = &bundle.election {
+        sqlx::query("INSERT INTO elections (proposal_id, threshold, trustee_count, public_key, created_at) VALUES ($1, $2, $3, $4, $5)")
+            .bind(election.proposal_id)
+            .bind(election.threshold)
+            .bind(election.trustee_count)
+            .bind(&election.public_key)
+            .bind(election.created_at)
+            .execute(&mut *transaction)
+            .await?;
+    }
+    // Trustees are users of the origin deployment, so none of them is registered here
+    for trustee in &bundle.trustees {
+        sqlx::query(
+            "INSERT INTO election_trustees (proposal_id, trustee_index, transport_key, commitments, dealt_shares, dealt_at, shares_checked_at, disqualified, public_share, decryption_shares, shares_posted_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)"
+        )
+        .bind(trustee.proposal_id)
+        .bind(trustee.trustee_index)
+        .bind(&trustee.transport_key)
+        .bind(&trustee.commitments)
+        .bind(&trustee.dealt_shares)
+        .bind(trustee.dealt_at)
+        .bind(trustee.shares_checked_at)
+        .bind(trustee.disqualified)
+        .bind(&trustee.public_share)
+        .bind(&trustee.decryption_shares)
+        .bind(trustee.shares_posted_at)
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    for (hash, bytes) in &proofs {
+        sqlx::query("INSERT INTO proofs (proof_hash, proof_bytes) VALUES ($1, $2) ON CONFLICT (proof_hash) DO NOTHING")
+            .bind(hash)
+            .bind(bytes)
+            .execute(&mut *transaction)
+            .await?;
+    }
+
+    let mut submissions: Vec<&Submission> = bundle.submissions.iter().collect();
+    submissions.sort_by_key(|s| (s.inclusion_index.is_none(), s.inclusion_index, s.id));
+    for submission in submissions {
+        sqlx::query(
+            "INSERT INTO submissions (id, proposal_id, proof_hash, program_hash, public_inputs, stack_outputs, note_commitment, nullifier_hash, weight, ballot, encrypted_ballot, verified_bool, failure_reason, verified_at, inclusion_index, receipt_kid, receipt_signature) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)"
+        )
+        .bind(submission.id)
+        .bind(submission.proposal_id)
+        .bind(&submission.proof_hash)
+        .bind(&submission.program_hash)
+        .bind(&submission.public_inputs)
+        .bind(&submission.stack_outputs)
+        .bind(&submission.note_commitment)
+        .bind(&submission.nullifier_hash)
+        .bind(submission.weight)
+        .bind(&submission.ballot)
+        .bind(&submission.encrypted_ballot)
+        .bind(submission.verified_bool)
+        .bind(&submission.failure_reason)
+        .bind(submission.verified_at)
+        .bind(submission.inclusion_index)
+        .bind(&submission.receipt_kid) // Receipts travel with their ballots, as the origin signed them
+        .bind(&submission.receipt_signature)
+        .execute(&mut *transaction)
+        .await?;
+
+        // Entries are appended in inclusion order, so the log comes out identical to the exporter's
+        if bulletin::append(&mut transaction, submission).await?.is_none() && submission.verified_bool {
+            return Err(ApiError::Validation(format!("Counted submission {} has no inclusion index", submission.id)));
+        }
+    }
+
+    let counted = sqlx::query_as::<_, Submission>(
+        "SELECT * FROM submissions WHERE proposal_id = $1 AND verified_bool = TRUE ORDER BY inclusion_index"
+    )
+    .bind(proposal_id)
+    .fetch_all(&mut *transaction)
+    .await?;
+    let recount = || match (imported.eligible_voters, imported.eligible_weight) {
+        (Some(_), Some(_)) => tally_handlers::count_quorum(&imported, &counted),
+        _ => Err(ApiError::Validation("Bundle proposal has no eligibility snapshot to recount against".to_string())),
+    };
+
+    // A failed proposal must have missed quorum with exactly the report it records
+    if imported.state == ProposalState::Failed {
+        let (_, quorum) = recount()?;
+        if quorum.reached || serde_json::to_value(&quorum).ok() != imported.quorum_json {
+            return Err(ApiError::Validation("Bundle quorum report does not match a recount of its ballots".to_string()));
+        }
+    }
+
+    if let Some(tally) = &bundle.tally {
+        let published = bulletin::publish(&mut transaction, proposal_id).await?;
+        let committed = tally.bulletin_size.zip(tally.bulletin_root.as_ref());
+        if committed.is_some_and(|(size, root)| size != published.tree_size || *root != published.root) {
+            return Err(ApiError::BulletinMismatch);
+        }
+
+        // Recount exactly as tally_proposal did, and check the tally program's proof of these results
+        let (ballots, quorum) = recount()?;
+        let results = tally_handlers::count_results(&mut transaction, &imported, &ballots).await?;
+        if serde_json::to_value(&quorum).ok().as_ref() != Some(&tally.quorum_json)
+            || serde_json::to_value(&results).ok().as_ref() != Some(&tally.results_json)
+        {
+            return Err(ApiError::Validation("Bundle tally does not match a recount of its ballots".to_string()));
+        }
+
+        let (public_inputs, stack_outputs) = tally_handlers::aggregate_io(&proposal_id, &published, &results)?;
+        let proof_bytes = tally.aggregate_proof_hash.as_ref().and_then(|hash| proofs.get(hash));
+        let (Some(proof_bytes), Some(program_hash)) = (proof_bytes, &tally.aggregate_program_hash) else {
+            return Err(ApiError::Validation("Bundle tally has no aggregate proof".to_string()));
+        };
+        if !program_hash.eq_ignore_ascii_case(&programs.tally) {
+            return Err(ApiError::TallyProofRejected(format!(
+                "Aggregate proof is for program {}, this deployment runs {}",
+                program_hash, programs.tally
+            )));
+        }
+        let verification = {
+            let (proof_bytes, program_hash) = (proof_bytes.clone(), programs.tally.clone());
+            web::block(move || {
+                verifier::verify_proof(&ProgramProof {
+                    proof_bytes: &proof_bytes,
+                    program_hash: &program_hash,
+                    public_inputs: &public_inputs,
+                    stack_outputs: &stack_outputs,
+                })
+            })
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+        };
+        if !verification.verified {
+            let reason = verification.failure_reason.unwrap_or_else(|| "Aggregate proof verification failed".to_string());
+            return Err(ApiError::TallyProofRejected(reason));
+        }
+
+        sqlx::query(
+            "INSERT INTO tallies (id, proposal_id, aggregate_proof_hash, results_json, quorum_json, verified_at, bulletin_size, bulletin_root, aggregate_program_hash, aggregate_public_inputs, aggregate_stack_outputs) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)"
+        )
+        .bind(tally.id)
+        .bind(tally.proposal_id)
+        .bind(&tally.aggregate_proof_hash)
+        .bind(&tally.results_json)
+        .bind(&tally.quorum_json)
+        .bind(tally.verified_at)
+        .bind(tally.bulletin_size)
+        .bind(&tally.bulletin_root)
+        .bind(&tally.aggregate_program_hash)
+        .bind(&tally.aggregate_public_inputs)
+        .bind(&tally.aggregate_stack_outputs)
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    transaction.commit().await?;
+    Ok(HttpResponse::Created().json(imported))
+}
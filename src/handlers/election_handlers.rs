@@ -0,0 +1,558 @@
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::election::{self, Ciphertext, CiphertextHex, DecryptionShare, DleqProof, EncryptedBallot, EncryptedShare, ShareDisclosure};
+use crate::error::ApiError;
+use crate::lifecycle::ProposalState;
+use crate::models::{Election, ElectionTrustee, Proposal, Submission};
+use crate::permissions::{self, Permission};
+use crate::tally;
+use crate::AuthExtractor;
+
+// The election key is generated by the trustees, never by a dealer: each posts a transport key, then
+// deals every trustee a share of a random polynomial (Feldman commitments, shares encrypted to the
+// recipients' transport keys), then checks the shares dealt to them, complaining with a disclosure
+// about any that doesn't match its dealer's commitments. Once all have checked, the key is the sum of
+// the qualified dealers' constant terms, and nobody holds its secret.
+
+// DTOs for request bodies
+#[derive(Deserialize)]
+pub struct SetupElectionRequest {
+    pub threshold: i32,
+    pub trustees: Vec<Uuid>, // Users registered as trustees, trustee i at position i - 1
+}
+
+#[derive(Deserialize)]
+pub struct PostTransportKeyRequest {
+    pub transport_key: String, // e * G, which shares are dealt to the trustee under
+}
+
+#[derive(Deserialize)]
+pub struct PostDealingRequest {
+    pub commitments: Vec<String>, // a_k * G for k < threshold
+    pub proof: DleqProof,         // Knowledge of a_0, see election::verify_dealing
+    pub shares: Vec<EncryptedShare>, // f(j) for trustee j at position j - 1
+}
+
+#[derive(Deserialize)]
+pub struct Complaint {
+    pub dealer_index: i32,
+    pub disclosure: ShareDisclosure, // Opens the share the dealer dealt the complaining trustee
+}
+
+#[derive(Deserialize)]
+pub struct PostComplaintsRequest {
+    pub complaints: Vec<Complaint>, // Empty when every dealt share matched its commitments
+}
+
+#[derive(Deserialize)]
+pub struct PostSharesRequest {
+    pub shares: Vec<DecryptionShare>, // One per aggregate ciphertext, in order
+}
+
+#[derive(Serialize)]
+pub struct TrusteeInfo {
+    pub trustee_index: i32,
+    pub user_id: Option<Uuid>,
+    pub transport_key: Option<String>,
+    pub commitments: Option<serde_json::Value>,
+    pub dealt_shares: Option<serde_json::Value>,
+    pub shares_checked: bool,
+    pub disqualified: bool,
+    pub public_share: Option<String>,
+    pub shares_posted: bool,
+}
+
+impl From<ElectionTrustee> for TrusteeInfo {
+    fn from(t: ElectionTrustee) -> Self {
+        TrusteeInfo {
+            trustee_index: t.trustee_index,
+            user_id: t.user_id,
+            transport_key: t.transport_key,
+            commitments: t.commitments,
+            dealt_shares: t.dealt_shares,
+            shares_checked: t.shares_checked_at.is_some(),
+            disqualified: t.disqualified,
+            public_share: t.public_share,
+            shares_posted: t.decryption_shares.is_some(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ElectionResponse {
+    pub proposal_id: Uuid,
+    pub threshold: i32,
+    pub trustee_count: i32,
+    pub public_key: Option<String>, // Set once every trustee has checked their dealt shares
+    pub trustees: Vec<TrusteeInfo>,
+    pub aggregate: Option<Vec<CiphertextHex>>, // Published once voting has closed
+}
+
+// Handlers
+/// Registers the trustees and threshold of a proposal's election. Its key is generated by the
+/// trustees afterwards; the proposal doesn't open for voting until it is.
+pub async fn setup_election(
+    pool: web::Data<PgPool>,
+    proposal_id: web::Path<Uuid>,
+    req: web::Json<SetupElectionRequest>,
+    auth: AuthExtractor,
+) -> Result<HttpResponse, ApiError> {
+    let proposal_id = proposal_id.into_inner();
+    let mut transaction = pool.begin().await?;
+
+    let proposal = sqlx::query_as::<_, Proposal>("SELECT * FROM proposals WHERE id = $1")
+        .bind(proposal_id)
+        .fetch_optional(&mut *transaction)
+        .await?
+        .ok_or(ApiError::ProposalNotFound)?;
+
+    permissions::require_in_project(&mut transaction, &auth, Permission::ManageProposals, proposal.project_id).await?;
+
+    // Voters encrypt to the key, so it has to be fixed before voting opens
+    require_keyable(&proposal)?;
+    if !tally::engine_for(&proposal.model_enum).is_some_and(|e| e.supports_encryption()) {
+        return Err(ApiError::Validation(format!("{} ballots can't be encrypted", proposal.model_enum)));
+    }
+
+    // Every eligible voter may cast one ballot, so the snapshot's total weight bounds the encrypted totals
+    let eligible_weight = sqlx::query_scalar::<_, i64>(
+        "SELECT COALESCE(SUM(GREATEST(weight, 1)), 0)::BIGINT FROM eligibility_leaves WHERE project_id = $1"
+    )
+    .bind(proposal.project_id)
+    .fetch_one(&mut *transaction)
+    .await?;
+    if eligible_weight as u64 > election::MAX_WEIGHT_TOTAL {
+        return Err(ApiError::Validation(format!(
+            "The eligibility snapshot's total weight exceeds {}, the most an election can tally; rescale the weights",
+            election::MAX_WEIGHT_TOTAL
+        )));
+    }
+
+    if req.threshold < 1 || req.threshold as usize > req.trustees.len() {
+        return Err(ApiError::Validation("Threshold must be between 1 and the number of trustees".to_string()));
+    }
+    if req.trustees.iter().collect::<HashSet<_>>().len() != req.trustees.len() {
+        return Err(ApiError::Validation("Each trustee can only be registered once".to_string()));
+    }
+
+    let election = match sqlx::query_as::<_, Election>(
+        "INSERT INTO elections (proposal_id, threshold, trustee_count, created_at) VALUES ($1, $2, $3, $4) RETURNING *"
+    )
+    .bind(proposal_id)
+    .bind(req.threshold)
+    .bind(req.trustees.len() as i32)
+    .bind(Utc::now().naive_utc())
+    .fetch_one(&mut *transaction)
+    .await
+    {
+        Ok(e) => e,
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => return Err(ApiError::ElectionExists),
+        Err(e) => return Err(e.into()),
+    };
+
+    for (i, user_id) in req.trustees.iter().enumerate() {
+        match sqlx::query("INSERT INTO election_trustees (proposal_id, trustee_index, user_id) VALUES ($1, $2, $3)")
+            .bind(proposal_id)
+            .bind(i as i32 + 1)
+            .bind(user_id)
+            .execute(&mut *transaction)
+            .await
+        {
+            Ok(_) => {}
+            // Trustees must be registered users
+            Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => return Err(ApiError::UserNotFound),
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Created().json(election))
+}
+
+pub async fn get_election(pool: web::Data<PgPool>, proposal_id: web::Path<Uuid>) -> Result<HttpResponse, ApiError> {
+    let proposal_id = proposal_id.into_inner();
+    let mut conn = pool.acquire().await?;
+
+    let (proposal, election, trustees) = load_election(&mut conn, proposal_id).await?.ok_or(ApiError::ElectionNotFound)?;
+
+    let aggregate = if is_closed(&proposal) {
+        let aggregate = encrypted_aggregate(&mut conn, &proposal).await?;
+        Some(aggregate.iter().map(Ciphertext::to_hex).collect())
+    } else {
+        None
+    };
+
+    Ok(HttpResponse::Ok().json(ElectionResponse {
+        proposal_id,
+        threshold: election.threshold,
+        trustee_count: election.trustee_count,
+        public_key: election.public_key,
+        trustees: trustees.into_iter().map(TrusteeInfo::from).collect(),
+        aggregate,
+    }))
+}
+
+/// First step of key generation: the calling trustee's transport key.
+pub async fn post_transport_key(
+    pool: web::Data<PgPool>,
+    proposal_id: web::Path<Uuid>,
+    req: web::Json<PostTransportKeyRequest>,
+    auth: AuthExtractor,
+) -> Result<HttpResponse, ApiError> {
+    let proposal_id = proposal_id.into_inner();
+    let mut transaction = pool.begin().await?;
+
+    let (_, _, trustee) = lock_key_generation(&mut transaction, proposal_id, &auth).await?;
+
+    if trustee.transport_key.is_some() {
+        return Err(ApiError::KeyGenerationConflict("Transport key already posted".to_string()));
+    }
+    if election::point_from_hex(&req.transport_key).is_none() {
+        return Err(ApiError::Validation("Malformed transport key".to_string()));
+    }
+
+    let t = sqlx::query_as::<_, ElectionTrustee>(
+        "UPDATE election_trustees SET transport_key = $1 WHERE proposal_id = $2 AND trustee_index = $3 RETURNING *"
+    )
+    .bind(&req.transport_key)
+    .bind(proposal_id)
+    .bind(trustee.trustee_index)
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().json(TrusteeInfo::from(t)))
+}
+
+/// Second step: the calling trustee's dealing, once every trustee has a transport key.
+pub async fn post_dealing(
+    pool: web::Data<PgPool>,
+    proposal_id: web::Path<Uuid>,
+    req: web::Json<PostDealingRequest>,
+    auth: AuthExtractor,
+) -> Result<HttpResponse, ApiError> {
+    let proposal_id = proposal_id.into_inner();
+    let mut transaction = pool.begin().await?;
+
+    let (election, trustees, trustee) = lock_key_generation(&mut transaction, proposal_id, &auth).await?;
+
+    if trustees.iter().any(|t| t.transport_key.is_none()) {
+        return Err(ApiError::KeyGenerationConflict("Waiting for every trustee's transport key".to_string()));
+    }
+    if trustee.dealt_at.is_some() {
+        return Err(ApiError::KeyGenerationConflict("Shares already dealt".to_string()));
+    }
+
+    if req.commitments.len() != election.threshold as usize {
+        return Err(ApiError::Validation(format!("Expected {} commitments, one per coefficient", election.threshold)));
+    }
+    let commitments: Option<Vec<_>> = req.commitments.iter().map(|c| election::point_from_hex(c)).collect();
+    let commitments = commitments.ok_or_else(|| ApiError::Validation("Malformed commitment".to_string()))?;
+    let context = election::dealing_context(&proposal_id, trustee.trustee_index as u64);
+    if !election::verify_dealing(&commitments, &req.proof, &context) {
+        return Err(ApiError::Validation("Invalid proof of the dealing's constant term".to_string()));
+    }
+
+    if req.shares.len() != trustees.len() {
+        return Err(ApiError::Validation(format!("Expected {} dealt shares, one per trustee", trustees.len())));
+    }
+    if req.shares.iter().any(|s| s.parse().is_none()) {
+        return Err(ApiError::Validation("Malformed dealt share".to_string()));
+    }
+
+    let t = sqlx::query_as::<_, ElectionTrustee>(
+        "UPDATE election_trustees SET commitments = $1, dealt_shares = $2, dealt_at = $3 WHERE proposal_id = $4 AND trustee_index = $5 RETURNING *"
+    )
+    .bind(serde_json::json!(req.commitments))
+    .bind(serde_json::json!(req.shares))
+    .bind(Utc::now().naive_utc())
+    .bind(proposal_id)
+    .bind(trustee.trustee_index)
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().json(TrusteeInfo::from(t)))
+}
+
+/// Last step: the calling trustee's complaints about the shares dealt to them, once every trustee has
+/// dealt. A complaint disqualifies its dealer if the disclosed share doesn't match their commitments.
+/// The key is generated when the last trustee has checked their shares.
+pub async fn post_complaints(
+    pool: web::Data<PgPool>,
+    proposal_id: web::Path<Uuid>,
+    req: web::Json<PostComplaintsRequest>,
+    auth: AuthExtractor,
+) -> Result<HttpResponse, ApiError> {
+    let proposal_id = proposal_id.into_inner();
+    let mut transaction = pool.begin().await?;
+
+    let (_, mut trustees, trustee) = lock_key_generation(&mut transaction, proposal_id, &auth).await?;
+
+    if trustees.iter().any(|t| t.dealt_at.is_none()) {
+        return Err(ApiError::KeyGenerationConflict("Waiting for every trustee's dealing".to_string()));
+    }
+    if trustee.shares_checked_at.is_some() {
+        return Err(ApiError::KeyGenerationConflict("Dealt shares already checked".to_string()));
+    }
+
+    let transport_key = trustee.transport_key.as_deref().and_then(election::point_from_hex);
+    let transport_key = transport_key.ok_or_else(|| ApiError::Internal(format!("Trustee {} has an invalid transport key", trustee.trustee_index)))?;
+
+    for complaint in &req.complaints {
+        let dealer = trustees
+            .iter_mut()
+            .find(|t| t.trustee_index == complaint.dealer_index)
+            .ok_or(ApiError::TrusteeNotFound)?;
+        if dealer.trustee_index == trustee.trustee_index {
+            return Err(ApiError::Validation("A trustee can't complain about their own dealing".to_string()));
+        }
+
+        let (commitments, shares) = dealing(dealer)
+            .ok_or_else(|| ApiError::Internal(format!("Trustee {} has a malformed dealing", dealer.trustee_index)))?;
+        let context = election::dealing_context(&proposal_id, dealer.trustee_index as u64);
+        let share = election::disclosed_share(&transport_key, &shares[trustee.trustee_index as usize - 1], &complaint.disclosure, &context)
+            .ok_or_else(|| ApiError::Validation(format!("Disclosure does not open the share trustee {} dealt", dealer.trustee_index)))?;
+        if election::verify_dealt_share(&commitments, trustee.trustee_index as u64, &share) {
+            return Err(ApiError::Validation(format!("The share trustee {} dealt matches their commitments", dealer.trustee_index)));
+        }
+
+        sqlx::query("UPDATE election_trustees SET disqualified = TRUE WHERE proposal_id = $1 AND trustee_index = $2")
+            .bind(proposal_id)
+            .bind(dealer.trustee_index)
+            .execute(&mut *transaction)
+            .await?;
+        dealer.disqualified = true;
+    }
+
+    let now = Utc::now().naive_utc();
+    sqlx::query("UPDATE election_trustees SET shares_checked_at = $1 WHERE proposal_id = $2 AND trustee_index = $3")
+        .bind(now)
+        .bind(proposal_id)
+        .bind(trustee.trustee_index)
+        .execute(&mut *transaction)
+        .await?;
+    for t in trustees.iter_mut().filter(|t| t.trustee_index == trustee.trustee_index) {
+        t.shares_checked_at = Some(now);
+    }
+
+    if trustees.iter().all(|t| t.shares_checked_at.is_some()) {
+        generate_key(&mut transaction, proposal_id, &mut trustees).await?;
+    }
+
+    transaction.commit().await?;
+
+    let t = trustees.into_iter().find(|t| t.trustee_index == trustee.trustee_index).unwrap_or(trustee);
+    Ok(HttpResponse::Ok().json(TrusteeInfo::from(t)))
+}
+
+pub async fn post_decryption_shares(
+    pool: web::Data<PgPool>,
+    proposal_id: web::Path<Uuid>,
+    req: web::Json<PostSharesRequest>,
+    auth: AuthExtractor,
+) -> Result<HttpResponse, ApiError> {
+    let proposal_id = proposal_id.into_inner();
+    let mut conn = pool.acquire().await?;
+
+    let (proposal, _, trustees) = load_election(&mut conn, proposal_id).await?.ok_or(ApiError::ElectionNotFound)?;
+
+    // Only the trustee registered for an index posts its shares
+    let trustee = trustees
+        .iter()
+        .find(|t| t.user_id == Some(auth.user_id))
+        .ok_or(ApiError::NotTrustee)?;
+
+    if proposal.state != ProposalState::Closed {
+        return Err(ApiError::InvalidState { action: "decrypted", state: proposal.state });
+    }
+
+    let aggregate = encrypted_aggregate(&mut conn, &proposal).await?;
+
+    if req.shares.len() != aggregate.len() {
+        return Err(ApiError::Validation(format!("Expected {} decryption shares", aggregate.len())));
+    }
+
+    // Shares prove themselves: only the holder of x_i can produce a valid proof against x_i * G
+    let public_share = trustee.public_share.as_deref().and_then(election::point_from_hex);
+    let valid = public_share.is_some_and(|pk| {
+        aggregate
+            .iter()
+            .zip(&req.shares)
+            .all(|(ct, share)| election::verify_share(&pk, ct, share).is_some())
+    });
+    if !valid {
+        return Err(ApiError::InvalidDecryptionShare);
+    }
+
+    let t = sqlx::query_as::<_, ElectionTrustee>(
+        "UPDATE election_trustees SET decryption_shares = $1, shares_posted_at = $2 WHERE proposal_id = $3 AND trustee_index = $4 RETURNING *"
+    )
+    .bind(serde_json::json!(req.shares))
+    .bind(Utc::now().naive_utc())
+    .bind(proposal_id)
+    .bind(trustee.trustee_index)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(HttpResponse::Ok().json(TrusteeInfo::from(t)))
+}
+
+fn require_keyable(proposal: &Proposal) -> Result<(), ApiError> {
+    match proposal.state {
+        ProposalState::Draft | ProposalState::Scheduled => Ok(()),
+        state => Err(ApiError::InvalidState { action: "given an election", state }),
+    }
+}
+
+// Locks the election of a proposal whose key is still being generated, returning it with its trustees
+// and the one `auth` is registered as. Steps of the key generation are serialized on the lock, so
+// exactly one of them sees the last trustee finish.
+async fn lock_key_generation(
+    conn: &mut PgConnection,
+    proposal_id: Uuid,
+    auth: &AuthExtractor,
+) -> Result<(Election, Vec<ElectionTrustee>, ElectionTrustee), ApiError> {
+    let proposal = sqlx::query_as::<_, Proposal>("SELECT * FROM proposals WHERE id = $1")
+        .bind(proposal_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(ApiError::ProposalNotFound)?;
+
+    let election = sqlx::query_as::<_, Election>("SELECT * FROM elections WHERE proposal_id = $1 FOR UPDATE")
+        .bind(proposal_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(ApiError::ElectionNotFound)?;
+
+    let trustees = sqlx::query_as::<_, ElectionTrustee>("SELECT * FROM election_trustees WHERE proposal_id = $1 ORDER BY trustee_index")
+        .bind(proposal_id)
+        .fetch_all(&mut *conn)
+        .await?;
+    let trustee = trustees
+        .iter()
+        .find(|t| t.user_id == Some(auth.user_id))
+        .cloned()
+        .ok_or(ApiError::NotTrustee)?;
+
+    require_keyable(&proposal)?;
+    if election.public_key.is_some() {
+        return Err(ApiError::KeyGenerationConflict("The election key has already been generated".to_string()));
+    }
+
+    Ok((election, trustees, trustee))
+}
+
+// A trustee's posted commitments and encrypted shares
+fn dealing(trustee: &ElectionTrustee) -> Option<(Vec<RistrettoPoint>, Vec<EncryptedShare>)> {
+    let commitments: Vec<String> = serde_json::from_value(trustee.commitments.clone()?).ok()?;
+    let commitments: Option<Vec<_>> = commitments.iter().map(|c| election::point_from_hex(c)).collect();
+    let shares = serde_json::from_value(trustee.dealt_shares.clone()?).ok()?;
+    Some((commitments?, shares))
+}
+
+// Sets the election key and every trustee's public share from the qualified dealings
+async fn generate_key(conn: &mut PgConnection, proposal_id: Uuid, trustees: &mut [ElectionTrustee]) -> Result<(), ApiError> {
+    let dealings: Option<Vec<_>> = trustees
+        .iter()
+        .filter(|t| !t.disqualified)
+        .map(|t| dealing(t).map(|(commitments, _)| commitments))
+        .collect();
+    let dealings = dealings.ok_or_else(|| ApiError::Internal("A qualified trustee has a malformed dealing".to_string()))?;
+    // With every dealer disqualified there is nothing to build a key from; the election stays unkeyed
+    if dealings.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query("UPDATE elections SET public_key = $1 WHERE proposal_id = $2")
+        .bind(election::point_to_hex(&election::election_key(&dealings)))
+        .bind(proposal_id)
+        .execute(&mut *conn)
+        .await?;
+
+    for trustee in trustees {
+        let public_share = election::point_to_hex(&election::public_share(&dealings, trustee.trustee_index as u64));
+        sqlx::query("UPDATE election_trustees SET public_share = $1 WHERE proposal_id = $2 AND trustee_index = $3")
+            .bind(&public_share)
+            .bind(proposal_id)
+            .bind(trustee.trustee_index)
+            .execute(&mut *conn)
+            .await?;
+        trustee.public_share = Some(public_share);
+    }
+
+    Ok(())
+}
+
+fn is_closed(proposal: &Proposal) -> bool {
+    matches!(
+        proposal.state,
+        ProposalState::Closed | ProposalState::Tallied | ProposalState::Finalized
+    )
+}
+
+pub(crate) async fn load_election(
+    conn: &mut PgConnection,
+    proposal_id: Uuid,
+) -> Result<Option<(Proposal, Election, Vec<ElectionTrustee>)>, sqlx::Error> {
+    let proposal = match sqlx::query_as::<_, Proposal>("SELECT * FROM proposals WHERE id = $1")
+        .bind(proposal_id)
+        .fetch_optional(&mut *conn)
+        .await?
+    {
+        Some(p) => p,
+        None => return Ok(None),
+    };
+
+    let election = match sqlx::query_as::<_, Election>("SELECT * FROM elections WHERE proposal_id = $1")
+        .bind(proposal_id)
+        .fetch_optional(&mut *conn)
+        .await?
+    {
+        Some(e) => e,
+        None => return Ok(None),
+    };
+
+    let trustees = sqlx::query_as::<_, ElectionTrustee>("SELECT * FROM election_trustees WHERE proposal_id = $1 ORDER BY trustee_index")
+        .bind(proposal_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(Some((proposal, election, trustees)))
+}
+
+/// Homomorphic sum of every verified ballot of the proposal, see `election::aggregate`.
+pub(crate) async fn encrypted_aggregate(conn: &mut PgConnection, proposal: &Proposal) -> Result<Vec<Ciphertext>, ApiError> {
+    let choices = tally::parse_choices(&proposal.choices_json)
+        .ok_or_else(|| ApiError::Validation("Proposal choices must be a non-empty array of strings".to_string()))?;
+    let engine = tally::engine_for(&proposal.model_enum).ok_or_else(|| ApiError::UnknownVotingModel(proposal.model_enum.clone()))?;
+
+    let submissions = sqlx::query_as::<_, Submission>("SELECT * FROM submissions WHERE proposal_id = $1 AND verified_bool = TRUE")
+        .bind(proposal.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let mut ballots = Vec::with_capacity(submissions.len());
+    for submission in submissions {
+        let ballot: EncryptedBallot = submission
+            .encrypted_ballot
+            .and_then(|b| serde_json::from_value(b).ok())
+            .ok_or_else(|| ApiError::Internal(format!("Submission {} has no encrypted ballot", submission.id)))?;
+        let ciphertexts: Option<Vec<_>> = ballot.choices.iter().map(|c| Ciphertext::parse(&c.ciphertext)).collect();
+        let weight = submission.weight as u64;
+        let power = (engine.voting_power(weight) * election::POWER_SCALE).round() as u64;
+        let ciphertexts = ciphertexts.ok_or_else(|| ApiError::Internal(format!("Submission {} has a malformed ciphertext", submission.id)))?;
+        ballots.push((weight, power, ciphertexts));
+    }
+
+    Ok(election::aggregate(choices.len(), &ballots))
+}
@@ -0,0 +1,151 @@
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::lifecycle::ProposalState;
+use crate::merkle::{self, MerkleTree};
+use crate::models::EligibilityLeaf;
+use crate::permissions::{self, Permission};
+use crate::AuthExtractor;
+
+// DTOs for request bodies
+#[derive(Deserialize)]
+pub struct SnapshotEntry {
+    pub address: String,
+    pub weight: u64,
+}
+
+#[derive(Deserialize)]
+pub struct UploadSnapshotRequest {
+    pub leaves: Vec<SnapshotEntry>,
+}
+
+#[derive(Serialize)]
+pub struct SnapshotResponse {
+    pub project_id: Uuid,
+    pub merkle_root: String,
+    pub leaf_count: usize,
+    pub total_weight: u64,
+}
+
+#[derive(Serialize)]
+pub struct EligibilityProof {
+    pub address: String,
+    pub weight: i64,
+    pub leaf_index: i32,
+    pub leaf_hash: String,
+    pub path: Vec<String>, // Sibling hashes, leaf level first
+    pub merkle_root: String,
+}
+
+// Handlers
+pub async fn upload_snapshot(
+    pool: web::Data<PgPool>,
+    project_id: web::Path<Uuid>,
+    req: web::Json<UploadSnapshotRequest>,
+    auth: AuthExtractor,
+) -> Result<HttpResponse, ApiError> {
+    let project_id = project_id.into_inner();
+
+    let mut seen = HashSet::new();
+    let mut total_weight: u64 = 0;
+    for entry in &req.leaves {
+        if !seen.insert(entry.address.as_str()) {
+            return Err(ApiError::Validation(format!("Duplicate address in snapshot: {}", entry.address)));
+        }
+        // The total is stored as a BIGINT when a proposal freezes the snapshot, so it must fit too
+        total_weight = total_weight
+            .checked_add(entry.weight)
+            .filter(|&total| total <= i64::MAX as u64)
+            .ok_or_else(|| ApiError::Validation("Total snapshot weight is out of range".to_string()))?;
+    }
+
+    // Sort by address so the root doesn't depend on upload order
+    let mut entries: Vec<&SnapshotEntry> = req.leaves.iter().collect();
+    entries.sort_by(|a, b| a.address.cmp(&b.address));
+
+    let leaf_hashes: Vec<_> = entries.iter().map(|e| merkle::leaf_hash(&e.address, e.weight)).collect();
+    let tree = MerkleTree::new(leaf_hashes.clone());
+    let merkle_root = merkle::digest_to_hex(&tree.root());
+
+    let mut transaction = pool.begin().await?;
+
+    permissions::require_in_project(&mut transaction, &auth, Permission::ManageEligibility, project_id).await?;
+
+    // Voters prove membership against the snapshot, so it's frozen while any proposal is open to
+    // them. Locking every proposal that could be published makes a concurrent publish wait for this.
+    let states = sqlx::query_scalar::<_, ProposalState>(
+        "SELECT state FROM proposals WHERE project_id = $1 AND state IN ('draft', 'scheduled', 'active') FOR UPDATE"
+    )
+    .bind(project_id)
+    .fetch_all(&mut *transaction)
+    .await?;
+    if states.iter().any(|s| matches!(s, ProposalState::Scheduled | ProposalState::Active)) {
+        return Err(ApiError::SnapshotLocked);
+    }
+
+    let updated = sqlx::query("UPDATE projects SET merkle_root = $1 WHERE id = $2")
+        .bind(&merkle_root)
+        .bind(project_id)
+        .execute(&mut *transaction)
+        .await?;
+    if updated.rows_affected() == 0 {
+        return Err(ApiError::ProjectNotFound);
+    }
+
+    // A snapshot replaces the previous one wholesale
+    sqlx::query("DELETE FROM eligibility_leaves WHERE project_id = $1")
+        .bind(project_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    for (index, (entry, hash)) in entries.iter().zip(&leaf_hashes).enumerate() {
+        sqlx::query("INSERT INTO eligibility_leaves (project_id, leaf_index, address, weight, leaf_hash) VALUES ($1, $2, $3, $4, $5)")
+            .bind(project_id)
+            .bind(index as i32)
+            .bind(&entry.address)
+            .bind(entry.weight as i64)
+            .bind(merkle::digest_to_hex(hash))
+            .execute(&mut *transaction)
+            .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().json(SnapshotResponse {
+        project_id,
+        merkle_root,
+        leaf_count: entries.len(),
+        total_weight,
+    }))
+}
+
+pub async fn get_eligibility_proof(pool: web::Data<PgPool>, path: web::Path<(Uuid, String)>) -> Result<HttpResponse, ApiError> {
+    let (project_id, address) = path.into_inner();
+
+    let leaves = sqlx::query_as::<_, EligibilityLeaf>("SELECT * FROM eligibility_leaves WHERE project_id = $1 ORDER BY leaf_index")
+        .bind(project_id)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    let leaf = leaves.iter().find(|l| l.address == address).cloned().ok_or(ApiError::NotEligible)?;
+
+    let leaf_hashes = leaves
+        .iter()
+        .map(|l| merkle::leaf_hash(&l.address, l.weight as u64))
+        .collect();
+    let tree = MerkleTree::new(leaf_hashes);
+    let path = tree.path(leaf.leaf_index as usize).unwrap_or_default();
+
+    Ok(HttpResponse::Ok().json(EligibilityProof {
+        address: leaf.address,
+        weight: leaf.weight,
+        leaf_index: leaf.leaf_index,
+        leaf_hash: leaf.leaf_hash,
+        path: path.iter().map(merkle::digest_to_hex).collect(),
+        merkle_root: merkle::digest_to_hex(&tree.root()),
+    }))
+}
@@ -0,0 +1,34 @@
+use actix_web::http::header;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::events::{self, EventBus, EventKind, ProposalEvent};
+use crate::lifecycle::ProposalState;
+
+/// Streams a proposal's events as server-sent events, starting with a snapshot of its state and turnout.
+pub async fn proposal_events(
+    pool: web::Data<PgPool>,
+    proposal_id: web::Path<Uuid>,
+    bus: web::Data<EventBus>,
+) -> Result<HttpResponse, ApiError> {
+    let proposal_id = proposal_id.into_inner();
+
+    // Subscribe first, so nothing published while the snapshot is read is lost
+    let receiver = bus.subscribe();
+    let (state, ballots_counted) = sqlx::query_as::<_, (ProposalState, i64)>(
+        "SELECT p.state, (SELECT COUNT(*) FROM submissions s WHERE s.proposal_id = p.id AND s.verified_bool = TRUE) FROM proposals p WHERE p.id = $1"
+    )
+    .bind(proposal_id)
+    .fetch_optional(pool.get_ref())
+    .await?
+    .ok_or(ApiError::ProposalNotFound)?;
+
+    let snapshot = ProposalEvent::new(proposal_id, EventKind::Snapshot { state, ballots_counted });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(events::stream(proposal_id, receiver, snapshot)))
+}
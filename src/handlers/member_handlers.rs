@@ -0,0 +1,81 @@
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::models::ProjectMember;
+use crate::permissions::{self, Permission, ProjectRole};
+use crate::AuthExtractor;
+
+// DTOs for request bodies
+#[derive(serde::Deserialize)]
+pub struct SetMemberRequest {
+    pub role: ProjectRole,
+}
+
+// Handlers
+pub async fn list_members(pool: web::Data<PgPool>, project_id: web::Path<Uuid>, auth: AuthExtractor) -> Result<HttpResponse, ApiError> {
+    let project_id = project_id.into_inner();
+    let mut conn = pool.acquire().await?;
+    permissions::require_in_project(&mut conn, &auth, Permission::ManageMembers, project_id).await?;
+
+    let members = sqlx::query_as::<_, ProjectMember>("SELECT * FROM project_members WHERE project_id = $1 ORDER BY created_at")
+        .bind(project_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(members))
+}
+
+pub async fn set_member(
+    pool: web::Data<PgPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: web::Json<SetMemberRequest>,
+    auth: AuthExtractor,
+) -> Result<HttpResponse, ApiError> {
+    let (project_id, user_id) = path.into_inner();
+    let mut conn = pool.acquire().await?;
+    permissions::require_in_project(&mut conn, &auth, Permission::ManageMembers, project_id).await?;
+
+    let project_exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM projects WHERE id = $1)")
+        .bind(project_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    if !project_exists {
+        return Err(ApiError::ProjectNotFound);
+    }
+
+    match sqlx::query_as::<_, ProjectMember>(
+        "INSERT INTO project_members (project_id, user_id, role, created_at) VALUES ($1, $2, $3, $4) ON CONFLICT (project_id, user_id) DO UPDATE SET role = EXCLUDED.role RETURNING *"
+    )
+    .bind(project_id)
+    .bind(user_id)
+    .bind(req.role)
+    .bind(Utc::now().naive_utc())
+    .fetch_one(&mut *conn)
+    .await
+    {
+        Ok(member) => Ok(HttpResponse::Ok().json(member)),
+        // Members must be registered users
+        Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => Err(ApiError::UserNotFound),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn remove_member(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>, auth: AuthExtractor) -> Result<HttpResponse, ApiError> {
+    let (project_id, user_id) = path.into_inner();
+    let mut conn = pool.acquire().await?;
+    permissions::require_in_project(&mut conn, &auth, Permission::ManageMembers, project_id).await?;
+
+    let removed = sqlx::query("DELETE FROM project_members WHERE project_id = $1 AND user_id = $2")
+        .bind(project_id)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    if removed.rows_affected() == 0 {
+        return Err(ApiError::MemberNotFound);
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
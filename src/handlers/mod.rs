@@ -1,6 +1,14 @@
 pub mod project_handlers;
+pub mod eligibility_handlers;
+pub mod election_handlers;
+pub mod member_handlers;
 pub mod proposal_handlers;
 pub mod submission_handlers;
 pub mod tally_handlers;
 pub mod auth_handlers;
 pub mod user_handlers;
+pub mod wallet_handlers;
+pub mod bulletin_handlers;
+pub mod bundle_handlers;
+pub mod event_handlers;
+pub mod webhook_handlers;
@@ -1,8 +1,10 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpResponse};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::error::ApiError;
 use crate::models::Project;
+use crate::pagination::{PageQuery, SortKey};
 
 #[derive(serde::Deserialize)]
 pub struct CreateProjectPayload {
@@ -12,59 +14,72 @@ pub struct CreateProjectPayload {
     config: serde_json::Value,
 }
 
-pub async fn create_project(payload: web::Json<CreateProjectPayload>, pool: web::Data<PgPool>, auth: AuthExtractor) -> impl Responder {
-    if auth.role != "admin" {
-        return HttpResponse::Unauthorized().body("Only admins can create projects");
-    }
-
+pub async fn create_project(payload: web::Json<CreateProjectPayload>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
     let project = Project {
         id: Uuid::new_v4(),
         owner: payload.owner.clone(),
         token_address: payload.token_address.clone(),
         merkle_root: payload.merkle_root.clone(),
         config: payload.config.clone(),
+        status: "active".to_string(),
         created_at: chrono::Utc::now().naive_utc(),
     };
 
-    match sqlx::query_as::<_, Project>("INSERT INTO projects (id, owner, token_address, merkle_root, config, created_at) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *")
+    let project = sqlx::query_as::<_, Project>("INSERT INTO projects (id, owner, token_address, merkle_root, config, status, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *")
         .bind(project.id)
         .bind(&project.owner)
         .bind(&project.token_address)
         .bind(&project.merkle_root)
         .bind(&project.config)
+        .bind(&project.status)
         .bind(project.created_at)
         .fetch_one(pool.get_ref())
-        .await
-    {
-        Ok(project) => HttpResponse::Ok().json(project),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
+        .await?;
+
+    Ok(HttpResponse::Ok().json(project))
 }
 
-pub async fn get_project(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
+pub async fn get_project(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
     let project_id = path.into_inner();
-    match sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE id = $1")
+    let project = sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE id = $1")
         .bind(project_id)
-        .fetch_one(pool.get_ref())
-        .await
-    {
-        Ok(project) => HttpResponse::Ok().json(project),
-        Err(_) => HttpResponse::NotFound().body("Project not found"),
-    }
+        .fetch_optional(pool.get_ref())
+        .await?
+        .ok_or(ApiError::ProjectNotFound)?;
+
+    Ok(HttpResponse::Ok().json(project))
 }
 
-pub async fn get_all_projects(pool: web::Data<PgPool>, auth: AuthExtractor) -> impl Responder {
-    if auth.role != "platform_owner" && auth.role != "project_admin" {
-        return HttpResponse::Unauthorized().body("Only platform owners and project admins can view all projects");
-    }
+const PROJECT_SORTS: &[SortKey] = &[
+    SortKey { name: "created_at", column: "created_at", sql_type: "timestamp" },
+    SortKey { name: "owner", column: "owner", sql_type: "text" },
+];
 
-    match sqlx::query_as::<_, Project>("SELECT * FROM projects")
-        .fetch_all(pool.get_ref())
-        .await
-    {
-        Ok(projects) => HttpResponse::Ok().json(projects),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
+#[derive(serde::Deserialize)]
+pub struct ProjectFilter {
+    pub status: Option<String>,
+    pub owner: Option<String>,
+}
+
+pub async fn get_all_projects(
+    page: web::Query<PageQuery>,
+    filter: web::Query<ProjectFilter>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ApiError> {
+    let pagination = page.parse(PROJECT_SORTS)?;
+    let projects = pagination
+        .fetch::<Project>(pool.get_ref(), |query| {
+            query.push("SELECT * FROM projects WHERE TRUE");
+            if let Some(status) = &filter.status {
+                query.push(" AND status = ").push_bind(status.clone());
+            }
+            if let Some(owner) = &filter.owner {
+                query.push(" AND owner = ").push_bind(owner.clone());
+            }
+        })
+        .await?;
+
+    Ok(HttpResponse::Ok().json(projects))
 }
 
 #[derive(serde::Deserialize)]
@@ -72,25 +87,20 @@ pub struct UpdateProjectStatusRequest {
     pub status: String,
 }
 
-pub async fn update_project_status(path: web::Path<Uuid>, req: web::Json<UpdateProjectStatusRequest>, pool: web::Data<PgPool>, auth: AuthExtractor) -> impl Responder {
-    if auth.role != "platform_owner" {
-        return HttpResponse::Unauthorized().body("Only platform owners can update project status");
-    }
-
+pub async fn update_project_status(path: web::Path<Uuid>, req: web::Json<UpdateProjectStatusRequest>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
     let project_id = path.into_inner();
     let new_status = req.status.clone();
 
     if !["active", "paused"].contains(&new_status.as_str()) {
-        return HttpResponse::BadRequest().body("Invalid project status");
+        return Err(ApiError::Validation("Invalid project status".to_string()));
     }
 
-    match sqlx::query_as::<_, Project>("UPDATE projects SET status = $1 WHERE id = $2 RETURNING *")
+    let project = sqlx::query_as::<_, Project>("UPDATE projects SET status = $1 WHERE id = $2 RETURNING *")
         .bind(&new_status)
         .bind(project_id)
-        .fetch_one(pool.get_ref())
-        .await
-    {
-        Ok(project) => HttpResponse::Ok().json(project),
-        Err(_) => HttpResponse::NotFound().body("Project not found"),
-    }
+        .fetch_optional(pool.get_ref())
+        .await?
+        .ok_or(ApiError::ProjectNotFound)?;
+
+    Ok(HttpResponse::Ok().json(project))
 }
@@ -1,9 +1,17 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpResponse};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 
+use crate::error::ApiError;
+use crate::events::{EventBus, EventKind};
+use crate::lifecycle::{self, ProposalState};
 use crate::models::Proposal;
+use crate::pagination::{PageQuery, SortKey};
+use crate::permissions::{self, Permission};
+use crate::tally;
+use crate::webhooks::{self, WebhookEvent};
+use crate::AuthExtractor;
 
 // DTOs for request bodies
 #[derive(serde::Deserialize)]
@@ -12,9 +20,10 @@ pub struct CreateProposalRequest {
     pub choices_json: serde_json::Value,
     pub model_enum: String,
     pub quorum: f64,
+    pub quorum_basis: Option<String>, // "voters" (default) or "weight"
     pub start_ts: chrono::NaiveDateTime,
     pub end_ts: chrono::NaiveDateTime,
-    pub state: String,
+    pub state: Option<ProposalState>, // "draft" (default) or "scheduled"
 }
 
 // Handlers
@@ -22,23 +31,55 @@ pub async fn create_proposal(
     pool: web::Data<PgPool>,
     project_id: web::Path<Uuid>,
     req: web::Json<CreateProposalRequest>,
-) -> impl Responder {
+    auth: AuthExtractor,
+) -> Result<HttpResponse, ApiError> {
+    let project_id = project_id.into_inner();
+    let mut transaction = pool.begin().await?;
+    permissions::require_in_project(&mut transaction, &auth, Permission::ManageProposals, project_id).await?;
+
+    if tally::engine_for(&req.model_enum).is_none() {
+        return Err(ApiError::UnknownVotingModel(req.model_enum.clone()));
+    }
+    if tally::parse_choices(&req.choices_json).is_none() {
+        return Err(ApiError::Validation("choices_json must be a non-empty array of strings".to_string()));
+    }
+
+    if req.end_ts <= req.start_ts {
+        return Err(ApiError::Validation("end_ts must be after start_ts".to_string()));
+    }
+
+    let state = req.state.unwrap_or(ProposalState::Draft);
+    if state != ProposalState::Draft && state != ProposalState::Scheduled {
+        return Err(ApiError::Validation("Proposals are created as draft or scheduled".to_string()));
+    }
+
+    let quorum_basis = req.quorum_basis.clone().unwrap_or_else(|| "voters".to_string());
+    if !["voters", "weight"].contains(&quorum_basis.as_str()) {
+        return Err(ApiError::Validation("Invalid quorum basis".to_string()));
+    }
+
     let new_proposal = Proposal {
         id: Uuid::new_v4(),
-        project_id: project_id.into_inner(),
+        project_id,
         title: req.title.clone(),
         choices_json: req.choices_json.clone(),
         model_enum: req.model_enum.clone(),
         quorum: req.quorum,
+        quorum_basis,
         start_ts: req.start_ts,
         end_ts: req.end_ts,
-        state: req.state.clone(),
+        state,
         revoked: false,
         finalized: false,
+        eligibility_root: None,
+        eligible_voters: None,
+        eligible_weight: None,
+        quorum_json: None,
+        imported_at: None,
     };
 
-    match sqlx::query_as::<_, Proposal>(
-        "INSERT INTO proposals (id, project_id, title, choices_json, model_enum, quorum, start_ts, end_ts, state, revoked, finalized) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING *"
+    let proposal = sqlx::query_as::<_, Proposal>(
+        "INSERT INTO proposals (id, project_id, title, choices_json, model_enum, quorum, quorum_basis, start_ts, end_ts, state, revoked, finalized) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) RETURNING *"
     )
     .bind(new_proposal.id)
     .bind(new_proposal.project_id)
@@ -46,72 +87,188 @@ pub async fn create_proposal(
     .bind(new_proposal.choices_json)
     .bind(new_proposal.model_enum)
     .bind(new_proposal.quorum)
+    .bind(new_proposal.quorum_basis)
     .bind(new_proposal.start_ts)
     .bind(new_proposal.end_ts)
     .bind(new_proposal.state)
     .bind(new_proposal.revoked)
     .bind(new_proposal.finalized)
-    .fetch_one(pool.get_ref())
-    .await
-    {
-        Ok(proposal) => HttpResponse::Created().json(proposal),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    webhooks::enqueue(&mut transaction, project_id, WebhookEvent::Created, serde_json::json!({ "proposal": proposal })).await?;
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Created().json(proposal))
 }
 
-pub async fn get_proposal(pool: web::Data<PgPool>, proposal_id: web::Path<Uuid>) -> impl Responder {
-    match sqlx::query_as::<_, Proposal>("SELECT * FROM proposals WHERE id = $1")
+pub async fn get_proposal(pool: web::Data<PgPool>, proposal_id: web::Path<Uuid>) -> Result<HttpResponse, ApiError> {
+    let proposal = sqlx::query_as::<_, Proposal>("SELECT * FROM proposals WHERE id = $1")
         .bind(proposal_id.into_inner())
         .fetch_optional(pool.get_ref())
-        .await
-    {
-        Ok(Some(proposal)) => HttpResponse::Ok().json(proposal),
-        Ok(None) => HttpResponse::NotFound().body("Proposal not found"),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
+        .await?
+        .ok_or(ApiError::ProposalNotFound)?;
+
+    Ok(HttpResponse::Ok().json(proposal))
 }
 
-pub async fn revoke_proposal(pool: web::Data<PgPool>, proposal_id: web::Path<Uuid>, auth: AuthExtractor) -> impl Responder {
-    if auth.role != "platform_owner" {
-        return HttpResponse::Unauthorized().body("Only platform owners can revoke proposals");
-    }
+// PublicProposal: What voters see of a proposal: no per-choice counts before the tally, just turnout
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct PublicProposal {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub title: String,
+    pub choices_json: serde_json::Value,
+    pub model_enum: String,
+    pub quorum: f64,
+    pub quorum_basis: String,
+    pub start_ts: chrono::NaiveDateTime,
+    pub end_ts: chrono::NaiveDateTime,
+    pub state: ProposalState,
+    pub ballots_counted: i64,
+    pub weight_counted: i64,
+    #[sqlx(skip)]
+    pub seconds_remaining: Option<i64>, // Until end_ts, while active
+}
 
-    match sqlx::query_as::<_, Proposal>("UPDATE proposals SET revoked = TRUE WHERE id = $1 RETURNING *")
-        .bind(proposal_id.into_inner())
-        .fetch_one(pool.get_ref())
-        .await
-    {
-        Ok(proposal) => HttpResponse::Ok().json(proposal),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+// States voters can see: open for voting or already decided
+const PUBLIC_STATES: [ProposalState; 4] = [ProposalState::Active, ProposalState::Closed, ProposalState::Tallied, ProposalState::Finalized];
+
+/// Lists a project's active and past proposals for voters. Revoked proposals are left out, and a paused
+/// project's proposals aren't listed at all.
+pub async fn list_project_proposals(
+    pool: web::Data<PgPool>,
+    project_id: web::Path<Uuid>,
+    page: web::Query<PageQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let project_id = project_id.into_inner();
+    let pagination = page.parse(PROPOSAL_SORTS)?;
+
+    sqlx::query_scalar::<_, Uuid>("SELECT id FROM projects WHERE id = $1 AND status = 'active'")
+        .bind(project_id)
+        .fetch_optional(pool.get_ref())
+        .await?
+        .ok_or(ApiError::ProjectNotFound)?;
+
+    let mut proposals = pagination
+        .fetch::<PublicProposal>(pool.get_ref(), |query| {
+            query.push(
+                "SELECT p.id, p.project_id, p.title, p.choices_json, p.model_enum, p.quorum, p.quorum_basis, p.start_ts, p.end_ts, p.state, \
+                 COUNT(s.id) AS ballots_counted, COALESCE(SUM(s.weight), 0)::BIGINT AS weight_counted \
+                 FROM proposals p LEFT JOIN submissions s ON s.proposal_id = p.id AND s.verified_bool = TRUE \
+                 WHERE p.project_id = "
+            );
+            query.push_bind(project_id);
+            query.push(" AND p.revoked = FALSE AND p.state = ANY(").push_bind(PUBLIC_STATES.map(|s| s.as_str()).to_vec()).push(")");
+            query.push(" GROUP BY p.id");
+        })
+        .await?;
+
+    let now = chrono::Utc::now().naive_utc();
+    for proposal in &mut proposals.items {
+        if proposal.state == ProposalState::Active {
+            proposal.seconds_remaining = Some((proposal.end_ts - now).num_seconds().max(0));
+        }
     }
+
+    Ok(HttpResponse::Ok().json(proposals))
 }
 
-pub async fn finalize_tally(pool: web::Data<PgPool>, proposal_id: web::Path<Uuid>, auth: AuthExtractor) -> impl Responder {
-    if auth.role != "platform_owner" {
-        return HttpResponse::Unauthorized().body("Only platform owners can finalize tallies");
-    }
+pub async fn publish_proposal(
+    pool: web::Data<PgPool>,
+    proposal_id: web::Path<Uuid>,
+    auth: AuthExtractor,
+    events: web::Data<EventBus>,
+) -> Result<HttpResponse, ApiError> {
+    transition_proposal(&pool, &events, &auth, Permission::ManageProposals, proposal_id.into_inner(), ProposalState::Scheduled).await
+}
 
-    match sqlx::query_as::<_, Proposal>("UPDATE proposals SET finalized = TRUE WHERE id = $1 RETURNING *")
-        .bind(proposal_id.into_inner())
-        .fetch_one(pool.get_ref())
-        .await
-    {
-        Ok(proposal) => HttpResponse::Ok().json(proposal),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
+pub async fn revoke_proposal(
+    pool: web::Data<PgPool>,
+    proposal_id: web::Path<Uuid>,
+    auth: AuthExtractor,
+    events: web::Data<EventBus>,
+) -> Result<HttpResponse, ApiError> {
+    transition_proposal(&pool, &events, &auth, Permission::RevokeProposals, proposal_id.into_inner(), ProposalState::Revoked).await
 }
 
-pub async fn get_all_proposals(pool: web::Data<PgPool>, auth: AuthExtractor) -> impl Responder {
-    if auth.role != "platform_owner" && auth.role != "project_admin" {
-        return HttpResponse::Unauthorized().body("Only platform owners and project admins can view all proposals");
+pub async fn finalize_tally(
+    pool: web::Data<PgPool>,
+    proposal_id: web::Path<Uuid>,
+    auth: AuthExtractor,
+    events: web::Data<EventBus>,
+) -> Result<HttpResponse, ApiError> {
+    transition_proposal(&pool, &events, &auth, Permission::FinalizeTallies, proposal_id.into_inner(), ProposalState::Finalized).await
+}
+
+async fn transition_proposal(
+    pool: &PgPool,
+    events: &EventBus,
+    auth: &AuthExtractor,
+    permission: Permission,
+    proposal_id: Uuid,
+    to: ProposalState,
+) -> Result<HttpResponse, ApiError> {
+    let mut transaction = pool.begin().await?;
+    permissions::require_for_proposal(&mut transaction, auth, permission, proposal_id).await?;
+    let proposal = lifecycle::transition(&mut transaction, proposal_id, to).await?;
+    if let Some(event) = WebhookEvent::for_state(proposal.state) {
+        webhooks::enqueue(&mut transaction, proposal.project_id, event, serde_json::json!({ "proposal": proposal })).await?;
     }
+    transaction.commit().await?;
+
+    events.publish(proposal_id, EventKind::StateChanged { state: proposal.state });
+    Ok(HttpResponse::Ok().json(proposal))
+}
+
+const PROPOSAL_SORTS: &[SortKey] = &[
+    SortKey { name: "start_ts", column: "start_ts", sql_type: "timestamp" },
+    SortKey { name: "end_ts", column: "end_ts", sql_type: "timestamp" },
+    SortKey { name: "title", column: "title", sql_type: "text" },
+];
 
-    match sqlx::query_as::<_, Proposal>("SELECT * FROM proposals")
-        .fetch_all(pool.get_ref())
-        .await
-    {
-        Ok(proposals) => HttpResponse::Ok().json(proposals),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+#[derive(serde::Deserialize)]
+pub struct ProposalFilter {
+    pub project_id: Option<Uuid>,
+    pub state: Option<ProposalState>,
+    pub revoked: Option<bool>,
+    pub from: Option<chrono::NaiveDateTime>, // With `to`: proposals whose voting window overlaps [from, to]
+    pub to: Option<chrono::NaiveDateTime>,
+}
+
+pub async fn get_all_proposals(
+    page: web::Query<PageQuery>,
+    filter: web::Query<ProposalFilter>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ApiError> {
+    if let (Some(from), Some(to)) = (filter.from, filter.to) {
+        if from > to {
+            return Err(ApiError::Validation("from must not be after to".to_string()));
+        }
     }
+
+    let pagination = page.parse(PROPOSAL_SORTS)?;
+    let proposals = pagination
+        .fetch::<Proposal>(pool.get_ref(), |query| {
+            query.push("SELECT * FROM proposals WHERE TRUE");
+            if let Some(project_id) = filter.project_id {
+                query.push(" AND project_id = ").push_bind(project_id);
+            }
+            if let Some(state) = filter.state {
+                query.push(" AND state = ").push_bind(state);
+            }
+            if let Some(revoked) = filter.revoked {
+                query.push(" AND revoked = ").push_bind(revoked);
+            }
+            if let Some(from) = filter.from {
+                query.push(" AND end_ts >= ").push_bind(from);
+            }
+            if let Some(to) = filter.to {
+                query.push(" AND start_ts <= ").push_bind(to);
+            }
+        })
+        .await?;
+
+    Ok(HttpResponse::Ok().json(proposals))
 }
 
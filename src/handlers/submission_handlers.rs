@@ -1,16 +1,54 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpResponse};
 use sqlx::{PgPool};
 use uuid::Uuid;
+use chrono::Utc;
+use miden_crypto::hash::rpo::Rpo256;
 
 
-use crate::models::Submission;
+use crate::bulletin;
+use crate::election::{self, EncryptedBallot};
+use crate::error::ApiError;
+use crate::events::{EventBus, EventKind};
+use crate::keyring::ReceiptKeyring;
+use crate::models::{Election, Proposal, Submission};
+use crate::lifecycle::ProposalState;
+use crate::merkle;
+use crate::pagination::{PageQuery, SortKey};
+use crate::receipts::Receipt;
+use crate::tally::{self, Ballot};
+use crate::verifier::{self, ProgramHashes, ProgramProof, VoteOutputs, VotePublicInputs};
 
 // DTOs for request bodies
 #[derive(serde::Deserialize)]
 pub struct SubmitVoteRequest {
-    pub proof_hash: String,
+    pub proof: String, // Hex-encoded serialized ExecutionProof
+    pub public_inputs: Vec<u64>,
+    pub stack_outputs: Vec<u64>,
     pub note_commitment: String,
-    pub nullifier_hash: String,
+    pub encrypted_ballot: Option<EncryptedBallot>, // Required when the proposal has an election
+}
+
+// DTOs for responses
+#[derive(serde::Serialize)]
+pub struct SubmitVoteResponse {
+    #[serde(flatten)]
+    pub submission: Submission,
+    pub receipt: Receipt,
+}
+
+#[derive(serde::Serialize)]
+pub struct ReceiptResponse {
+    pub receipt: Receipt,
+    pub tallied_at: Option<chrono::NaiveDateTime>, // Set once the final tally, which counts every receipted ballot, exists
+}
+
+// Ballots are only accepted while the proposal is active and inside its voting window
+fn check_voting_open(proposal: &Proposal) -> Result<(), ApiError> {
+    let now = Utc::now().naive_utc();
+    if proposal.state != ProposalState::Active || now < proposal.start_ts || now >= proposal.end_ts {
+        return Err(ApiError::VotingClosed(proposal.state));
+    }
+    Ok(())
 }
 
 // Handlers
@@ -18,70 +56,282 @@ pub async fn submit_vote(
     pool: web::Data<PgPool>,
     proposal_id: web::Path<Uuid>,
     req: web::Json<SubmitVoteRequest>,
-) -> impl Responder {
-    let mut transaction = match pool.begin().await {
-        Ok(tx) => tx,
-        Err(e) => return HttpResponse::InternalServerError().body(e.to_string()),
+    receipt_keys: web::Data<ReceiptKeyring>,
+    events: web::Data<EventBus>,
+    programs: web::Data<ProgramHashes>,
+) -> Result<HttpResponse, ApiError> {
+    let proof_hex = req.proof.trim_start_matches("0x");
+    if proof_hex.len() > 2 * verifier::MAX_PROOF_BYTES {
+        return Err(ApiError::MalformedProof(format!("Proof exceeds {} bytes", verifier::MAX_PROOF_BYTES)));
+    }
+    let proof_bytes = hex::decode(proof_hex).map_err(|_| ApiError::MalformedProof("Proof must be hex-encoded".to_string()))?;
+
+    let proposal_id = proposal_id.into_inner();
+
+    let proposal = sqlx::query_as::<_, Proposal>("SELECT * FROM proposals WHERE id = $1")
+        .bind(proposal_id)
+        .fetch_optional(pool.get_ref())
+        .await?
+        .ok_or(ApiError::ProposalNotFound)?;
+
+    // Fails fast before the proof is verified; checked again under the row lock below
+    check_voting_open(&proposal)?;
+
+    let public_inputs = VotePublicInputs::parse(&req.public_inputs)
+        .ok_or_else(|| ApiError::MalformedProof("Public inputs do not match the vote program layout".to_string()))?;
+
+    // The proof must show membership in the snapshot the proposal was opened against
+    match proposal.eligibility_root.as_deref().and_then(merkle::digest_from_hex) {
+        Some(root) if merkle::digest_to_ints(&root) == public_inputs.merkle_root => {}
+        _ => return Err(ApiError::EligibilityRootMismatch),
+    }
+
+    // The nullifier is derived in the proof from the voter's secret and this proposal, so it can't be
+    // made up, and a voter's nullifiers on different proposals are unlinkable
+    if public_inputs.proposal_id != verifier::proposal_id_word(&proposal_id) {
+        return Err(ApiError::MalformedProof("Proof is not bound to this proposal".to_string()));
+    }
+    let nullifier_hash = merkle::digest_to_hex(&merkle::digest_from_ints(public_inputs.nullifier));
+
+    let outputs = VoteOutputs::parse(&req.stack_outputs)
+        .ok_or_else(|| ApiError::MalformedProof("Stack outputs do not match the vote program layout".to_string()))?;
+
+    let ballot = Ballot {
+        weight: outputs.weight,
+        ranking: outputs.choices.iter().map(|&c| c as usize).collect(),
     };
 
-    // Check for unique nullifier_hash (prevent double voting)
-    match sqlx::query_as::<_, Submission>("SELECT * FROM submissions WHERE nullifier_hash = $1")
-        .bind(&req.nullifier_hash)
-        .fetch_optional(&mut *transaction)
-        .await
-    {
-        Ok(Some(_)) => {
-            let _ = transaction.rollback().await;
-            return HttpResponse::BadRequest().body("Nullifier hash already used (double voting detected)");
+    let choice_count = tally::parse_choices(&proposal.choices_json).map_or(0, |c| c.len());
+
+    let election = sqlx::query_as::<_, Election>("SELECT * FROM elections WHERE proposal_id = $1")
+        .bind(proposal_id)
+        .fetch_optional(pool.get_ref())
+        .await?;
+
+    let validation = match (&election, &req.encrypted_ballot) {
+        // Secret ballot: the choice stays encrypted and the proof commits to the ciphertexts
+        (Some(election), Some(encrypted)) => {
+            let public_key = election.public_key.as_deref().and_then(election::point_from_hex).ok_or("Election key is invalid".to_string());
+            public_key
+                .and_then(|pk| {
+                    let context = election::ballot_context(&proposal_id, &public_inputs.nullifier);
+                    election::verify_ballot(&pk, encrypted, choice_count, &context)
+                })
+                .and_then(|ciphertexts| {
+                    let commitment = Rpo256::hash(&election::ballot_bytes(&ciphertexts));
+                    if !ballot.ranking.is_empty() {
+                        Err("Encrypted ballots must not reveal a choice in the proof outputs".to_string())
+                    } else if merkle::digest_to_ints(&commitment) != public_inputs.ballot_commitment {
+                        Err("Proof does not commit to the encrypted ballot".to_string())
+                    } else {
+                        Ok(())
+                    }
+                })
         }
-        Err(e) => {
-            let _ = transaction.rollback().await;
-            return HttpResponse::InternalServerError().body(e.to_string());
+        (Some(_), None) => Err("This proposal only accepts encrypted ballots".to_string()),
+        (None, Some(_)) => Err("This proposal has no election key to encrypt to".to_string()),
+        (None, None) => match tally::engine_for(&proposal.model_enum) {
+            Some(engine) => engine.validate(&ballot, choice_count),
+            None => return Err(ApiError::UnknownVotingModel(proposal.model_enum)),
+        },
+    };
+    validation.map_err(ApiError::InvalidBallot)?;
+    if ballot.weight > i64::MAX as u64 {
+        return Err(ApiError::InvalidBallot("Voting weight out of range".to_string()));
+    }
+
+    // Verifying a STARK proof is CPU-bound, so keep it off the async workers
+    let program_hash = programs.vote.clone();
+    let (proof_bytes, verification) = {
+        let program_hash = program_hash.clone();
+        let public_inputs = req.public_inputs.clone();
+        let stack_outputs = req.stack_outputs.clone();
+        web::block(move || {
+            let verification = verifier::verify_proof(&ProgramProof {
+                proof_bytes: &proof_bytes,
+                program_hash: &program_hash,
+                public_inputs: &public_inputs,
+                stack_outputs: &stack_outputs,
+            });
+            (proof_bytes, verification)
+        })
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+    };
+
+    let mut transaction = pool.begin().await?;
+
+    // The row lock holds off transitions and serializes ballots, so the proposal can't close (or be
+    // revoked) between this check and the commit
+    let proposal = sqlx::query_as::<_, Proposal>("SELECT * FROM proposals WHERE id = $1 FOR UPDATE")
+        .bind(proposal_id)
+        .fetch_one(&mut *transaction)
+        .await?;
+    check_voting_open(&proposal)?;
+
+    // Encrypted totals are only decryptable up to a bound, so an election stops counting ballots there
+    if election.is_some() && verification.verified {
+        let counted_weight = sqlx::query_scalar::<_, i64>(
+            "SELECT COALESCE(SUM(GREATEST(weight, 1)), 0)::BIGINT FROM submissions WHERE proposal_id = $1 AND verified_bool = TRUE"
+        )
+        .bind(proposal_id)
+        .fetch_one(&mut *transaction)
+        .await?;
+        if counted_weight as u64 + ballot.weight.max(1) > election::MAX_WEIGHT_TOTAL {
+            return Err(ApiError::InvalidBallot("Total voting weight would exceed what the election can decrypt".to_string()));
         }
-        _ => {}
     }
 
-    let new_submission = Submission {
+    // Counted ballots are numbered in the order they are accepted
+    let inclusion_index = if verification.verified {
+        let counted = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM submissions WHERE proposal_id = $1 AND verified_bool = TRUE")
+            .bind(proposal_id)
+            .fetch_one(&mut *transaction)
+            .await?;
+        Some(counted)
+    } else {
+        None
+    };
+
+    let mut new_submission = Submission {
         id: Uuid::new_v4(),
-        proposal_id: proposal_id.into_inner(),
-        proof_hash: req.proof_hash.clone(),
+        proposal_id,
+        proof_hash: verifier::proof_hash(&proof_bytes),
+        program_hash,
+        public_inputs: serde_json::json!(req.public_inputs),
+        stack_outputs: serde_json::json!(req.stack_outputs),
         note_commitment: req.note_commitment.clone(),
-        nullifier_hash: req.nullifier_hash.clone(),
-        verified_bool: crate::verifier::verify_proof(&req.proof_hash), // Call verifier stub
-        verified_at: None,
+        nullifier_hash,
+        weight: ballot.weight as i64,
+        ballot: serde_json::json!(ballot.ranking),
+        encrypted_ballot: req.encrypted_ballot.as_ref().map(|b| serde_json::json!(b)),
+        verified_bool: verification.verified,
+        failure_reason: verification.failure_reason,
+        verified_at: verification.verified.then(|| Utc::now().naive_utc()),
+        inclusion_index,
+        receipt_kid: None,
+        receipt_signature: None,
     };
+    // The receipt is signed once, here, and stored with the ballot it vouches for
+    (new_submission.receipt_kid, new_submission.receipt_signature) = Receipt::sign(&receipt_keys, &new_submission).unzip();
+
+    // Keep the raw proof of a counted ballot so it can be re-verified independently later
+    if new_submission.verified_bool {
+        sqlx::query("INSERT INTO proofs (proof_hash, proof_bytes) VALUES ($1, $2) ON CONFLICT (proof_hash) DO NOTHING")
+            .bind(&new_submission.proof_hash)
+            .bind(&proof_bytes)
+            .execute(&mut *transaction)
+            .await?;
+    }
 
-    match sqlx::query_as::<_, Submission>(
-        "INSERT INTO submissions (id, proposal_id, proof_hash, note_commitment, nullifier_hash, verified_bool, verified_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *"
+    let submission = match sqlx::query_as::<_, Submission>(
+        "INSERT INTO submissions (id, proposal_id, proof_hash, program_hash, public_inputs, stack_outputs, note_commitment, nullifier_hash, weight, ballot, encrypted_ballot, verified_bool, failure_reason, verified_at, inclusion_index, receipt_kid, receipt_signature) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17) RETURNING *"
     )
     .bind(new_submission.id)
     .bind(new_submission.proposal_id)
     .bind(new_submission.proof_hash)
+    .bind(new_submission.program_hash)
+    .bind(new_submission.public_inputs)
+    .bind(new_submission.stack_outputs)
     .bind(new_submission.note_commitment)
     .bind(new_submission.nullifier_hash)
+    .bind(new_submission.weight)
+    .bind(new_submission.ballot)
+    .bind(new_submission.encrypted_ballot)
     .bind(new_submission.verified_bool)
+    .bind(new_submission.failure_reason)
     .bind(new_submission.verified_at)
+    .bind(new_submission.inclusion_index)
+    .bind(new_submission.receipt_kid)
+    .bind(new_submission.receipt_signature)
     .fetch_one(&mut *transaction)
     .await
     {
-        Ok(submission) => {
-            let _ = transaction.commit().await;
-            HttpResponse::Created().json(submission)
-        }
-        Err(e) => {
-            let _ = transaction.rollback().await;
-            HttpResponse::InternalServerError().body(e.to_string())
-        }
+        Ok(s) => s,
+        // One counted ballot per (proposal, nullifier), enforced by a partial unique index. Rejected
+        // ballots don't burn the nullifier.
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => return Err(ApiError::NullifierReused),
+        Err(e) => return Err(e.into()),
+    };
+
+    // Counted ballots go on the bulletin board in the same transaction, so none is counted unlogged
+    bulletin::append(&mut transaction, &submission).await?;
+
+    // Rejected ballots are still recorded, so commit before reporting the failure
+    transaction.commit().await?;
+
+    if let Some(inclusion_index) = submission.inclusion_index {
+        events.publish(proposal_id, EventKind::SubmissionAccepted {
+            submission_id: submission.id,
+            inclusion_index,
+            ballots_counted: inclusion_index + 1, // Indexes run gap-free from 0 in counting order
+        });
     }
-}
 
-pub async fn list_submissions(pool: web::Data<PgPool>, proposal_id: web::Path<Uuid>) -> impl Responder {
-    match sqlx::query_as::<_, Submission>("SELECT * FROM submissions WHERE proposal_id = $1")
-        .bind(proposal_id.into_inner())
-        .fetch_all(pool.get_ref())
-        .await
-    {
-        Ok(submissions) => HttpResponse::Ok().json(submissions),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    match Receipt::stored(&submission) {
+        Some(receipt) => Ok(HttpResponse::Created().json(SubmitVoteResponse { submission, receipt })),
+        None => Err(ApiError::ProofRejected(Box::new(submission))),
     }
-}
\ No newline at end of file
+}
+
+const SUBMISSION_SORTS: &[SortKey] = &[
+    // Rejected ballots have no inclusion index and sort after every counted one
+    SortKey { name: "inclusion_index", column: "COALESCE(inclusion_index, 9223372036854775807)", sql_type: "bigint" },
+    SortKey { name: "weight", column: "weight", sql_type: "bigint" },
+];
+
+#[derive(serde::Deserialize)]
+pub struct SubmissionFilter {
+    pub verified: Option<bool>,
+}
+
+pub async fn list_submissions(
+    pool: web::Data<PgPool>,
+    proposal_id: web::Path<Uuid>,
+    page: web::Query<PageQuery>,
+    filter: web::Query<SubmissionFilter>,
+) -> Result<HttpResponse, ApiError> {
+    let proposal_id = proposal_id.into_inner();
+    let pagination = page.parse(SUBMISSION_SORTS)?;
+    let submissions = pagination
+        .fetch::<Submission>(pool.get_ref(), |query| {
+            query.push("SELECT * FROM submissions WHERE proposal_id = ").push_bind(proposal_id);
+            if let Some(verified) = filter.verified {
+                query.push(" AND verified_bool = ").push_bind(verified);
+            }
+        })
+        .await?;
+
+    Ok(HttpResponse::Ok().json(submissions))
+}
+
+/// Looks up the receipt for a counted ballot, so a voter can check it was included without revealing
+/// their choice.
+pub async fn get_receipt(
+    pool: web::Data<PgPool>,
+    path: web::Path<(Uuid, String)>,
+) -> Result<HttpResponse, ApiError> {
+    let (proposal_id, nullifier_hash) = path.into_inner();
+
+    let submission = sqlx::query_as::<_, Submission>(
+        "SELECT * FROM submissions WHERE proposal_id = $1 AND nullifier_hash = $2 AND verified_bool = TRUE"
+    )
+    .bind(proposal_id)
+    .bind(nullifier_hash.to_lowercase())
+    .fetch_optional(pool.get_ref())
+    .await?
+    .ok_or(ApiError::ReceiptNotFound)?;
+
+    let tallied_at = sqlx::query_scalar::<_, chrono::NaiveDateTime>("SELECT verified_at FROM tallies WHERE proposal_id = $1")
+        .bind(proposal_id)
+        .fetch_optional(pool.get_ref())
+        .await?;
+
+    let receipt = Receipt::stored(&submission).ok_or(ApiError::ReceiptNotFound)?;
+    Ok(HttpResponse::Ok().json(ReceiptResponse { receipt, tallied_at }))
+}
+
+/// Public halves of the receipt keys, to check receipt signatures against.
+pub async fn receipt_keys(receipt_keys: web::Data<ReceiptKeyring>) -> HttpResponse {
+    HttpResponse::Ok().json(receipt_keys.jwks())
+}
@@ -1,111 +1,307 @@
-use actix_web::{web, HttpResponse, Responder};
-use sqlx::PgPool;
+use actix_web::{web, HttpResponse};
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 use chrono::Utc;
-use std::collections::HashMap;
 
-use crate::models::{Proposal, Submission, Tally};
+use crate::ballot_log;
+use crate::bulletin;
+use crate::election::{self, DecryptionShare};
+use crate::error::ApiError;
+use crate::events::{EventBus, EventKind};
+use crate::handlers::election_handlers;
+use crate::lifecycle::{self, ProposalState};
+use crate::models::{BulletinRoot, Election, ElectionTrustee, Proposal, Submission, Tally};
+use crate::permissions::{self, Permission};
+use crate::tally::{self, Ballot, ChoiceTotal, QuorumReport, TallyResult};
+use crate::verifier::{self, ProgramHashes, ProgramProof, TallyPublicInputs};
+use crate::webhooks::{self, WebhookEvent};
+use crate::AuthExtractor;
+
+// DTOs for request bodies
+#[derive(serde::Deserialize)]
+pub struct TallyRequest {
+    pub aggregate_proof: String, // Hex-encoded ExecutionProof of the tally program, from an external prover
+}
+
+// DTOs for responses
+#[derive(serde::Serialize)]
+pub struct TallyProofResponse {
+    pub tally_id: Uuid,
+    pub proof_hash: String,
+    pub program_hash: String,
+    pub public_inputs: serde_json::Value,
+    pub stack_outputs: serde_json::Value,
+    pub proof: String, // Hex-encoded ExecutionProof
+}
 
 // Handlers
-pub async fn tally_proposal(pool: web::Data<PgPool>, proposal_id: web::Path<Uuid>) -> impl Responder {
-    let mut transaction = match pool.begin().await {
-        Ok(tx) => tx,
-        Err(e) => return HttpResponse::InternalServerError().body(e.to_string()),
-    };
+/// Tallies a closed proposal. The caller supplies a proof that the tally program, run over the ballots
+/// under the current bulletin board root, outputs a commitment to exactly these results; nothing is
+/// written unless it verifies.
+pub async fn tally_proposal(
+    pool: web::Data<PgPool>,
+    proposal_id: web::Path<Uuid>,
+    req: web::Json<TallyRequest>,
+    auth: AuthExtractor,
+    events: web::Data<EventBus>,
+    programs: web::Data<ProgramHashes>,
+) -> Result<HttpResponse, ApiError> {
+    let proof_bytes = hex::decode(req.aggregate_proof.trim_start_matches("0x"))
+        .map_err(|_| ApiError::MalformedProof("Aggregate proof must be hex-encoded".to_string()))?;
+
+    let mut transaction = pool.begin().await?;
 
     let prop_id = proposal_id.into_inner(); // Call into_inner() once
 
-    // Fetch the proposal to get quorum and state
-    let proposal = match sqlx::query_as::<_, Proposal>("SELECT * FROM proposals WHERE id = $1")
+    // Fetch the proposal to get quorum and state, locked so no ballot or transition lands mid-tally
+    let proposal = sqlx::query_as::<_, Proposal>("SELECT * FROM proposals WHERE id = $1 FOR UPDATE")
         .bind(prop_id)
         .fetch_optional(&mut *transaction)
-        .await
-    {
-        Ok(Some(p)) => p,
-        Ok(None) => {
-            let _ = transaction.rollback().await;
-            return HttpResponse::NotFound().body("Proposal not found");
-        }
-        Err(e) => {
-            let _ = transaction.rollback().await;
-            return HttpResponse::InternalServerError().body(e.to_string());
-        }
-    };
+        .await?
+        .ok_or(ApiError::ProposalNotFound)?;
+
+    permissions::require_in_project(&mut transaction, &auth, Permission::TallyProposals, proposal.project_id).await?;
 
-    // Check if proposal is already tallied or not in a state to be tallied
-    if proposal.state == "closed" || proposal.state == "tallied" {
-        let _ = transaction.rollback().await;
-        return HttpResponse::BadRequest().body("Proposal is already closed or tallied.");
+    // Only closed proposals can be tallied
+    if proposal.state != ProposalState::Closed {
+        return Err(ApiError::InvalidState { action: "tallied", state: proposal.state });
     }
 
     // Fetch all verified submissions for the proposal
-    let submissions = match sqlx::query_as::<_, Submission>(
-        "SELECT * FROM submissions WHERE proposal_id = $1 AND verified_bool = TRUE"
+    let submissions = sqlx::query_as::<_, Submission>(
+        "SELECT * FROM submissions WHERE proposal_id = $1 AND verified_bool = TRUE ORDER BY inclusion_index"
     )
     .bind(prop_id)
     .fetch_all(&mut *transaction)
-    .await
-    {
-        Ok(s) => s,
-        Err(e) => {
-            let _ = transaction.rollback().await;
-            return HttpResponse::InternalServerError().body(e.to_string());
-        }
-    };
+    .await?;
 
-    // Enforce quorum
-    // For simplicity, let's assume total eligible votes is a fixed number or fetched from somewhere
-    // Here, we'll just use the number of verified submissions as the total votes for quorum calculation
-    let total_eligible_votes = 100.0; // Placeholder: Replace with actual total eligible votes
-    let actual_votes = submissions.len() as f64;
-    let quorum_reached = (actual_votes / total_eligible_votes) * 100.0 >= proposal.quorum;
+    // Count exactly what the bulletin board logged, and commit to that root
+    let published = bulletin::publish(&mut transaction, prop_id).await?;
+    if !bulletin::matches(&submissions, &published) {
+        return Err(ApiError::BulletinMismatch);
+    }
+
+    let (ballots, quorum) = count_quorum(&proposal, &submissions)?;
+
+    // A missed quorum is final: fail the proposal with the report it was judged by, then report it
+    if !quorum.reached {
+        lifecycle::transition(&mut transaction, prop_id, ProposalState::Failed).await?;
+        let proposal = sqlx::query_as::<_, Proposal>("UPDATE proposals SET quorum_json = $1 WHERE id = $2 RETURNING *")
+            .bind(serde_json::to_value(&quorum).unwrap_or_default())
+            .bind(prop_id)
+            .fetch_one(&mut *transaction)
+            .await?;
+        webhooks::enqueue(
+            &mut transaction,
+            proposal.project_id,
+            WebhookEvent::Failed,
+            serde_json::json!({ "proposal": proposal, "quorum": quorum }),
+        )
+        .await?;
 
-    if !quorum_reached {
-        let _ = transaction.rollback().await;
-        return HttpResponse::BadRequest().body("Quorum not reached.");
+        transaction.commit().await?;
+        events.publish(prop_id, EventKind::StateChanged { state: ProposalState::Failed });
+        return Err(ApiError::QuorumNotReached(quorum));
     }
 
-    // Simple tallying logic (e.g., count occurrences of choices from note_commitment or proof_hash)
-    let mut results: HashMap<String, u32> = HashMap::new();
-    for submission in &submissions {
-        // This is a placeholder; actual tallying would depend on the voting model (single-choice, ranked-choice)
-        // For now, let's assume note_commitment can be mapped to a choice or just count them.
-        *results.entry(submission.note_commitment.clone()).or_insert(0) += 1;
+    let results = count_results(&mut transaction, &proposal, &ballots).await?;
+    let (public_inputs, stack_outputs) = aggregate_io(&prop_id, &published, &results)?;
+    let program_hash = programs.tally.clone();
+
+    let verification = verifier::verify_proof(&ProgramProof {
+        proof_bytes: &proof_bytes,
+        program_hash: &program_hash,
+        public_inputs: &public_inputs,
+        stack_outputs: &stack_outputs,
+    });
+    if !verification.verified {
+        let reason = verification.failure_reason.unwrap_or_else(|| "Aggregate proof verification failed".to_string());
+        return Err(ApiError::TallyProofRejected(reason));
     }
 
+    let proof_hash = verifier::proof_hash(&proof_bytes);
+    sqlx::query("INSERT INTO proofs (proof_hash, proof_bytes) VALUES ($1, $2) ON CONFLICT (proof_hash) DO NOTHING")
+        .bind(&proof_hash)
+        .bind(&proof_bytes)
+        .execute(&mut *transaction)
+        .await?;
+
     let new_tally = Tally {
         id: Uuid::new_v4(),
         proposal_id: prop_id,
-        aggregate_proof_hash: "dummy_aggregate_proof_hash".to_string(), // Placeholder
+        aggregate_proof_hash: Some(proof_hash),
         results_json: serde_json::to_value(results).unwrap_or_default(),
+        quorum_json: serde_json::to_value(quorum).unwrap_or_default(),
         verified_at: Utc::now().naive_utc(),
+        bulletin_size: Some(published.tree_size),
+        bulletin_root: Some(published.root),
+        aggregate_program_hash: Some(program_hash),
+        aggregate_public_inputs: Some(serde_json::json!(public_inputs)),
+        aggregate_stack_outputs: Some(serde_json::json!(stack_outputs)),
     };
 
-    match sqlx::query_as::<_, Tally>(
-        "INSERT INTO tallies (id, proposal_id, aggregate_proof_hash, results_json, verified_at) VALUES ($1, $2, $3, $4, $5) RETURNING *"
+    let tally = sqlx::query_as::<_, Tally>(
+        "INSERT INTO tallies (id, proposal_id, aggregate_proof_hash, results_json, quorum_json, verified_at, bulletin_size, bulletin_root, aggregate_program_hash, aggregate_public_inputs, aggregate_stack_outputs) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING *"
     )
     .bind(new_tally.id)
     .bind(new_tally.proposal_id)
     .bind(new_tally.aggregate_proof_hash)
     .bind(new_tally.results_json)
+    .bind(new_tally.quorum_json)
     .bind(new_tally.verified_at)
+    .bind(new_tally.bulletin_size)
+    .bind(new_tally.bulletin_root)
+    .bind(new_tally.aggregate_program_hash)
+    .bind(new_tally.aggregate_public_inputs)
+    .bind(new_tally.aggregate_stack_outputs)
     .fetch_one(&mut *transaction)
-    .await
-    {
-        Ok(tally) => {
-            // Update proposal state to 'tallied'
-            let _ = sqlx::query("UPDATE proposals SET state = $1 WHERE id = $2")
-                .bind("tallied")
-                .bind(prop_id)
-                .execute(&mut *transaction)
-                .await;
-
-            let _ = transaction.commit().await;
-            HttpResponse::Created().json(tally)
-        }
-        Err(e) => {
-            let _ = transaction.rollback().await;
-            HttpResponse::InternalServerError().body(e.to_string())
+    .await?;
+
+    let proposal = lifecycle::transition(&mut transaction, prop_id, ProposalState::Tallied).await?;
+    webhooks::enqueue(
+        &mut transaction,
+        proposal.project_id,
+        WebhookEvent::Tallied,
+        serde_json::json!({ "proposal": proposal, "tally": tally }),
+    )
+    .await?;
+
+    transaction.commit().await?;
+    events.publish(prop_id, EventKind::StateChanged { state: ProposalState::Tallied });
+    Ok(HttpResponse::Created().json(tally))
+}
+
+/// Serves a tally's aggregate proof with the exact inputs and outputs it was verified against.
+pub async fn get_tally_proof(pool: web::Data<PgPool>, proposal_id: web::Path<Uuid>) -> Result<HttpResponse, ApiError> {
+    let tally = sqlx::query_as::<_, Tally>("SELECT * FROM tallies WHERE proposal_id = $1")
+        .bind(proposal_id.into_inner())
+        .fetch_optional(pool.get_ref())
+        .await?
+        .ok_or(ApiError::TallyProofNotFound)?;
+
+    let (Some(proof_hash), Some(program_hash), Some(public_inputs), Some(stack_outputs)) = (
+        tally.aggregate_proof_hash,
+        tally.aggregate_program_hash,
+        tally.aggregate_public_inputs,
+        tally.aggregate_stack_outputs,
+    ) else {
+        return Err(ApiError::TallyProofNotFound);
+    };
+
+    let proof_bytes = sqlx::query_scalar::<_, Vec<u8>>("SELECT proof_bytes FROM proofs WHERE proof_hash = $1")
+        .bind(&proof_hash)
+        .fetch_one(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(TallyProofResponse {
+        tally_id: tally.id,
+        proof_hash,
+        program_hash,
+        public_inputs,
+        stack_outputs,
+        proof: hex::encode(proof_bytes),
+    }))
+}
+
+/// The ballots a tally counts and the quorum they reach, measured against the eligibility snapshot the
+/// proposal was opened with.
+pub(crate) fn count_quorum(proposal: &Proposal, submissions: &[Submission]) -> Result<(Vec<Ballot>, QuorumReport), ApiError> {
+    let ballots: Vec<Ballot> = submissions
+        .iter()
+        .map(|s| Ballot {
+            weight: s.weight as u64,
+            ranking: serde_json::from_value(s.ballot.clone()).unwrap_or_default(),
+        })
+        .collect();
+
+    let (Some(eligible_voters), Some(eligible_weight)) = (proposal.eligible_voters, proposal.eligible_weight) else {
+        return Err(ApiError::Internal(format!("Proposal {} was closed without an eligibility snapshot", proposal.id)));
+    };
+
+    let quorum = tally::compute_quorum(
+        &proposal.quorum_basis,
+        proposal.quorum,
+        eligible_voters as u64,
+        eligible_weight as u64,
+        &ballots,
+    )
+    .ok_or_else(|| ApiError::Validation(format!("Unknown quorum basis: {}", proposal.quorum_basis)))?;
+
+    Ok((ballots, quorum))
+}
+
+/// Results of the counted ballots. Secret ballots are only ever decrypted in aggregate, once enough
+/// trustees have posted shares; plain ones are counted by the proposal's engine.
+pub(crate) async fn count_results(conn: &mut PgConnection, proposal: &Proposal, ballots: &[Ballot]) -> Result<TallyResult, ApiError> {
+    let engine = tally::engine_for(&proposal.model_enum)
+        .ok_or_else(|| ApiError::UnknownVotingModel(proposal.model_enum.clone()))?;
+
+    let choices = tally::parse_choices(&proposal.choices_json)
+        .ok_or_else(|| ApiError::Validation("Proposal choices must be a non-empty array of strings".to_string()))?;
+
+    match election_handlers::load_election(&mut *conn, proposal.id).await? {
+        Some((_, election, trustees)) => decrypt_results(conn, proposal, &election, &trustees, &choices).await,
+        None => Ok(engine.tally(&choices, ballots)),
+    }
+}
+
+/// Public inputs and stack outputs the tally program's proof must have for these results under the
+/// published bulletin board root.
+pub(crate) fn aggregate_io(proposal_id: &Uuid, published: &BulletinRoot, results: &TallyResult) -> Result<(Vec<u64>, Vec<u64>), ApiError> {
+    let bulletin_root = ballot_log::from_hex(&published.root).ok_or(ApiError::BulletinMismatch)?;
+    let public_inputs = TallyPublicInputs::new(proposal_id, &bulletin_root, published.tree_size as u64).to_ints();
+    Ok((public_inputs, verifier::results_commitment(results).to_vec()))
+}
+
+async fn decrypt_results(
+    conn: &mut PgConnection,
+    proposal: &Proposal,
+    election: &Election,
+    trustees: &[ElectionTrustee],
+    choices: &[String],
+) -> Result<TallyResult, ApiError> {
+    let aggregate = election_handlers::encrypted_aggregate(conn, proposal).await?;
+
+    let mut shares = Vec::new();
+    for trustee in trustees {
+        let posted: Option<Vec<DecryptionShare>> = trustee
+            .decryption_shares
+            .clone()
+            .and_then(|s| serde_json::from_value(s).ok());
+        let public_share = trustee.public_share.as_deref().and_then(election::point_from_hex);
+        if let (Some(posted), Some(public_share)) = (posted, public_share) {
+            let verified: Option<Vec<_>> = aggregate
+                .iter()
+                .zip(&posted)
+                .map(|(ct, share)| election::verify_share(&public_share, ct, share))
+                .collect();
+            if let Some(points) = verified.filter(|p| p.len() == aggregate.len()) {
+                shares.push((trustee.trustee_index as u64, points));
+            }
         }
     }
-}
\ No newline at end of file
+
+    let threshold = election.threshold as usize;
+    if shares.len() < threshold {
+        return Err(ApiError::DecryptionSharesPending { posted: shares.len(), required: threshold });
+    }
+    shares.truncate(threshold);
+
+    let totals = web::block(move || election::decrypt_aggregates(&aggregate, &shares))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map_err(ApiError::Internal)?;
+
+    let totals = choices
+        .iter()
+        .zip(totals.chunks(election::AGGREGATES_PER_CHOICE))
+        .map(|(choice, t)| ChoiceTotal {
+            choice: choice.clone(),
+            votes: t[0],
+            raw_weight: t[1],
+            weighted: t[2] as f64 / election::POWER_SCALE,
+        })
+        .collect();
+
+    Ok(TallyResult::from_totals(&proposal.model_enum, totals))
+}
@@ -1,85 +1,128 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpResponse};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::accounts::AccountKeys;
+use crate::error::ApiError;
 use crate::models::User;
-use crate::main::AuthExtractor;
+use crate::pagination::{PageQuery, SortKey};
+use crate::permissions::Role;
+use crate::tokens;
 
 #[derive(serde::Deserialize)]
 pub struct UpdateUserRoleRequest {
-    pub role: String,
+    pub role: Role,
+}
+
+// A user together with the addresses of their linked wallets
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct UserResponse {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub user: User,
+    pub wallets: Vec<String>,
 }
 
 #[derive(serde::Deserialize)]
 pub struct RegisterUserRequest {
     pub wallet_address: String,
-    pub role: Option<String>,
+    pub public_key: String, // Auth key commitment of the wallet's account
+    pub role: Option<Role>,
 }
 
-pub async fn get_all_users(pool: web::Data<PgPool>, auth: AuthExtractor) -> impl Responder {
-    if auth.role != "platform_owner" {
-        return HttpResponse::Unauthorized().body("Only platform owners can view all users");
-    }
+const USER_SORTS: &[SortKey] = &[
+    SortKey { name: "created_at", column: "created_at", sql_type: "timestamp" },
+    SortKey { name: "role", column: "role", sql_type: "text" },
+];
 
-    match sqlx::query_as::<_, User>("SELECT * FROM users")
-        .fetch_all(pool.get_ref())
-        .await
-    {
-        Ok(users) => HttpResponse::Ok().json(users),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
+#[derive(serde::Deserialize)]
+pub struct UserFilter {
+    pub role: Option<Role>,
 }
 
-pub async fn update_user_role(path: web::Path<String>, req: web::Json<UpdateUserRoleRequest>, pool: web::Data<PgPool>, auth: AuthExtractor) -> impl Responder {
-    if auth.role != "platform_owner" {
-        return HttpResponse::Unauthorized().body("Only platform owners can update user roles");
-    }
+pub async fn get_all_users(
+    page: web::Query<PageQuery>,
+    filter: web::Query<UserFilter>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ApiError> {
+    let pagination = page.parse(USER_SORTS)?;
+    let users = pagination
+        .fetch::<UserResponse>(pool.get_ref(), |query| {
+            query.push(
+                "SELECT u.*, COALESCE(array_agg(w.address ORDER BY w.created_at) FILTER (WHERE w.address IS NOT NULL), '{}') AS wallets FROM users u LEFT JOIN wallets w ON w.user_id = u.id WHERE TRUE"
+            );
+            if let Some(role) = filter.role {
+                query.push(" AND u.role = ").push_bind(role);
+            }
+            query.push(" GROUP BY u.id");
+        })
+        .await?;
 
-    let wallet_address = path.into_inner();
-    let new_role = req.role.clone();
+    Ok(HttpResponse::Ok().json(users))
+}
 
-    // Basic role validation
-    if !["user", "project_admin", "platform_owner"].contains(&new_role.as_str()) {
-        return HttpResponse::BadRequest().body("Invalid role specified");
-    }
+pub async fn update_user_role(path: web::Path<Uuid>, req: web::Json<UpdateUserRoleRequest>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let user_id = path.into_inner();
+    let new_role = req.role;
 
-    match sqlx::query_as::<_, User>("UPDATE users SET role = $1 WHERE wallet_address = $2 RETURNING *")
-        .bind(&new_role)
-        .bind(&wallet_address)
-        .fetch_one(pool.get_ref())
-        .await
-    {
-        Ok(user) => HttpResponse::Ok().json(user),
-        Err(_) => HttpResponse::NotFound().body("User not found"),
-    }
-}
+    let mut transaction = pool.begin().await?;
 
-pub async fn register_user(req: web::Json<RegisterUserRequest>, pool: web::Data<PgPool>, auth: AuthExtractor) -> impl Responder {
-    if auth.role != "platform_owner" {
-        return HttpResponse::Unauthorized().body("Only platform owners can register users");
-    }
+    let user = sqlx::query_as::<_, User>("UPDATE users SET role = $1 WHERE id = $2 RETURNING *")
+        .bind(new_role)
+        .bind(user_id)
+        .fetch_optional(&mut *transaction)
+        .await?
+        .ok_or(ApiError::UserNotFound)?;
 
-    let new_user_role = req.role.clone().unwrap_or_else(|| "user".to_string());
+    // Outstanding tokens still carry the old role, so the user has to log in again
+    tokens::revoke_all(&mut transaction, user_id).await?;
 
-    // Basic role validation
-    if !["user", "project_admin", "platform_owner"].contains(&new_user_role.as_str()) {
-        return HttpResponse::BadRequest().body("Invalid role specified");
+    transaction.commit().await?;
+    Ok(HttpResponse::Ok().json(user))
+}
+
+pub async fn register_user(
+    req: web::Json<RegisterUserRequest>,
+    pool: web::Data<PgPool>,
+    accounts: web::Data<AccountKeys>,
+) -> Result<HttpResponse, ApiError> {
+    let mut transaction = pool.begin().await?;
+
+    match accounts.check(&mut transaction, &req.wallet_address, &req.public_key).await {
+        Ok(()) => {}
+        Err(ApiError::PublicKeyMismatch) => {
+            return Err(ApiError::Validation("Public key is not the auth key of this wallet's account".to_string()));
+        }
+        Err(e) => return Err(e),
     }
 
     let new_user = User {
-        wallet_address: req.wallet_address.clone(),
-        role: new_user_role,
+        id: Uuid::new_v4(),
+        role: req.role.unwrap_or(Role::User),
         created_at: chrono::Utc::now().naive_utc(),
+        token_version: 0,
     };
 
-    match sqlx::query_as::<_, User>("INSERT INTO users (wallet_address, role, created_at) VALUES ($1, $2, $3) RETURNING *")
-        .bind(&new_user.wallet_address)
-        .bind(&new_user.role)
+    let user = sqlx::query_as::<_, User>("INSERT INTO users (id, role, created_at) VALUES ($1, $2, $3) RETURNING *")
+        .bind(new_user.id)
+        .bind(new_user.role)
         .bind(new_user.created_at)
-        .fetch_one(pool.get_ref())
+        .fetch_one(&mut *transaction)
+        .await?;
+
+    match sqlx::query("INSERT INTO wallets (address, user_id, public_key, created_at) VALUES ($1, $2, $3, $4)")
+        .bind(&req.wallet_address)
+        .bind(user.id)
+        .bind(&req.public_key)
+        .bind(user.created_at)
+        .execute(&mut *transaction)
         .await
     {
-        Ok(user) => HttpResponse::Created().json(user),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+        Ok(_) => {}
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => return Err(ApiError::UserExists),
+        Err(e) => return Err(e.into()),
     }
+
+    transaction.commit().await?;
+    Ok(HttpResponse::Created().json(UserResponse { user, wallets: vec![req.wallet_address.clone()] }))
 }
@@ -0,0 +1,93 @@
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::accounts::AccountKeys;
+use crate::error::ApiError;
+use crate::handlers::auth_handlers::{self, LoginRequest};
+use crate::models::Wallet;
+use crate::AuthExtractor;
+
+// Handlers for the caller's own wallets
+pub async fn list_wallets(pool: web::Data<PgPool>, auth: AuthExtractor) -> Result<HttpResponse, ApiError> {
+    let wallets = sqlx::query_as::<_, Wallet>("SELECT * FROM wallets WHERE user_id = $1 ORDER BY created_at")
+        .bind(auth.user_id)
+        .fetch_all(pool.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(wallets))
+}
+
+/// Links another wallet to the caller. The wallet proves control by answering a login challenge,
+/// exactly as it would to sign in.
+pub async fn link_wallet(
+    req: web::Json<LoginRequest>,
+    pool: web::Data<PgPool>,
+    accounts: web::Data<AccountKeys>,
+    auth: AuthExtractor,
+) -> Result<HttpResponse, ApiError> {
+    let mut transaction = pool.begin().await?;
+
+    if let Err(e) = auth_handlers::verify_challenge(&mut transaction, &accounts, &req).await {
+        transaction.commit().await?;
+        return Err(e);
+    }
+
+    let linked = sqlx::query_as::<_, Wallet>(
+        "INSERT INTO wallets (address, user_id, public_key, created_at) VALUES ($1, $2, $3, $4) ON CONFLICT (address) DO NOTHING RETURNING *"
+    )
+    .bind(&req.wallet_address)
+    .bind(auth.user_id)
+    .bind(&req.public_key)
+    .bind(Utc::now().naive_utc())
+    .fetch_optional(&mut *transaction)
+    .await?;
+
+    if let Some(wallet) = linked {
+        transaction.commit().await?;
+        return Ok(HttpResponse::Created().json(wallet));
+    }
+
+    // Already linked: fine if it is the caller's own wallet
+    let existing = sqlx::query_as::<_, Wallet>("SELECT * FROM wallets WHERE address = $1")
+        .bind(&req.wallet_address)
+        .fetch_one(&mut *transaction)
+        .await?;
+    transaction.commit().await?;
+
+    if existing.user_id != auth.user_id {
+        return Err(ApiError::WalletInUse);
+    }
+    if existing.public_key.as_deref().is_some_and(|key| key != req.public_key) {
+        return Err(ApiError::PublicKeyMismatch);
+    }
+    Ok(HttpResponse::Ok().json(existing))
+}
+
+/// Unlinks one of the caller's wallets. Sessions opened with it end: its refresh tokens are deleted
+/// along with it, and access tokens issued to it no longer pass `tokens::check_access`.
+pub async fn unlink_wallet(path: web::Path<String>, pool: web::Data<PgPool>, auth: AuthExtractor) -> Result<HttpResponse, ApiError> {
+    let address = path.into_inner();
+    let mut transaction = pool.begin().await?;
+
+    // Lock the user's wallets so two concurrent unlinks can't remove the last two
+    let addresses = sqlx::query_scalar::<_, String>("SELECT address FROM wallets WHERE user_id = $1 FOR UPDATE")
+        .bind(auth.user_id)
+        .fetch_all(&mut *transaction)
+        .await?;
+
+    if !addresses.contains(&address) {
+        return Err(ApiError::WalletNotFound);
+    }
+    if addresses.len() == 1 {
+        return Err(ApiError::LastWallet);
+    }
+
+    sqlx::query("DELETE FROM wallets WHERE address = $1")
+        .bind(&address)
+        .execute(&mut *transaction)
+        .await?;
+
+    transaction.commit().await?;
+    Ok(HttpResponse::NoContent().finish())
+}
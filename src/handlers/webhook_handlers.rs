@@ -0,0 +1,178 @@
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use rand::RngCore;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::models::{Webhook, WebhookDelivery};
+use crate::pagination::{PageQuery, SortKey};
+use crate::permissions::{self, Permission};
+use crate::webhooks::{self, WebhookEvent, WebhookPolicy};
+use crate::AuthExtractor;
+
+// DTOs for request bodies
+#[derive(serde::Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct DeliveryFilter {
+    pub status: Option<String>,
+}
+
+// The only response that includes the signing secret
+#[derive(serde::Serialize)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+const WEBHOOK_SORTS: &[SortKey] = &[SortKey { name: "created_at", column: "created_at", sql_type: "timestamp" }];
+
+const DELIVERY_SORTS: &[SortKey] = &[SortKey { name: "created_at", column: "created_at", sql_type: "timestamp" }];
+
+// A project's webhook, or WebhookNotFound if it belongs to another project
+async fn find_webhook(conn: &mut PgConnection, project_id: Uuid, webhook_id: Uuid) -> Result<Webhook, ApiError> {
+    sqlx::query_as::<_, Webhook>("SELECT * FROM webhooks WHERE id = $1 AND project_id = $2")
+        .bind(webhook_id)
+        .bind(project_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(ApiError::WebhookNotFound)
+}
+
+// Handlers
+pub async fn create_webhook(
+    pool: web::Data<PgPool>,
+    project_id: web::Path<Uuid>,
+    req: web::Json<CreateWebhookRequest>,
+    auth: AuthExtractor,
+    policy: web::Data<WebhookPolicy>,
+) -> Result<HttpResponse, ApiError> {
+    let project_id = project_id.into_inner();
+    let mut conn = pool.acquire().await?;
+    permissions::require_in_project(&mut conn, &auth, Permission::ManageWebhooks, project_id).await?;
+
+    policy.check(&req.url).await.map_err(ApiError::Validation)?;
+
+    let mut events: Vec<&str> = req.events.iter().map(|e| e.as_str()).collect();
+    events.sort_unstable();
+    events.dedup();
+    if events.is_empty() {
+        return Err(ApiError::Validation("Subscribe to at least one event".to_string()));
+    }
+
+    let project_exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM projects WHERE id = $1)")
+        .bind(project_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    if !project_exists {
+        return Err(ApiError::ProjectNotFound);
+    }
+
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let secret = format!("whsec_{}", hex::encode(bytes));
+
+    let webhook = sqlx::query_as::<_, Webhook>(
+        "INSERT INTO webhooks (id, project_id, url, secret, events, created_at) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *"
+    )
+    .bind(Uuid::new_v4())
+    .bind(project_id)
+    .bind(&req.url)
+    .bind(&secret)
+    .bind(&events)
+    .bind(Utc::now().naive_utc())
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(HttpResponse::Created().json(CreatedWebhook { webhook, secret }))
+}
+
+pub async fn list_webhooks(
+    pool: web::Data<PgPool>,
+    project_id: web::Path<Uuid>,
+    page: web::Query<PageQuery>,
+    auth: AuthExtractor,
+) -> Result<HttpResponse, ApiError> {
+    let project_id = project_id.into_inner();
+    let mut conn = pool.acquire().await?;
+    permissions::require_in_project(&mut conn, &auth, Permission::ManageWebhooks, project_id).await?;
+    drop(conn);
+
+    let pagination = page.parse(WEBHOOK_SORTS)?;
+    let webhooks = pagination
+        .fetch::<Webhook>(pool.get_ref(), |query| {
+            query.push("SELECT * FROM webhooks WHERE project_id = ").push_bind(project_id);
+        })
+        .await?;
+
+    Ok(HttpResponse::Ok().json(webhooks))
+}
+
+pub async fn delete_webhook(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid)>, auth: AuthExtractor) -> Result<HttpResponse, ApiError> {
+    let (project_id, webhook_id) = path.into_inner();
+    let mut conn = pool.acquire().await?;
+    permissions::require_in_project(&mut conn, &auth, Permission::ManageWebhooks, project_id).await?;
+
+    // Its deliveries go with it
+    let deleted = sqlx::query("DELETE FROM webhooks WHERE id = $1 AND project_id = $2")
+        .bind(webhook_id)
+        .bind(project_id)
+        .execute(&mut *conn)
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(ApiError::WebhookNotFound);
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// The delivery log of a webhook: every attempt's outcome, including pending retries.
+pub async fn list_deliveries(
+    pool: web::Data<PgPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    page: web::Query<PageQuery>,
+    filter: web::Query<DeliveryFilter>,
+    auth: AuthExtractor,
+) -> Result<HttpResponse, ApiError> {
+    let (project_id, webhook_id) = path.into_inner();
+    let mut conn = pool.acquire().await?;
+    permissions::require_in_project(&mut conn, &auth, Permission::ManageWebhooks, project_id).await?;
+    find_webhook(&mut conn, project_id, webhook_id).await?;
+    drop(conn);
+
+    let pagination = page.parse(DELIVERY_SORTS)?;
+    let deliveries = pagination
+        .fetch::<WebhookDelivery>(pool.get_ref(), |query| {
+            query.push("SELECT * FROM webhook_deliveries WHERE webhook_id = ").push_bind(webhook_id);
+            if let Some(status) = &filter.status {
+                query.push(" AND status = ").push_bind(status.clone());
+            }
+        })
+        .await?;
+
+    Ok(HttpResponse::Ok().json(deliveries))
+}
+
+/// Queues a fresh delivery of an earlier one's event, e.g. after the endpoint was fixed.
+pub async fn redeliver(pool: web::Data<PgPool>, path: web::Path<(Uuid, Uuid, Uuid)>, auth: AuthExtractor) -> Result<HttpResponse, ApiError> {
+    let (project_id, webhook_id, delivery_id) = path.into_inner();
+    let mut conn = pool.acquire().await?;
+    permissions::require_in_project(&mut conn, &auth, Permission::ManageWebhooks, project_id).await?;
+    find_webhook(&mut conn, project_id, webhook_id).await?;
+
+    let delivery = sqlx::query_as::<_, WebhookDelivery>("SELECT * FROM webhook_deliveries WHERE id = $1 AND webhook_id = $2")
+        .bind(delivery_id)
+        .bind(webhook_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(ApiError::WebhookDeliveryNotFound)?;
+
+    let redelivery = webhooks::redeliver(&mut conn, &delivery).await?;
+    Ok(HttpResponse::Accepted().json(redelivery))
+}
@@ -0,0 +1,164 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ed25519_dalek::pkcs8::EncodePrivateKey;
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::error::ApiError;
+use crate::Claims;
+
+// Access tokens and vote receipts are signed with Ed25519 so others can verify them from a JWKS alone.
+// Each has its own keys, so a token key can't vouch for a receipt or the other way round.
+//   JWT_SIGNING_KEY=<kid>:<hex 32-byte seed>               the key new tokens are signed with
+//   JWT_VERIFICATION_KEYS=<kid>:<hex public key>,...        optional, keys still accepted but no longer signing with
+//   RECEIPT_SIGNING_KEY, RECEIPT_VERIFICATION_KEYS          the same for receipts
+// To rotate, move the old key's public half into the verification keys and install the new signing key.
+// Drop an old token key once the tokens it signed have expired; keep receipt keys as long as their
+// receipts should stay checkable.
+
+#[derive(Debug, Serialize)]
+pub struct Jwk {
+    pub kty: &'static str,
+    pub crv: &'static str,
+    pub alg: &'static str,
+    #[serde(rename = "use")]
+    pub use_: &'static str,
+    pub kid: String,
+    pub x: String, // Base64url public key
+}
+
+#[derive(Debug, Serialize)]
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
+}
+
+struct VerificationKey {
+    public_key: VerifyingKey,
+    decoding: DecodingKey,
+}
+
+pub struct Keyring {
+    signing_kid: String,
+    signing_key: SigningKey,
+    encoding: EncodingKey,
+    verification: HashMap<String, VerificationKey>,
+}
+
+fn split_entry(entry: &str) -> Result<(String, Vec<u8>), String> {
+    let (kid, key) = entry.trim().split_once(':').ok_or_else(|| format!("Expected <kid>:<hex key>, got {}", entry))?;
+    if kid.is_empty() {
+        return Err("Key id must not be empty".to_string());
+    }
+    let bytes = hex::decode(key.trim_start_matches("0x")).map_err(|_| format!("Key {} must be hex-encoded", kid))?;
+    Ok((kid.to_string(), bytes))
+}
+
+fn env_keys(signing_var: &str, verification_var: &str) -> Result<(String, String), String> {
+    let signing = std::env::var(signing_var).map_err(|_| format!("{} must be set", signing_var))?;
+    Ok((signing, std::env::var(verification_var).unwrap_or_default()))
+}
+
+fn key_bytes(kid: &str, bytes: Vec<u8>) -> Result<[u8; 32], String> {
+    bytes.try_into().map_err(|_| format!("Key {} must be 32 bytes", kid))
+}
+
+impl VerificationKey {
+    fn new(public_key: VerifyingKey) -> Self {
+        let decoding = DecodingKey::from_ed_components(&URL_SAFE_NO_PAD.encode(public_key.as_bytes()))
+            .expect("A base64url encoded Ed25519 key is a valid decoding key");
+        VerificationKey { public_key, decoding }
+    }
+}
+
+impl Keyring {
+    pub fn from_env() -> Result<Self, String> {
+        let (signing, verification) = env_keys("JWT_SIGNING_KEY", "JWT_VERIFICATION_KEYS")?;
+        Self::new(&signing, &verification)
+    }
+
+    pub fn new(signing: &str, verification: &str) -> Result<Self, String> {
+        let (signing_kid, seed) = split_entry(signing)?;
+        let signing_key = SigningKey::from_bytes(&key_bytes(&signing_kid, seed)?);
+        let der = signing_key.to_pkcs8_der().map_err(|e| e.to_string())?;
+
+        let mut keys = HashMap::new();
+        keys.insert(signing_kid.clone(), VerificationKey::new(signing_key.verifying_key()));
+
+        for entry in verification.split(',').filter(|e| !e.trim().is_empty()) {
+            let (kid, bytes) = split_entry(entry)?;
+            let public_key = VerifyingKey::from_bytes(&key_bytes(&kid, bytes)?).map_err(|_| format!("Key {} is not a valid Ed25519 public key", kid))?;
+            if keys.insert(kid.clone(), VerificationKey::new(public_key)).is_some() {
+                return Err(format!("Duplicate key id {}", kid));
+            }
+        }
+
+        Ok(Keyring {
+            signing_kid,
+            signing_key,
+            encoding: EncodingKey::from_ed_der(der.as_bytes()),
+            verification: keys,
+        })
+    }
+
+    pub fn sign(&self, claims: &Claims) -> Result<String, ApiError> {
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some(self.signing_kid.clone());
+        encode(&header, claims, &self.encoding).map_err(|e| ApiError::Internal(format!("Could not create JWT: {}", e)))
+    }
+
+    /// Checks the signature against the key named by the token's `kid`, and its expiry.
+    pub fn verify(&self, token: &str) -> Result<Claims, ApiError> {
+        let header = decode_header(token).map_err(|_| ApiError::InvalidToken)?;
+        let key = header
+            .kid
+            .and_then(|kid| self.verification.get(&kid))
+            .ok_or(ApiError::InvalidToken)?;
+
+        decode::<Claims>(token, &key.decoding, &Validation::new(Algorithm::EdDSA))
+            .map(|data| data.claims)
+            .map_err(|_| ApiError::InvalidToken)
+    }
+
+    pub fn jwks(&self) -> Jwks {
+        let mut keys: Vec<Jwk> = self
+            .verification
+            .iter()
+            .map(|(kid, key)| Jwk {
+                kty: "OKP",
+                crv: "Ed25519",
+                alg: "EdDSA",
+                use_: "sig",
+                kid: kid.clone(),
+                x: URL_SAFE_NO_PAD.encode(key.public_key.as_bytes()),
+            })
+            .collect();
+        keys.sort_by(|a, b| a.kid.cmp(&b.kid));
+        Jwks { keys }
+    }
+}
+
+/// Keys vote receipts are signed with, published at /.well-known/receipt-keys.json.
+pub struct ReceiptKeyring(Keyring);
+
+impl ReceiptKeyring {
+    pub fn from_env() -> Result<Self, String> {
+        let (signing, verification) = env_keys("RECEIPT_SIGNING_KEY", "RECEIPT_VERIFICATION_KEYS")?;
+        Self::new(&signing, &verification)
+    }
+
+    pub fn new(signing: &str, verification: &str) -> Result<Self, String> {
+        Keyring::new(signing, verification).map(ReceiptKeyring)
+    }
+
+    /// Signs with the current receipt key. Returns the key id and the base64url signature.
+    pub fn sign(&self, message: &[u8]) -> (String, String) {
+        let signature = self.0.signing_key.sign(message);
+        (self.0.signing_kid.clone(), URL_SAFE_NO_PAD.encode(signature.to_bytes()))
+    }
+
+    pub fn jwks(&self) -> Jwks {
+        self.0.jwks()
+    }
+}
@@ -0,0 +1,8 @@
+// Everything needed to check a proposal's outcome without the database, shared by the server and the
+// offline verifier (`miden-vote-verify`).
+pub mod ballot_log;
+pub mod bundle;
+pub mod election;
+pub mod merkle;
+pub mod tally;
+pub mod verifier;
@@ -0,0 +1,178 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use std::fmt;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::events::{EventBus, EventKind};
+use crate::models::Proposal;
+use crate::webhooks::{self, WebhookEvent};
+
+// How often the scheduler opens and closes proposals whose start_ts/end_ts have passed
+const SCHEDULER_INTERVAL_SECS: u64 = 15;
+
+// Set on proposals as they become active: the eligibility snapshot ballots and quorum are checked against
+const FREEZE_ELIGIBILITY: &str = "eligibility_root = (SELECT merkle_root FROM projects WHERE id = proposals.project_id), \
+    eligible_voters = (SELECT COUNT(*) FROM eligibility_leaves WHERE project_id = proposals.project_id), \
+    eligible_weight = (SELECT COALESCE(SUM(weight), 0)::BIGINT FROM eligibility_leaves WHERE project_id = proposals.project_id)";
+
+// Holds back proposals whose election trustees haven't generated its key yet, since there is nothing to encrypt ballots to
+const ELECTION_KEYED: &str = "NOT EXISTS (SELECT 1 FROM elections WHERE proposal_id = proposals.id AND public_key IS NULL)";
+
+// ProposalState: draft -> scheduled -> active -> closed -> tallied -> finalized, or closed -> failed when the
+// tally misses quorum, or revoked from any non-terminal state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum ProposalState {
+    Draft,
+    Scheduled,
+    Active,
+    Closed,
+    Tallied,
+    Finalized,
+    Failed,
+    Revoked,
+}
+
+impl ProposalState {
+    pub const ALL: [ProposalState; 8] = [
+        ProposalState::Draft,
+        ProposalState::Scheduled,
+        ProposalState::Active,
+        ProposalState::Closed,
+        ProposalState::Tallied,
+        ProposalState::Finalized,
+        ProposalState::Failed,
+        ProposalState::Revoked,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProposalState::Draft => "draft",
+            ProposalState::Scheduled => "scheduled",
+            ProposalState::Active => "active",
+            ProposalState::Closed => "closed",
+            ProposalState::Tallied => "tallied",
+            ProposalState::Finalized => "finalized",
+            ProposalState::Failed => "failed",
+            ProposalState::Revoked => "revoked",
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, ProposalState::Finalized | ProposalState::Failed | ProposalState::Revoked)
+    }
+
+    pub fn can_transition_to(&self, next: ProposalState) -> bool {
+        use ProposalState::*;
+        match (self, next) {
+            (Draft, Scheduled) | (Scheduled, Active) | (Active, Closed) | (Closed, Tallied) | (Closed, Failed) | (Tallied, Finalized) => true,
+            (from, Revoked) => !from.is_terminal(),
+            _ => false,
+        }
+    }
+
+    /// States from which `next` can be reached in one step.
+    pub fn predecessors(next: ProposalState) -> Vec<ProposalState> {
+        Self::ALL.iter().copied().filter(|s| s.can_transition_to(next)).collect()
+    }
+}
+
+impl fmt::Display for ProposalState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub enum TransitionError {
+    NotFound,
+    Invalid { from: ProposalState, to: ProposalState },
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for TransitionError {
+    fn from(e: sqlx::Error) -> Self {
+        TransitionError::Database(e)
+    }
+}
+
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransitionError::NotFound => write!(f, "Proposal not found"),
+            TransitionError::Invalid { from, to } => write!(f, "Cannot move proposal from {} to {}", from, to),
+            TransitionError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Moves a proposal to `to`, atomically checking the move is allowed from its current state.
+pub async fn transition(conn: &mut PgConnection, proposal_id: Uuid, to: ProposalState) -> Result<Proposal, TransitionError> {
+    let freeze = if to == ProposalState::Active { format!(", {}", FREEZE_ELIGIBILITY) } else { String::new() };
+    let updated = sqlx::query_as::<_, Proposal>(&format!(
+        "UPDATE proposals SET state = $1, revoked = revoked OR $1 = 'revoked', finalized = finalized OR $1 = 'finalized'{} WHERE id = $2 AND state = ANY($3) RETURNING *",
+        freeze
+    ))
+    .bind(to)
+    .bind(proposal_id)
+    .bind(ProposalState::predecessors(to).iter().map(|s| s.as_str()).collect::<Vec<_>>())
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    if let Some(proposal) = updated {
+        return Ok(proposal);
+    }
+
+    match sqlx::query_scalar::<_, ProposalState>("SELECT state FROM proposals WHERE id = $1")
+        .bind(proposal_id)
+        .fetch_optional(&mut *conn)
+        .await?
+    {
+        Some(from) => Err(TransitionError::Invalid { from, to }),
+        None => Err(TransitionError::NotFound),
+    }
+}
+
+/// Opens scheduled proposals whose start_ts has passed and closes active ones past end_ts.
+pub async fn advance_by_time(pool: &PgPool, events: &EventBus) -> Result<(), sqlx::Error> {
+    let now = Utc::now().naive_utc();
+
+    let opened = sqlx::query_scalar::<_, Uuid>(&format!(
+        "UPDATE proposals SET state = 'active', {} WHERE state = 'scheduled' AND start_ts <= $1 AND {} RETURNING id",
+        FREEZE_ELIGIBILITY, ELECTION_KEYED
+    ))
+    .bind(now)
+    .fetch_all(pool)
+    .await?;
+    for proposal_id in opened {
+        events.publish(proposal_id, EventKind::StateChanged { state: ProposalState::Active });
+    }
+
+    let mut transaction = pool.begin().await?;
+    let closed = sqlx::query_as::<_, Proposal>("UPDATE proposals SET state = 'closed' WHERE state = 'active' AND end_ts <= $1 RETURNING *")
+        .bind(now)
+        .fetch_all(&mut *transaction)
+        .await?;
+    for proposal in &closed {
+        webhooks::enqueue(&mut transaction, proposal.project_id, WebhookEvent::Closed, serde_json::json!({ "proposal": proposal })).await?;
+    }
+    transaction.commit().await?;
+    for proposal in closed {
+        events.publish(proposal.id, EventKind::StateChanged { state: ProposalState::Closed });
+    }
+
+    Ok(())
+}
+
+pub async fn run_scheduler(pool: PgPool, events: EventBus) {
+    let mut interval = actix_web::rt::time::interval(Duration::from_secs(SCHEDULER_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        if let Err(e) = advance_by_time(&pool, &events).await {
+            log::error!("Failed to advance proposal states: {}", e);
+        }
+    }
+}
@@ -3,25 +3,49 @@ use actix_web::middleware::Logger;
 use std::io;
 use uuid::Uuid;
 use actix_web::dev::Payload;
-use actix_web::error::ErrorUnauthorized;
 use actix_web::http::header::AUTHORIZATION;
 use std::future::{ready, Ready};
 use futures_util::future::LocalBoxFuture;
 use std::rc::Rc;
 use std::task::{Context, Poll};
-use jsonwebtoken::{decode, DecodingKey, Validation, Algorithm, Header};
 use serde::{Deserialize, Serialize};
-
+use sqlx::PgPool;
+
+use crate::accounts::AccountKeys;
+use crate::error::ApiError;
+use crate::events::EventBus;
+use crate::keyring::{Keyring, ReceiptKeyring};
+use crate::permissions::Role;
+use crate::verifier::ProgramHashes;
+use crate::webhooks::WebhookPolicy;
+
+mod accounts;
+mod bulletin;
 mod db;
+mod error;
+mod events;
 mod models;
 mod handlers;
+mod keyring;
+mod lifecycle;
+mod pagination;
+mod permissions;
+mod receipts;
 mod routes;
-mod verifier;
+mod tokens;
+mod webhooks;
+
+// Shared with the offline verifier
+use miden_voting_backend::{ballot_log, bundle, election, merkle, tally, verifier};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
-    pub user_id: Uuid,
-    pub role: String,
+    pub user_id: Uuid,          // users.id, stable across the user's wallets
+    pub wallet_address: String, // Wallet that signed the login challenge
+    pub role: Role,
+    pub ver: i32,   // users.token_version when issued
+    pub jti: Uuid,
+    pub iat: usize,
     pub exp: usize,
 }
 
@@ -29,18 +53,21 @@ pub struct Claims {
 #[derive(Clone, Debug)]
 pub struct AuthExtractor {
     pub user_id: Uuid,
-    pub role: String,
+    pub wallet_address: String,
+    pub role: Role,
+    pub jti: Uuid,
+    pub exp: usize,
 }
 
 impl FromRequest for AuthExtractor {
-    type Error = Error;
-    type Future = Ready<Result<Self, Error>>;
+    type Error = ApiError;
+    type Future = Ready<Result<Self, ApiError>>;
 
     fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
         if let Some(auth) = req.extensions().get::<AuthExtractor>() {
             ready(Ok(auth.clone()))
         } else {
-            ready(Err(ErrorUnauthorized("Unauthorized")))
+            ready(Err(ApiError::Unauthenticated))
         }
     }
 }
@@ -91,21 +118,20 @@ where
             if let Some(auth_header) = auth_header {
                 if auth_header.starts_with("Bearer ") {
                     let token = auth_header.trim_start_matches("Bearer ");
-                    let secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set");
-
-                    match decode::<Claims>(
-                        token,
-                        &DecodingKey::from_secret(secret.as_ref()),
-                        &Validation::new(Algorithm::Hs256),
-                    ) {
-                        Ok(token_data) => {
-                            let user_id = token_data.claims.user_id;
-                            let role = token_data.claims.role;
-                            let auth_info = AuthExtractor { user_id, role };
-                            req.extensions_mut().insert(auth_info);
-                        }
-                        Err(_) => return Err(ErrorUnauthorized("Invalid token")),
-                    }
+                    let keyring = req
+                        .app_data::<web::Data<Keyring>>()
+                        .ok_or_else(|| ApiError::Internal("JWT keyring is not configured".to_string()))?;
+                    let claims = keyring.verify(token)?;
+
+                    // Signature and expiry aren't enough: the token may have been logged out or its role changed
+                    let pool = req
+                        .app_data::<web::Data<PgPool>>()
+                        .ok_or_else(|| ApiError::Internal("Database pool is not configured".to_string()))?;
+                    tokens::check_access(pool, &claims).await?;
+
+                    let Claims { user_id, wallet_address, role, jti, exp, .. } = claims;
+                    let auth_info = AuthExtractor { user_id, wallet_address, role, jti, exp };
+                    req.extensions_mut().insert(auth_info);
                 }
             }
 
@@ -123,13 +149,40 @@ async fn main() -> io::Result<()> {
         .await
         .expect("Failed to create pool.");
 
+    let keyring = web::Data::new(Keyring::from_env().expect("Failed to load JWT keys."));
+    let receipt_keys = web::Data::new(ReceiptKeyring::from_env().expect("Failed to load receipt keys."));
+    let programs = web::Data::new(ProgramHashes::from_env().expect("Failed to load program hashes."));
+    let events = web::Data::new(EventBus::default());
+    let webhook_policy = web::Data::new(WebhookPolicy::from_env());
+    let account_keys = web::Data::new(AccountKeys::from_env());
+
+    // Open and close proposals as their voting windows start and end
+    actix_web::rt::spawn(lifecycle::run_scheduler(pool.clone(), events.get_ref().clone()));
+    // Drop denylisted and refresh tokens once they have expired, and spent or expired login challenges
+    actix_web::rt::spawn(tokens::run_purger(pool.clone()));
+    // Log ballots counted before the bulletin board existed, then publish roots as logs grow
+    bulletin::backfill(&pool).await.expect("Failed to backfill the bulletin board.");
+    actix_web::rt::spawn(bulletin::run_publisher(pool.clone()));
+    // Sign receipts for ballots counted before receipts were stored
+    receipts::backfill(&pool, &receipt_keys).await.expect("Failed to backfill vote receipts.");
+    // Send queued webhook deliveries and retry failed ones
+    actix_web::rt::spawn(webhooks::run_dispatcher(pool.clone(), webhook_policy.get_ref().clone()));
+
     HttpServer::new(move || {
         App::new()
             .wrap(Logger::default())
             .wrap(Auth) // Apply the Auth middleware globally
             .app_data(web::Data::new(pool.clone()))
+            .app_data(keyring.clone())
+            .app_data(receipt_keys.clone())
+            .app_data(programs.clone())
+            .app_data(events.clone())
+            .app_data(webhook_policy.clone())
+            .app_data(account_keys.clone())
+            .app_data(web::JsonConfig::default().error_handler(|e, _| error::extractor_error(e)))
+            .app_data(web::PathConfig::default().error_handler(|e, _| error::extractor_error(e)))
+            .app_data(web::QueryConfig::default().error_handler(|e, _| error::extractor_error(e)))
             .configure(routes::config_routes)
-            .configure(routes::auth_routes)
     })
     .workers(1)
     .bind(("127.0.0.1", 8080))?
@@ -0,0 +1,77 @@
+use miden_crypto::hash::rpo::{Rpo256, RpoDigest};
+use miden_crypto::{Felt, StarkField};
+
+// Binary RPO Merkle tree over eligibility leaves, matching what the vote program
+// recomputes inside the VM when it checks the voter's inclusion path.
+pub struct MerkleTree {
+    levels: Vec<Vec<RpoDigest>>, // levels[0] are the (padded) leaves, the last level is the root
+}
+
+impl MerkleTree {
+    pub fn new(mut leaves: Vec<RpoDigest>) -> Self {
+        // Pad to a power of two with empty leaves so every node has a sibling
+        let width = leaves.len().max(1).next_power_of_two();
+        leaves.resize(width, RpoDigest::default());
+
+        let mut levels = vec![leaves];
+        while levels.last().map_or(0, |l| l.len()) > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| Rpo256::merge(&[pair[0], pair[1]]))
+                .collect();
+            levels.push(next);
+        }
+
+        MerkleTree { levels }
+    }
+
+    pub fn root(&self) -> RpoDigest {
+        self.levels.last().unwrap()[0]
+    }
+
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Sibling hashes from the leaf up to (but excluding) the root.
+    pub fn path(&self, index: usize) -> Option<Vec<RpoDigest>> {
+        if index >= self.levels[0].len() {
+            return None;
+        }
+
+        let mut path = Vec::with_capacity(self.depth());
+        let mut i = index;
+        for level in &self.levels[..self.depth()] {
+            path.push(level[i ^ 1]);
+            i /= 2;
+        }
+        Some(path)
+    }
+}
+
+/// Leaf committing to a voter and their voting weight: RPO(RPO(address) || weight).
+pub fn leaf_hash(address: &str, weight: u64) -> RpoDigest {
+    let mut elements = Rpo256::hash(address.as_bytes()).as_elements().to_vec();
+    elements.push(Felt::new(weight));
+    Rpo256::hash_elements(&elements)
+}
+
+pub fn digest_to_hex(digest: &RpoDigest) -> String {
+    format!("0x{}", hex::encode(digest.as_bytes()))
+}
+
+pub fn digest_from_hex(value: &str) -> Option<RpoDigest> {
+    RpoDigest::try_from(value).ok()
+}
+
+/// The four field elements of a digest as they appear in the vote program's public inputs.
+pub fn digest_to_ints(digest: &RpoDigest) -> [u64; 4] {
+    let e = digest.as_elements();
+    [e[0].as_int(), e[1].as_int(), e[2].as_int(), e[3].as_int()]
+}
+
+pub fn digest_from_ints(ints: [u64; 4]) -> RpoDigest {
+    RpoDigest::new(ints.map(Felt::new))
+}
@@ -3,54 +3,309 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::NaiveDateTime;
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-pub struct User {
-    pub wallet_address: String,
-    pub role: String,
-    pub created_at: NaiveDateTime,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-pub struct Project {
-    pub id: Uuid,
-    pub owner: String,
-    pub token_address: String,
-    pub merkle_root: String,
-    pub config: serde_json::Value,
-    pub created_at: NaiveDateTime,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-pub struct Proposal {
-    pub id: Uuid,
-    pub project_id: Uuid,
-    pub title: String,
-    pub choices_json: serde_json::Value,
-    pub model_enum: String,
-    pub quorum: f64,
-    pub start_ts: NaiveDateTime,
-    pub end_ts: NaiveDateTime,
-    pub state: String,
-    pub revoked: bool,
-    pub finalized: bool,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-pub struct Submission {
-    pub id: Uuid,
-    pub proposal_id: Uuid,
-    pub proof_hash: String,
-    pub note_commitment: String,
-    pub nullifier_hash: String,
-    pub verified_bool: bool,
-    pub verified_at: Option<NaiveDateTime>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-pub struct Tally {
-    pub id: Uuid,
-    pub proposal_id: Uuid,
-    pub aggregate_proof_hash: String,
-    pub results_json: serde_json::Value,
-    pub verified_at: NaiveDateTime,
+use crate::lifecycle::ProposalState;
+use crate::permissions::{ProjectRole, Role};
+
+/// Table a model is loaded from, with the columns its `FromRow` impl expects as
+/// (name, Postgres `data_type`, nullable). `db::init_db` checks these against the live schema.
+pub trait TableSchema {
+    const TABLE: &'static str;
+    const COLUMNS: &'static [(&'static str, &'static str, bool)];
+}
+
+/// Postgres `data_type` a field of this type is decoded from, and whether the column may be NULL.
+pub trait Column {
+    const DATA_TYPE: &'static str;
+    const NULLABLE: bool = false;
+}
+
+macro_rules! column {
+    ($($ty:ty => $data_type:literal),* $(,)?) => {
+        $(impl Column for $ty {
+            const DATA_TYPE: &'static str = $data_type;
+        })*
+    };
+}
+
+column! {
+    Uuid => "uuid",
+    String => "text",
+    serde_json::Value => "jsonb",
+    bool => "boolean",
+    i32 => "integer",
+    i64 => "bigint",
+    f64 => "double precision",
+    NaiveDateTime => "timestamp without time zone",
+    Vec<String> => "ARRAY",
+    Role => "text",
+    ProjectRole => "text",
+    ProposalState => "text",
+}
+
+impl<T: Column> Column for Option<T> {
+    const DATA_TYPE: &'static str = T::DATA_TYPE;
+    const NULLABLE: bool = true;
+}
+
+/// Declares a model together with its `TableSchema`, so the checked columns are always the
+/// struct's own fields.
+macro_rules! table {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident in $table:literal {
+            $($(#[$field_meta:meta])* pub $field:ident: $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        pub struct $name {
+            $($(#[$field_meta])* pub $field: $ty),*
+        }
+
+        impl TableSchema for $name {
+            const TABLE: &'static str = $table;
+            const COLUMNS: &'static [(&'static str, &'static str, bool)] = &[
+                $((stringify!($field), <$ty as Column>::DATA_TYPE, <$ty as Column>::NULLABLE)),*
+            ];
+        }
+    };
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct User in "users" {
+        pub id: Uuid,
+        pub role: Role,
+        pub created_at: NaiveDateTime,
+        #[serde(skip)]
+        pub token_version: i32, // Bumped to invalidate every outstanding access token
+    }
+}
+
+// Wallet: An address a user signs in with. A user can link several.
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct Wallet in "wallets" {
+        pub address: String,
+        pub user_id: Uuid,
+        pub public_key: Option<String>, // Account auth key the wallet proved, recorded when the wallet is added
+        pub created_at: NaiveDateTime,
+    }
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct RefreshToken in "refresh_tokens" {
+        pub id: Uuid,
+        pub family_id: Uuid, // Shared by every token rotated from the same login
+        pub user_id: Uuid,
+        pub wallet_address: String, // Wallet the session was opened with
+        pub token_hash: String,
+        pub expires_at: NaiveDateTime,
+        pub created_at: NaiveDateTime,
+        pub revoked_at: Option<NaiveDateTime>,
+    }
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct RevokedToken in "revoked_tokens" {
+        pub jti: Uuid,
+        pub expires_at: NaiveDateTime,
+    }
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct AuthChallenge in "auth_challenges" {
+        pub nonce: String,
+        pub wallet_address: String,
+        pub expires_at: NaiveDateTime,
+        pub consumed_at: Option<NaiveDateTime>,
+    }
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct Project in "projects" {
+        pub id: Uuid,
+        pub owner: String,
+        pub token_address: String,
+        pub merkle_root: String,
+        pub config: serde_json::Value,
+        pub status: String,
+        pub created_at: NaiveDateTime,
+    }
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct ProjectMember in "project_members" {
+        pub project_id: Uuid,
+        pub user_id: Uuid,
+        pub role: ProjectRole,
+        pub created_at: NaiveDateTime,
+    }
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct EligibilityLeaf in "eligibility_leaves" {
+        pub project_id: Uuid,
+        pub leaf_index: i32,
+        pub address: String,
+        pub weight: i64,
+        pub leaf_hash: String,
+    }
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct Proposal in "proposals" {
+        pub id: Uuid,
+        pub project_id: Uuid,
+        pub title: String,
+        pub choices_json: serde_json::Value,
+        pub model_enum: String,
+        pub quorum: f64,
+        pub quorum_basis: String,
+        pub start_ts: NaiveDateTime,
+        pub end_ts: NaiveDateTime,
+        pub state: ProposalState,
+        pub revoked: bool,
+        pub finalized: bool,
+        // The project's eligibility snapshot when the proposal became active; unset before that
+        pub eligibility_root: Option<String>,
+        pub eligible_voters: Option<i64>,
+        pub eligible_weight: Option<i64>,
+        pub quorum_json: Option<serde_json::Value>, // Quorum report of a proposal that failed it; tallied ones keep theirs on the tally
+        pub imported_at: Option<NaiveDateTime>, // Set when the proposal was imported from another deployment
+    }
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct Submission in "submissions" {
+        pub id: Uuid,
+        pub proposal_id: Uuid,
+        pub proof_hash: String,
+        pub program_hash: String,
+        pub public_inputs: serde_json::Value,
+        pub stack_outputs: serde_json::Value,
+        pub note_commitment: String,
+        pub nullifier_hash: String,
+        pub weight: i64,
+        pub ballot: serde_json::Value, // Choice indices, most preferred first
+        pub encrypted_ballot: Option<serde_json::Value>, // Set instead of ballot when the proposal has an election
+        pub verified_bool: bool,
+        pub failure_reason: Option<String>,
+        pub verified_at: Option<NaiveDateTime>,
+        pub inclusion_index: Option<i64>, // Set on counted ballots, in the order they were accepted
+        pub receipt_kid: Option<String>, // Receipt key the ballot's receipt was signed with when it was counted
+        pub receipt_signature: Option<String>,
+    }
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct BulletinEntry in "bulletin_entries" {
+        pub proposal_id: Uuid,
+        pub entry_index: i64,
+        pub submission_id: Uuid,
+        pub leaf_hash: String,
+        pub chain_hash: String, // SHA-256(previous chain_hash || leaf_hash)
+        pub root: String,       // Merkle root of entries 0..=entry_index
+        #[serde(skip)]
+        pub frontier: serde_json::Value,
+        pub created_at: NaiveDateTime,
+    }
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct BulletinRoot in "bulletin_roots" {
+        pub proposal_id: Uuid,
+        pub tree_size: i64,
+        pub root: String,
+        pub chain_hash: String,
+        pub published_at: NaiveDateTime,
+    }
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct Webhook in "webhooks" {
+        pub id: Uuid,
+        pub project_id: Uuid,
+        pub url: String,
+        #[serde(skip_serializing)] // Shown once, when the webhook is created
+        pub secret: String,
+        pub events: Vec<String>,
+        pub created_at: NaiveDateTime,
+    }
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct WebhookDelivery in "webhook_deliveries" {
+        pub id: Uuid,
+        pub webhook_id: Uuid,
+        pub event_id: Uuid,
+        pub event: String,
+        pub payload: serde_json::Value,
+        pub status: String, // "pending", "delivered" or "failed"
+        pub attempts: i32,
+        pub next_attempt_at: Option<NaiveDateTime>,
+        pub last_attempt_at: Option<NaiveDateTime>,
+        pub response_status: Option<i32>,
+        pub last_error: Option<String>,
+        pub redelivery_of: Option<Uuid>,
+        pub created_at: NaiveDateTime,
+        pub delivered_at: Option<NaiveDateTime>,
+    }
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct Election in "elections" {
+        pub proposal_id: Uuid,
+        pub threshold: i32,
+        pub trustee_count: i32,
+        pub public_key: Option<String>, // Set once the trustees' key generation completes
+        pub created_at: NaiveDateTime,
+    }
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct ElectionTrustee in "election_trustees" {
+        pub proposal_id: Uuid,
+        pub trustee_index: i32,
+        pub user_id: Option<Uuid>, // Registered trustee; unset on elections imported from another deployment
+        pub transport_key: Option<String>, // Key the others deal the trustee's shares to
+        pub commitments: Option<serde_json::Value>, // Feldman commitments a_k * G to the trustee's dealing polynomial
+        pub dealt_shares: Option<serde_json::Value>, // election::EncryptedShare for each trustee, in index order
+        pub dealt_at: Option<NaiveDateTime>,
+        pub shares_checked_at: Option<NaiveDateTime>, // Set once the trustee has checked what they were dealt
+        pub disqualified: bool, // A share the trustee dealt was shown not to match their commitments
+        pub public_share: Option<String>, // x_i * G, derived from the qualified dealings once the key is set
+        pub decryption_shares: Option<serde_json::Value>,
+        pub shares_posted_at: Option<NaiveDateTime>,
+    }
+}
+
+table! {
+    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+    pub struct Tally in "tallies" {
+        pub id: Uuid,
+        pub proposal_id: Uuid,
+        pub aggregate_proof_hash: Option<String>, // Proof of the tally program; None for tallies from before proofs were required
+        pub results_json: serde_json::Value,
+        pub quorum_json: serde_json::Value,
+        pub verified_at: NaiveDateTime,
+        pub bulletin_size: Option<i64>, // Bulletin board root the tally was computed against
+        pub bulletin_root: Option<String>,
+        pub aggregate_program_hash: Option<String>,
+        pub aggregate_public_inputs: Option<serde_json::Value>,
+        pub aggregate_stack_outputs: Option<serde_json::Value>,
+    }
 }
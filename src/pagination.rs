@@ -0,0 +1,141 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder, Row};
+use uuid::Uuid;
+
+use crate::error::ApiError;
+
+// Keyset pagination shared by the list routes. Rows are ordered by a whitelisted sort column with `id`
+// as tie-breaker, and a page resumes strictly after the (sort value, id) of the previous page's last
+// row, so pages stay stable while rows are inserted.
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+// PageQuery: ?limit=&cursor=&sort= on every list route. `sort` names a sort key, prefixed with `-`
+// for descending; `cursor` is the `next_cursor` of the previous page, issued for the same sort.
+#[derive(Debug, Deserialize)]
+pub struct PageQuery {
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+}
+
+// SortKey: A column a list can be sorted by. `column` is evaluated over the listed rows and must not be
+// NULL; `sql_type` is what the cursor's text value is cast back to.
+pub struct SortKey {
+    pub name: &'static str,
+    pub column: &'static str,
+    pub sql_type: &'static str,
+}
+
+// Page: The envelope of every list response; `next_cursor` is absent on the last page
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Cursor {
+    sort: String,
+    value: String,
+    id: Uuid,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    fn decode(cursor: &str) -> Option<Cursor> {
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+}
+
+pub struct Pagination {
+    key: &'static SortKey,
+    descending: bool,
+    limit: i64,
+    after: Option<Cursor>,
+}
+
+impl PageQuery {
+    /// Resolves the query against the sort keys a list allows; without `sort`, the first key ascending.
+    pub fn parse(&self, keys: &'static [SortKey]) -> Result<Pagination, ApiError> {
+        let sort = self.sort.as_deref().unwrap_or(keys[0].name);
+        let (name, descending) = match sort.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (sort, false),
+        };
+        let key = keys.iter().find(|k| k.name == name).ok_or_else(|| {
+            let names: Vec<&str> = keys.iter().map(|k| k.name).collect();
+            ApiError::Validation(format!("Unknown sort '{}', expected one of: {}", name, names.join(", ")))
+        })?;
+
+        let limit = self.limit.unwrap_or(DEFAULT_LIMIT);
+        if !(1..=MAX_LIMIT).contains(&limit) {
+            return Err(ApiError::Validation(format!("limit must be between 1 and {}", MAX_LIMIT)));
+        }
+
+        let after = match &self.cursor {
+            Some(cursor) => {
+                let cursor = Cursor::decode(cursor).ok_or_else(|| ApiError::Validation("Invalid cursor".to_string()))?;
+                if cursor.sort != sort {
+                    return Err(ApiError::Validation(format!("Cursor was issued for sort '{}'", cursor.sort)));
+                }
+                Some(cursor)
+            }
+            None => None,
+        };
+
+        Ok(Pagination { key, descending, limit, after })
+    }
+}
+
+impl Pagination {
+    /// Fetches one page of the rows selected by `rows`, which pushes a complete SELECT (filters
+    /// included) that yields an `id` column and the sort column.
+    pub async fn fetch<T>(&self, pool: &PgPool, rows: impl FnOnce(&mut QueryBuilder<'_, Postgres>)) -> Result<Page<T>, ApiError>
+    where
+        T: for<'r> FromRow<'r, PgRow>,
+    {
+        let column = self.key.column;
+        let (direction, comparison) = if self.descending { ("DESC", "<") } else { ("ASC", ">") };
+
+        let mut query = QueryBuilder::new("SELECT *, (");
+        query.push(column).push(")::text AS page_sort_value FROM (");
+        rows(&mut query);
+        query.push(") AS page_rows");
+
+        if let Some(after) = &self.after {
+            query.push(" WHERE (").push(column).push(", id) ").push(comparison).push(" ((");
+            query.push_bind(after.value.clone()).push(")::").push(self.key.sql_type).push(", ");
+            query.push_bind(after.id).push(")");
+        }
+
+        // One row past the page tells whether there's a next one
+        query.push(format!(" ORDER BY {} {}, id {} LIMIT ", column, direction, direction));
+        query.push_bind(self.limit + 1);
+
+        let mut rows = query.build().fetch_all(pool).await?;
+        let next_cursor = if rows.len() as i64 > self.limit {
+            rows.truncate(self.limit as usize);
+            let last = &rows[rows.len() - 1];
+            let cursor = Cursor {
+                sort: format!("{}{}", if self.descending { "-" } else { "" }, self.key.name),
+                value: last.try_get("page_sort_value")?,
+                id: last.try_get("id")?,
+            };
+            Some(cursor.encode())
+        } else {
+            None
+        };
+
+        let items = rows.iter().map(T::from_row).collect::<Result<Vec<T>, _>>()?;
+        Ok(Page { items, next_cursor })
+    }
+}
@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::fmt;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::AuthExtractor;
+
+// Role: A user's platform-wide role, stored in users.role and carried in the JWT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum Role {
+    User,
+    ProjectAdmin,
+    PlatformOwner,
+}
+
+// ProjectRole: A role on a single project, granted through project_members
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum ProjectRole {
+    Admin,  // Everything on the project, including its members
+    Editor, // Drafts and publishes proposals
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    // Platform-wide
+    ManageUsers,
+    CreateProjects,
+    ManageProjectStatus,
+    ListProjects,
+    ListProposals,
+    FinalizeTallies,
+    ImportProposals,
+    // Per project
+    ManageMembers,
+    ManageEligibility,
+    ManageProposals,
+    RevokeProposals,
+    TallyProposals,
+    ManageWebhooks,
+}
+
+impl Role {
+    pub fn grants(&self, permission: Permission) -> bool {
+        match self {
+            Role::PlatformOwner => true,
+            Role::ProjectAdmin => matches!(permission, Permission::ListProjects | Permission::ListProposals),
+            Role::User => false,
+        }
+    }
+}
+
+impl ProjectRole {
+    pub fn grants(&self, permission: Permission) -> bool {
+        match self {
+            ProjectRole::Admin => matches!(
+                permission,
+                Permission::ManageMembers
+                    | Permission::ManageEligibility
+                    | Permission::ManageProposals
+                    | Permission::RevokeProposals
+                    | Permission::TallyProposals
+                    | Permission::ManageWebhooks
+            ),
+            ProjectRole::Editor => permission == Permission::ManageProposals,
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Permission::ManageUsers => "manage users",
+            Permission::CreateProjects => "create projects",
+            Permission::ManageProjectStatus => "manage project status",
+            Permission::ListProjects => "list projects",
+            Permission::ListProposals => "list proposals",
+            Permission::FinalizeTallies => "finalize tallies",
+            Permission::ImportProposals => "import proposals",
+            Permission::ManageMembers => "manage project members",
+            Permission::ManageEligibility => "manage eligibility",
+            Permission::ManageProposals => "manage proposals",
+            Permission::RevokeProposals => "revoke proposals",
+            Permission::TallyProposals => "tally proposals",
+            Permission::ManageWebhooks => "manage webhooks",
+        })
+    }
+}
+
+/// Checks a platform-wide permission against the caller's role.
+pub fn require(auth: &AuthExtractor, permission: Permission) -> Result<(), ApiError> {
+    if auth.role.grants(permission) {
+        Ok(())
+    } else {
+        Err(ApiError::Forbidden(permission))
+    }
+}
+
+/// Checks `permission` on a project, granted either by the caller's platform role or their membership.
+pub async fn require_in_project(
+    conn: &mut PgConnection,
+    auth: &AuthExtractor,
+    permission: Permission,
+    project_id: Uuid,
+) -> Result<(), ApiError> {
+    if auth.role.grants(permission) {
+        return Ok(());
+    }
+
+    let role = sqlx::query_scalar::<_, ProjectRole>("SELECT role FROM project_members WHERE project_id = $1 AND user_id = $2")
+        .bind(project_id)
+        .bind(auth.user_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    match role {
+        Some(role) if role.grants(permission) => Ok(()),
+        _ => Err(ApiError::Forbidden(permission)),
+    }
+}
+
+/// `require_in_project` for the project that owns `proposal_id`.
+pub async fn require_for_proposal(
+    conn: &mut PgConnection,
+    auth: &AuthExtractor,
+    permission: Permission,
+    proposal_id: Uuid,
+) -> Result<(), ApiError> {
+    let project_id = sqlx::query_scalar::<_, Uuid>("SELECT project_id FROM proposals WHERE id = $1")
+        .bind(proposal_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(ApiError::ProposalNotFound)?;
+
+    require_in_project(conn, auth, permission, project_id).await
+}
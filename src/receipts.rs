@@ -0,0 +1,80 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::keyring::ReceiptKeyring;
+use crate::models::Submission;
+
+// Receipt: What a voter keeps to later show their ballot was counted. It names the ballot by its
+// nullifier only, so it reveals nothing about the choice. Signed once, when the ballot is counted, with
+// the receipt key `kid`; check it by rebuilding `Receipt::message` and verifying `signature` against that
+// key in /.well-known/receipt-keys.json.
+#[derive(Debug, Clone, Serialize)]
+pub struct Receipt {
+    pub submission_id: Uuid,
+    pub proposal_id: Uuid,
+    pub nullifier_hash: String,
+    pub inclusion_index: i64,
+    pub issued_at: NaiveDateTime, // When the ballot was verified
+    pub kid: String,
+    pub signature: String, // Base64url Ed25519 signature over `message`
+}
+
+impl Receipt {
+    /// Signs the receipt of a counted ballot, returning the key id and signature to store with it;
+    /// `None` for ballots that were rejected.
+    pub fn sign(keys: &ReceiptKeyring, submission: &Submission) -> Option<(String, String)> {
+        let inclusion_index = submission.inclusion_index?;
+        let issued_at = submission.verified_at?;
+        let message = Self::message(submission.id, submission.proposal_id, &submission.nullifier_hash, inclusion_index, issued_at);
+        Some(keys.sign(message.as_bytes()))
+    }
+
+    /// The receipt stored with a counted ballot; `None` for rejected ballots.
+    pub fn stored(submission: &Submission) -> Option<Receipt> {
+        Some(Receipt {
+            submission_id: submission.id,
+            proposal_id: submission.proposal_id,
+            nullifier_hash: submission.nullifier_hash.clone(),
+            inclusion_index: submission.inclusion_index?,
+            issued_at: submission.verified_at?,
+            kid: submission.receipt_kid.clone()?,
+            signature: submission.receipt_signature.clone()?,
+        })
+    }
+
+    /// The signed text, one field per line; `issued_at` as Unix microseconds.
+    pub fn message(submission_id: Uuid, proposal_id: Uuid, nullifier_hash: &str, inclusion_index: i64, issued_at: NaiveDateTime) -> String {
+        format!(
+            "miden-voting receipt v1\nsubmission: {}\nproposal: {}\nnullifier: {}\nindex: {}\nissued_at: {}",
+            submission_id,
+            proposal_id,
+            nullifier_hash,
+            inclusion_index,
+            issued_at.and_utc().timestamp_micros()
+        )
+    }
+}
+
+/// Signs the receipts of ballots counted here before receipts were stored. Runs once at startup.
+/// Imported ballots are left as they came: a receipt is only ever issued by the deployment that counted it.
+pub async fn backfill(pool: &PgPool, keys: &ReceiptKeyring) -> Result<(), sqlx::Error> {
+    let submissions = sqlx::query_as::<_, Submission>(
+        "SELECT s.* FROM submissions s JOIN proposals p ON p.id = s.proposal_id \
+         WHERE s.verified_bool = TRUE AND s.receipt_signature IS NULL AND p.imported_at IS NULL"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for submission in &submissions {
+        let Some((kid, signature)) = Receipt::sign(keys, submission) else { continue };
+        sqlx::query("UPDATE submissions SET receipt_kid = $1, receipt_signature = $2 WHERE id = $3 AND receipt_signature IS NULL")
+            .bind(kid)
+            .bind(signature)
+            .bind(submission.id)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use actix_web::dev::{HttpServiceFactory, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::Method;
+use actix_web::{guard, web, Error, FromRequest, Handler, HttpMessage, Responder};
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+use std::task::{Context, Poll};
+
+use crate::error::ApiError;
+use crate::permissions::{self, Permission};
+use crate::AuthExtractor;
+
+// Access: Who may reach a route. Checked before the handler runs, on the AuthExtractor the global
+// Auth middleware attached. Project-scoped permissions depend on the project being addressed, so
+// handlers still check those themselves; routes using them are declared `Authenticated`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Public,
+    Authenticated,
+    Permission(Permission), // Platform-wide, granted by the caller's role
+}
+
+impl Access {
+    fn check(&self, auth: Option<&AuthExtractor>) -> Result<(), ApiError> {
+        match (self, auth) {
+            (Access::Public, _) => Ok(()),
+            (_, None) => Err(ApiError::Unauthenticated),
+            (Access::Authenticated, Some(_)) => Ok(()),
+            (Access::Permission(permission), Some(auth)) => permissions::require(auth, *permission),
+        }
+    }
+}
+
+/// `method path`, served by `handler` to callers with `access`.
+pub fn route<F, Args>(method: Method, path: &str, access: Access, handler: F) -> impl HttpServiceFactory
+where
+    F: Handler<Args>,
+    Args: FromRequest + 'static,
+    F::Output: Responder + 'static,
+{
+    // One resource per method, so methods on the same path can differ in access
+    web::resource(path)
+        .guard(guard::Method(method.clone()))
+        .wrap(RequireAccess(access))
+        .route(web::method(method).to(handler))
+}
+
+pub fn get<F, Args>(path: &str, access: Access, handler: F) -> impl HttpServiceFactory
+where
+    F: Handler<Args>,
+    Args: FromRequest + 'static,
+    F::Output: Responder + 'static,
+{
+    route(Method::GET, path, access, handler)
+}
+
+pub fn post<F, Args>(path: &str, access: Access, handler: F) -> impl HttpServiceFactory
+where
+    F: Handler<Args>,
+    Args: FromRequest + 'static,
+    F::Output: Responder + 'static,
+{
+    route(Method::POST, path, access, handler)
+}
+
+pub fn put<F, Args>(path: &str, access: Access, handler: F) -> impl HttpServiceFactory
+where
+    F: Handler<Args>,
+    Args: FromRequest + 'static,
+    F::Output: Responder + 'static,
+{
+    route(Method::PUT, path, access, handler)
+}
+
+pub fn delete<F, Args>(path: &str, access: Access, handler: F) -> impl HttpServiceFactory
+where
+    F: Handler<Args>,
+    Args: FromRequest + 'static,
+    F::Output: Responder + 'static,
+{
+    route(Method::DELETE, path, access, handler)
+}
+
+pub struct RequireAccess(pub Access);
+
+impl<S, B> Transform<S, ServiceRequest> for RequireAccess
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequireAccessMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireAccessMiddleware { service, access: self.0 }))
+    }
+}
+
+pub struct RequireAccessMiddleware<S> {
+    service: S,
+    access: Access,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireAccessMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let checked = self.access.check(req.extensions().get::<AuthExtractor>());
+        match checked {
+            Ok(()) => Box::pin(self.service.call(req)),
+            Err(e) => Box::pin(ready(Err(e.into()))),
+        }
+    }
+}
@@ -1,10 +1,16 @@
-use actix_web::{web};
+use actix_web::web;
 
-use crate::handlers::auth_handlers;
+use crate::handlers::{auth_handlers, submission_handlers};
+use crate::routes::access::{get, post, Access::*};
 
 pub fn auth_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/auth")
-            .route("/login", web::post().to(auth_handlers::login)),
-    );
+            .service(get("/challenge", Public, auth_handlers::challenge))
+            .service(post("/login", Public, auth_handlers::login))
+            .service(post("/refresh", Public, auth_handlers::refresh))
+            .service(post("/logout", Authenticated, auth_handlers::logout)),
+    )
+    .service(get("/.well-known/jwks.json", Public, auth_handlers::jwks))
+    .service(get("/.well-known/receipt-keys.json", Public, submission_handlers::receipt_keys));
 }
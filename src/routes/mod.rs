@@ -1,8 +1,14 @@
+use actix_web::web;
+
+pub mod access;
 pub mod project_routes;
 pub mod proposal_routes;
 pub mod auth_routes;
 pub mod user_routes;
 
+#[cfg(test)]
+mod tests;
+
 pub fn config_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(web::scope("")
         .configure(project_routes::project_routes)
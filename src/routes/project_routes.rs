@@ -1,15 +1,31 @@
 use actix_web::web;
 
+use crate::handlers::eligibility_handlers;
+use crate::handlers::member_handlers;
 use crate::handlers::project_handlers;
 use crate::handlers::proposal_handlers;
+use crate::handlers::webhook_handlers;
+use crate::permissions::Permission::*;
+use crate::routes::access::{delete, get, post, put, Access::*};
 
 pub fn project_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/projects")
-            .route("", web::post().to(project_handlers::create_project))
-            .route("", web::get().to(project_handlers::get_all_projects))
-            .route("/{project_id}", web::get().to(project_handlers::get_project))
-            .route("/{project_id}/status", web::put().to(project_handlers::update_project_status))
-            .route("/{project_id}/proposals", web::post().to(proposal_handlers::create_proposal)),
+            .service(post("", Permission(CreateProjects), project_handlers::create_project))
+            .service(get("", Permission(ListProjects), project_handlers::get_all_projects))
+            .service(get("/{project_id}", Public, project_handlers::get_project))
+            .service(put("/{project_id}/status", Permission(ManageProjectStatus), project_handlers::update_project_status))
+            .service(get("/{project_id}/members", Authenticated, member_handlers::list_members))
+            .service(put("/{project_id}/members/{user_id}", Authenticated, member_handlers::set_member))
+            .service(delete("/{project_id}/members/{user_id}", Authenticated, member_handlers::remove_member))
+            .service(post("/{project_id}/eligibility", Authenticated, eligibility_handlers::upload_snapshot))
+            .service(get("/{project_id}/eligibility/{address}", Public, eligibility_handlers::get_eligibility_proof))
+            .service(post("/{project_id}/proposals", Authenticated, proposal_handlers::create_proposal))
+            .service(get("/{project_id}/proposals", Public, proposal_handlers::list_project_proposals))
+            .service(post("/{project_id}/webhooks", Authenticated, webhook_handlers::create_webhook))
+            .service(get("/{project_id}/webhooks", Authenticated, webhook_handlers::list_webhooks))
+            .service(delete("/{project_id}/webhooks/{webhook_id}", Authenticated, webhook_handlers::delete_webhook))
+            .service(get("/{project_id}/webhooks/{webhook_id}/deliveries", Authenticated, webhook_handlers::list_deliveries))
+            .service(post("/{project_id}/webhooks/{webhook_id}/deliveries/{delivery_id}/redeliver", Authenticated, webhook_handlers::redeliver)),
     );
 }
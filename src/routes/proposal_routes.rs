@@ -1,18 +1,40 @@
 use actix_web::web;
 
+use crate::handlers::bulletin_handlers;
+use crate::handlers::bundle_handlers;
+use crate::handlers::election_handlers;
+use crate::handlers::event_handlers;
 use crate::handlers::proposal_handlers;
 use crate::handlers::submission_handlers;
 use crate::handlers::tally_handlers;
+use crate::permissions::Permission::*;
+use crate::routes::access::{get, post, Access::*};
 
 pub fn proposal_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/proposals")
-            .route("", web::get().to(proposal_handlers::get_all_proposals))
-            .route("/{proposal_id}", web::get().to(proposal_handlers::get_proposal))
-            .route("/{proposal_id}/submit", web::post().to(submission_handlers::submit_vote))
-            .route("/{proposal_id}/submissions", web::get().to(submission_handlers::list_submissions))
-            .route("/{proposal_id}/tally", web::post().to(tally_handlers::tally_proposal))
-            .route("/{proposal_id}/revoke", web::post().to(proposal_handlers::revoke_proposal))
-            .route("/{proposal_id}/finalize", web::post().to(proposal_handlers::finalize_tally)),
+            .service(get("", Permission(ListProposals), proposal_handlers::get_all_proposals))
+            .service(post("/import", Permission(ImportProposals), bundle_handlers::import_proposal))
+            .service(get("/{proposal_id}", Public, proposal_handlers::get_proposal))
+            .service(post("/{proposal_id}/submit", Authenticated, submission_handlers::submit_vote))
+            .service(get("/{proposal_id}/submissions", Public, submission_handlers::list_submissions))
+            .service(get("/{proposal_id}/export", Public, bundle_handlers::export_proposal))
+            .service(get("/{proposal_id}/events", Public, event_handlers::proposal_events))
+            .service(get("/{proposal_id}/receipts/{nullifier_hash}", Public, submission_handlers::get_receipt))
+            .service(get("/{proposal_id}/bulletin", Public, bulletin_handlers::get_bulletin))
+            .service(get("/{proposal_id}/bulletin/entries", Public, bulletin_handlers::list_entries))
+            .service(get("/{proposal_id}/bulletin/inclusion", Public, bulletin_handlers::get_inclusion_proof))
+            .service(get("/{proposal_id}/bulletin/consistency", Public, bulletin_handlers::get_consistency_proof))
+            .service(post("/{proposal_id}/election", Authenticated, election_handlers::setup_election))
+            .service(get("/{proposal_id}/election", Public, election_handlers::get_election))
+            .service(post("/{proposal_id}/election/transport-key", Authenticated, election_handlers::post_transport_key))
+            .service(post("/{proposal_id}/election/dealing", Authenticated, election_handlers::post_dealing))
+            .service(post("/{proposal_id}/election/complaints", Authenticated, election_handlers::post_complaints))
+            .service(post("/{proposal_id}/election/shares", Authenticated, election_handlers::post_decryption_shares))
+            .service(post("/{proposal_id}/tally", Authenticated, tally_handlers::tally_proposal))
+            .service(get("/{proposal_id}/tally/proof", Public, tally_handlers::get_tally_proof))
+            .service(post("/{proposal_id}/publish", Authenticated, proposal_handlers::publish_proposal))
+            .service(post("/{proposal_id}/revoke", Authenticated, proposal_handlers::revoke_proposal))
+            .service(post("/{proposal_id}/finalize", Permission(FinalizeTallies), proposal_handlers::finalize_tally)),
     );
 }
@@ -0,0 +1,476 @@
+// Access matrix: every route, called anonymously and with each role, must be guarded as declared.
+// Runs against a fresh database per test, created from DATABASE_URL by `sqlx::test`.
+
+use actix_web::body::{self, MessageBody};
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::http::{Method, StatusCode};
+use actix_web::{test, web, App, Error};
+use chrono::Utc;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::scalar::Scalar;
+use miden_crypto::dsa::rpo_falcon512::SecretKey;
+use miden_crypto::hash::rpo::{Rpo256, RpoDigest};
+use miden_crypto::utils::Serializable;
+use miden_crypto::Word;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::accounts::AccountKeys;
+use crate::election;
+use crate::events::EventBus;
+use crate::keyring::{Keyring, ReceiptKeyring};
+use crate::models::User;
+use crate::verifier::ProgramHashes;
+use crate::webhooks::WebhookPolicy;
+use crate::permissions::Permission::*;
+use crate::permissions::{ProjectRole, Role};
+use crate::routes::access::Access::{self, *};
+use crate::{routes, tokens, Auth};
+
+const SIGNING_KEY: &str = "test:0101010101010101010101010101010101010101010101010101010101010101";
+const RECEIPT_KEY: &str = "test-receipts:0202020202020202020202020202020202020202020202020202020202020202";
+const PROGRAM_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+fn matrix() -> Vec<(Method, String, Access)> {
+    let project = Uuid::new_v4();
+    let proposal = Uuid::new_v4();
+    let user = Uuid::new_v4();
+    let webhook = Uuid::new_v4();
+    let delivery = Uuid::new_v4();
+
+    vec![
+        // auth_routes
+        (Method::GET, "/auth/challenge?wallet_address=0xabc".to_string(), Public),
+        (Method::POST, "/auth/login".to_string(), Public),
+        (Method::POST, "/auth/refresh".to_string(), Public),
+        (Method::POST, "/auth/logout".to_string(), Authenticated),
+        (Method::GET, "/.well-known/jwks.json".to_string(), Public),
+        (Method::GET, "/.well-known/receipt-keys.json".to_string(), Public),
+        // project_routes
+        (Method::POST, "/projects".to_string(), Permission(CreateProjects)),
+        (Method::GET, "/projects".to_string(), Permission(ListProjects)),
+        (Method::GET, format!("/projects/{}", project), Public),
+        (Method::PUT, format!("/projects/{}/status", project), Permission(ManageProjectStatus)),
+        (Method::GET, format!("/projects/{}/members", project), Authenticated),
+        (Method::PUT, format!("/projects/{}/members/{}", project, user), Authenticated),
+        (Method::DELETE, format!("/projects/{}/members/{}", project, user), Authenticated),
+        (Method::POST, format!("/projects/{}/eligibility", project), Authenticated),
+        (Method::GET, format!("/projects/{}/eligibility/0xabc", project), Public),
+        (Method::POST, format!("/projects/{}/proposals", project), Authenticated),
+        (Method::GET, format!("/projects/{}/proposals", project), Public),
+        (Method::POST, format!("/projects/{}/webhooks", project), Authenticated),
+        (Method::GET, format!("/projects/{}/webhooks", project), Authenticated),
+        (Method::DELETE, format!("/projects/{}/webhooks/{}", project, webhook), Authenticated),
+        (Method::GET, format!("/projects/{}/webhooks/{}/deliveries", project, webhook), Authenticated),
+        (Method::POST, format!("/projects/{}/webhooks/{}/deliveries/{}/redeliver", project, webhook, delivery), Authenticated),
+        // proposal_routes
+        (Method::GET, "/proposals".to_string(), Permission(ListProposals)),
+        (Method::POST, "/proposals/import".to_string(), Permission(ImportProposals)),
+        (Method::GET, format!("/proposals/{}", proposal), Public),
+        (Method::POST, format!("/proposals/{}/submit", proposal), Authenticated),
+        (Method::GET, format!("/proposals/{}/submissions", proposal), Public),
+        (Method::GET, format!("/proposals/{}/export", proposal), Public),
+        (Method::GET, format!("/proposals/{}/events", proposal), Public),
+        (Method::GET, format!("/proposals/{}/receipts/0xabc", proposal), Public),
+        (Method::GET, format!("/proposals/{}/bulletin", proposal), Public),
+        (Method::GET, format!("/proposals/{}/bulletin/entries", proposal), Public),
+        (Method::GET, format!("/proposals/{}/bulletin/inclusion?leaf_index=0&tree_size=1", proposal), Public),
+        (Method::GET, format!("/proposals/{}/bulletin/consistency?from=0&to=1", proposal), Public),
+        (Method::POST, format!("/proposals/{}/election", proposal), Authenticated),
+        (Method::GET, format!("/proposals/{}/election", proposal), Public),
+        (Method::POST, format!("/proposals/{}/election/transport-key", proposal), Authenticated),
+        (Method::POST, format!("/proposals/{}/election/dealing", proposal), Authenticated),
+        (Method::POST, format!("/proposals/{}/election/complaints", proposal), Authenticated),
+        (Method::POST, format!("/proposals/{}/election/shares", proposal), Authenticated),
+        (Method::POST, format!("/proposals/{}/tally", proposal), Authenticated),
+        (Method::GET, format!("/proposals/{}/tally/proof", proposal), Public),
+        (Method::POST, format!("/proposals/{}/publish", proposal), Authenticated),
+        (Method::POST, format!("/proposals/{}/revoke", proposal), Authenticated),
+        (Method::POST, format!("/proposals/{}/finalize", proposal), Permission(FinalizeTallies)),
+        // user_routes
+        (Method::GET, "/users".to_string(), Permission(ManageUsers)),
+        (Method::POST, "/users".to_string(), Permission(ManageUsers)),
+        (Method::GET, "/users/me/wallets".to_string(), Authenticated),
+        (Method::POST, "/users/me/wallets".to_string(), Authenticated),
+        (Method::DELETE, "/users/me/wallets/0xabc".to_string(), Authenticated),
+        (Method::PUT, format!("/users/{}/role", user), Permission(ManageUsers)),
+    ]
+}
+
+// A project with two draft proposals and a webhook, owned by nobody the membership tests log in as
+struct ProjectFixture {
+    id: Uuid,
+    proposal: Uuid,
+    revocable: Uuid,
+    webhook: Uuid,
+}
+
+// Project-scoped routes with the project permission each needs and the status a caller holding it gets.
+// In call order: the snapshot goes up while the proposals are still drafts, and the tally is refused
+// on state once the permission check passes.
+fn project_matrix(
+    project: &ProjectFixture,
+    user: Uuid,
+) -> Vec<(Method, String, Option<serde_json::Value>, crate::permissions::Permission, StatusCode)> {
+    let proposal = serde_json::json!({
+        "title": "Proposal",
+        "choices_json": ["yes", "no"],
+        "model_enum": "one-person-one-vote",
+        "quorum": 0,
+        "start_ts": "2099-01-01T00:00:00",
+        "end_ts": "2099-01-02T00:00:00",
+    });
+    let snapshot = serde_json::json!({ "leaves": [{ "address": "0xabc", "weight": 1 }] });
+
+    vec![
+        (Method::GET, format!("/projects/{}/members", project.id), None, ManageMembers, StatusCode::OK),
+        (Method::PUT, format!("/projects/{}/members/{}", project.id, user), Some(serde_json::json!({ "role": "editor" })), ManageMembers, StatusCode::OK),
+        (Method::DELETE, format!("/projects/{}/members/{}", project.id, user), None, ManageMembers, StatusCode::NO_CONTENT),
+        (Method::POST, format!("/projects/{}/eligibility", project.id), Some(snapshot), ManageEligibility, StatusCode::OK),
+        (Method::POST, format!("/projects/{}/proposals", project.id), Some(proposal), ManageProposals, StatusCode::CREATED),
+        (Method::POST, format!("/proposals/{}/publish", project.proposal), None, ManageProposals, StatusCode::OK),
+        (Method::POST, format!("/proposals/{}/revoke", project.revocable), None, RevokeProposals, StatusCode::OK),
+        (Method::POST, format!("/proposals/{}/tally", project.proposal), Some(serde_json::json!({ "aggregate_proof": "00" })), TallyProposals, StatusCode::CONFLICT),
+        (Method::GET, format!("/projects/{}/webhooks", project.id), None, ManageWebhooks, StatusCode::OK),
+        (Method::GET, format!("/projects/{}/webhooks/{}/deliveries", project.id, project.webhook), None, ManageWebhooks, StatusCode::OK),
+        (Method::DELETE, format!("/projects/{}/webhooks/{}", project.id, project.webhook), None, ManageWebhooks, StatusCode::NO_CONTENT),
+    ]
+}
+
+fn keyring() -> web::Data<Keyring> {
+    web::Data::new(Keyring::new(SIGNING_KEY, "").expect("Test signing key is valid"))
+}
+
+// A user with `role` and one wallet
+async fn create_user(pool: &PgPool, role: Role) -> (User, String) {
+    let wallet_address = format!("0x{}", Uuid::new_v4().simple());
+    let user = sqlx::query_as::<_, User>("INSERT INTO users (id, role, created_at) VALUES ($1, $2, $3) RETURNING *")
+        .bind(Uuid::new_v4())
+        .bind(role)
+        .bind(Utc::now().naive_utc())
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO wallets (address, user_id) VALUES ($1, $2)")
+        .bind(&wallet_address)
+        .bind(user.id)
+        .execute(pool)
+        .await
+        .unwrap();
+    (user, wallet_address)
+}
+
+async fn create_project(pool: &PgPool) -> ProjectFixture {
+    let id = Uuid::new_v4();
+    sqlx::query("INSERT INTO projects (id, owner, token_address, merkle_root) VALUES ($1, '0xowner', '0xtoken', '0x00')")
+        .bind(id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    let mut proposals = [Uuid::new_v4(), Uuid::new_v4()];
+    for proposal in &mut proposals {
+        sqlx::query(
+            "INSERT INTO proposals (id, project_id, title, choices_json, model_enum, quorum, start_ts, end_ts) \
+             VALUES ($1, $2, 'Proposal', '[\"yes\", \"no\"]', 'one-person-one-vote', 0, '2099-01-01', '2099-01-02')",
+        )
+        .bind(*proposal)
+        .bind(id)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    let webhook = Uuid::new_v4();
+    sqlx::query("INSERT INTO webhooks (id, project_id, url, secret, events) VALUES ($1, $2, 'https://hooks.example/vote', 'secret', '{proposal.created}')")
+        .bind(webhook)
+        .bind(id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    ProjectFixture { id, proposal: proposals[0], revocable: proposals[1], webhook }
+}
+
+// A fresh access token per request, so the logout route can't revoke the next request's token
+async fn access_token(pool: &PgPool, keyring: &Keyring, user: &(User, String)) -> String {
+    let mut conn = pool.acquire().await.unwrap();
+    tokens::issue(&mut conn, keyring, &user.0, &user.1).await.unwrap().token
+}
+
+fn request(method: &Method, path: &str, token: Option<&str>) -> test::TestRequest {
+    let req = test::TestRequest::default().method(method.clone()).uri(path);
+    match token {
+        Some(token) => req.insert_header(("Authorization", format!("Bearer {}", token))),
+        None => req,
+    }
+}
+
+// Status and error code of a response, whether the error came from a handler or a middleware
+async fn call<S, R, B>(app: &S, req: R) -> (StatusCode, Option<String>)
+where
+    S: Service<R, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody,
+{
+    let (status, bytes) = match test::try_call_service(app, req).await {
+        Ok(res) => (res.status(), body::to_bytes(res.into_body()).await.unwrap_or_default()),
+        Err(e) => {
+            let res = e.error_response();
+            (res.status(), body::to_bytes(res.into_body()).await.unwrap_or_default())
+        }
+    };
+
+    let code = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .ok()
+        .and_then(|body| body["code"].as_str().map(str::to_string));
+    (status, code)
+}
+
+macro_rules! app {
+    ($pool:expr, $keyring:expr) => {
+        test::init_service(
+            App::new()
+                .wrap(Auth)
+                .app_data(web::Data::new($pool.clone()))
+                .app_data($keyring.clone())
+                .app_data(web::Data::new(ReceiptKeyring::new(RECEIPT_KEY, "").expect("Test receipt key is valid")))
+                .app_data(web::Data::new(ProgramHashes { vote: PROGRAM_HASH.to_string(), tally: PROGRAM_HASH.to_string() }))
+                .app_data(web::Data::new(EventBus::default()))
+                .app_data(web::Data::new(WebhookPolicy::default()))
+                .app_data(web::Data::new(AccountKeys::default()))
+                .configure(routes::config_routes),
+        )
+        .await
+    };
+}
+
+#[sqlx::test]
+async fn anonymous_requests_only_reach_public_routes(pool: PgPool) {
+    let keyring = keyring();
+    let app = app!(pool, keyring);
+
+    for (method, path, access) in matrix() {
+        let (status, code) = call(&app, request(&method, &path, None).to_request()).await;
+        if access == Public {
+            assert_ne!(code.as_deref(), Some("UNAUTHENTICATED"), "{} {} should be public", method, path);
+        } else {
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{} {} should require a token", method, path);
+            assert_eq!(code.as_deref(), Some("UNAUTHENTICATED"), "{} {}", method, path);
+        }
+    }
+}
+
+#[sqlx::test]
+async fn invalid_tokens_are_rejected(pool: PgPool) {
+    let keyring = keyring();
+    let app = app!(pool, keyring);
+
+    for (method, path, _) in matrix() {
+        let (status, code) = call(&app, request(&method, &path, Some("not-a-jwt")).to_request()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{} {}", method, path);
+        assert_eq!(code.as_deref(), Some("INVALID_TOKEN"), "{} {}", method, path);
+    }
+}
+
+#[sqlx::test]
+async fn permission_routes_follow_the_callers_role(pool: PgPool) {
+    let keyring = keyring();
+    let app = app!(pool, keyring);
+
+    for role in [Role::User, Role::ProjectAdmin, Role::PlatformOwner] {
+        let user = create_user(&pool, role).await;
+
+        for (method, path, access) in matrix() {
+            let token = access_token(&pool, &keyring, &user).await;
+            let (status, code) = call(&app, request(&method, &path, Some(&token)).to_request()).await;
+            assert_ne!(code.as_deref(), Some("UNAUTHENTICATED"), "{:?} {} {}", role, method, path);
+
+            match access {
+                Permission(permission) if !role.grants(permission) => {
+                    assert_eq!(status, StatusCode::FORBIDDEN, "{:?} {} {}", role, method, path);
+                    assert_eq!(code.as_deref(), Some("FORBIDDEN"), "{:?} {} {}", role, method, path);
+                }
+                // Platform owners hold every permission, project-scoped ones included
+                _ if role == Role::PlatformOwner => {
+                    assert_ne!(code.as_deref(), Some("FORBIDDEN"), "{:?} {} {}", role, method, path);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[sqlx::test]
+async fn unlinked_wallet_tokens_are_rejected(pool: PgPool) {
+    let keyring = keyring();
+    let app = app!(pool, keyring);
+
+    let user = create_user(&pool, Role::User).await;
+    let token = access_token(&pool, &keyring, &user).await;
+    sqlx::query("DELETE FROM wallets WHERE address = $1").bind(&user.1).execute(&pool).await.unwrap();
+
+    let (status, code) = call(&app, request(&Method::GET, "/users/me/wallets", Some(&token)).to_request()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(code.as_deref(), Some("TOKEN_REVOKED"));
+}
+
+#[sqlx::test]
+async fn project_routes_follow_the_callers_membership(pool: PgPool) {
+    let keyring = keyring();
+    let app = app!(pool, keyring);
+
+    // A project admin's platform role grants nothing inside a project; only membership does
+    let caller = create_user(&pool, Role::ProjectAdmin).await;
+    let other = create_user(&pool, Role::User).await;
+
+    for membership in [Some(ProjectRole::Admin), Some(ProjectRole::Editor), None] {
+        let project = create_project(&pool).await;
+        if let Some(role) = membership {
+            sqlx::query("INSERT INTO project_members (project_id, user_id, role) VALUES ($1, $2, $3)")
+                .bind(project.id)
+                .bind(caller.0.id)
+                .bind(role)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        for (method, path, body, permission, success) in project_matrix(&project, other.0.id) {
+            let token = access_token(&pool, &keyring, &caller).await;
+            let req = match body {
+                Some(body) => request(&method, &path, Some(&token)).set_json(body),
+                None => request(&method, &path, Some(&token)),
+            };
+            let (status, code) = call(&app, req.to_request()).await;
+
+            if membership.is_some_and(|role| role.grants(permission)) {
+                assert_eq!(status, success, "{:?} {} {} ({:?})", membership, method, path, code);
+            } else {
+                assert_eq!(status, StatusCode::FORBIDDEN, "{:?} {} {}", membership, method, path);
+                assert_eq!(code.as_deref(), Some("FORBIDDEN"), "{:?} {} {}", membership, method, path);
+            }
+        }
+    }
+}
+
+#[sqlx::test]
+async fn open_challenges_are_capped_per_address(pool: PgPool) {
+    let keyring = keyring();
+    let app = app!(pool, keyring);
+    let challenge = |address: &str| request(&Method::GET, &format!("/auth/challenge?wallet_address={}", address), None).to_request();
+
+    for _ in 0..5 {
+        assert_eq!(call(&app, challenge("0xabc")).await.0, StatusCode::OK);
+    }
+    let (status, code) = call(&app, challenge("0xabc")).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(code.as_deref(), Some("TOO_MANY_CHALLENGES"));
+    assert_eq!(call(&app, challenge("0xdef")).await.0, StatusCode::OK);
+
+    // Spent and expired challenges no longer count, and the purger deletes them
+    sqlx::query("UPDATE auth_challenges SET consumed_at = NOW() WHERE nonce = (SELECT nonce FROM auth_challenges WHERE wallet_address = '0xabc' LIMIT 1)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE auth_challenges SET expires_at = NOW() - INTERVAL '1 minute' WHERE wallet_address = '0xdef'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(call(&app, challenge("0xabc")).await.0, StatusCode::OK);
+
+    tokens::purge_expired(&pool).await.unwrap();
+    let left = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM auth_challenges").fetch_one(&pool).await.unwrap();
+    assert_eq!(left, 5);
+}
+
+#[sqlx::test]
+async fn logins_are_checked_against_the_accounts_auth_key(pool: PgPool) {
+    let keyring = keyring();
+    let app = app!(pool, keyring);
+
+    // A Miden account and the Falcon key its auth component holds; the ID says nothing about the key
+    let account_id = "0x9a4bd5b3e4a6c1f0";
+    let auth_key = SecretKey::new();
+    let other_key = SecretKey::new();
+    let key_hex = |key: &SecretKey| RpoDigest::from(Word::from(key.public_key())).to_hex();
+    sqlx::query("INSERT INTO account_keys (account_id, public_key) VALUES ($1, $2)")
+        .bind(account_id)
+        .bind(key_hex(&auth_key))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let cases = [
+        (account_id, &other_key, StatusCode::UNAUTHORIZED),
+        ("0x7c31e0f4a2b9d855", &auth_key, StatusCode::UNAUTHORIZED), // An account whose key isn't known
+        (account_id, &auth_key, StatusCode::OK),
+    ];
+    for (address, key, expected) in cases {
+        let req = request(&Method::GET, &format!("/auth/challenge?wallet_address={}", address), None).to_request();
+        let challenge: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let signature = key.sign(Rpo256::hash(challenge["message"].as_str().unwrap().as_bytes()).into());
+
+        let login = serde_json::json!({
+            "wallet_address": address,
+            "nonce": challenge["nonce"],
+            "public_key": key_hex(key),
+            "signed_message": hex::encode(signature.to_bytes()),
+        });
+        let (status, code) = call(&app, request(&Method::POST, "/auth/login", None).set_json(login).to_request()).await;
+        assert_eq!(status, expected, "{} signed by {}: {:?}", address, key_hex(key), code);
+        if expected != StatusCode::OK {
+            assert_eq!(code.as_deref(), Some("PUBLIC_KEY_MISMATCH"));
+        }
+    }
+}
+
+#[sqlx::test]
+async fn key_generation_steps_are_reserved_for_registered_trustees(pool: PgPool) {
+    let keyring = keyring();
+    let app = app!(pool, keyring);
+
+    let project = create_project(&pool).await;
+    let manager = create_user(&pool, Role::User).await;
+    sqlx::query("INSERT INTO project_members (project_id, user_id, role) VALUES ($1, $2, $3)")
+        .bind(project.id)
+        .bind(manager.0.id)
+        .bind(ProjectRole::Admin)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (first, second, outsider) = (create_user(&pool, Role::User).await, create_user(&pool, Role::User).await, create_user(&pool, Role::User).await);
+
+    let post = |path: &str, token: &str, body: serde_json::Value| request(&Method::POST, path, Some(token)).set_json(body).to_request();
+    let election = format!("/proposals/{}/election", project.proposal);
+    let transport_key = serde_json::json!({ "transport_key": election::point_to_hex(&(RISTRETTO_BASEPOINT_POINT * Scalar::from(7u64))) });
+
+    // Trustees must be users
+    let token = access_token(&pool, &keyring, &manager).await;
+    let unknown = serde_json::json!({ "threshold": 1, "trustees": [first.0.id, Uuid::new_v4()] });
+    let (status, code) = call(&app, post(&format!("/proposals/{}/election", project.revocable), &token, unknown)).await;
+    assert_eq!((status, code.as_deref()), (StatusCode::NOT_FOUND, Some("USER_NOT_FOUND")));
+
+    let token = access_token(&pool, &keyring, &manager).await;
+    let setup = serde_json::json!({ "threshold": 1, "trustees": [first.0.id, second.0.id] });
+    assert_eq!(call(&app, post(&election, &token, setup)).await.0, StatusCode::CREATED);
+
+    let token = access_token(&pool, &keyring, &outsider).await;
+    let (status, code) = call(&app, post(&format!("{}/transport-key", election), &token, transport_key.clone())).await;
+    assert_eq!((status, code.as_deref()), (StatusCode::FORBIDDEN, Some("NOT_TRUSTEE")));
+
+    let token = access_token(&pool, &keyring, &first).await;
+    assert_eq!(call(&app, post(&format!("{}/transport-key", election), &token, transport_key.clone())).await.0, StatusCode::OK);
+    let token = access_token(&pool, &keyring, &first).await;
+    let (status, code) = call(&app, post(&format!("{}/transport-key", election), &token, transport_key)).await;
+    assert_eq!((status, code.as_deref()), (StatusCode::CONFLICT, Some("KEY_GENERATION_CONFLICT")));
+
+    // Nobody deals before every trustee has a transport key
+    let token = access_token(&pool, &keyring, &first).await;
+    let dealing = serde_json::json!({ "commitments": [], "proof": { "c": "", "z": "" }, "shares": [] });
+    let (status, code) = call(&app, post(&format!("{}/dealing", election), &token, dealing)).await;
+    assert_eq!((status, code.as_deref()), (StatusCode::CONFLICT, Some("KEY_GENERATION_CONFLICT")));
+
+    let token = access_token(&pool, &keyring, &outsider).await;
+    let (status, code) = call(&app, post(&format!("{}/shares", election), &token, serde_json::json!({ "shares": [] }))).await;
+    assert_eq!((status, code.as_deref()), (StatusCode::FORBIDDEN, Some("NOT_TRUSTEE")));
+}
@@ -1,12 +1,17 @@
 use actix_web::web;
 
-use crate::handlers::user_handlers;
+use crate::handlers::{user_handlers, wallet_handlers};
+use crate::permissions::Permission::*;
+use crate::routes::access::{delete, get, post, put, Access::*};
 
 pub fn user_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/users")
-            .route("", web::get().to(user_handlers::get_all_users))
-            .route("", web::post().to(user_handlers::register_user))
-            .route("/{wallet_address}/role", web::put().to(user_handlers::update_user_role)),
+            .service(get("", Permission(ManageUsers), user_handlers::get_all_users))
+            .service(post("", Permission(ManageUsers), user_handlers::register_user))
+            .service(get("/me/wallets", Authenticated, wallet_handlers::list_wallets))
+            .service(post("/me/wallets", Authenticated, wallet_handlers::link_wallet))
+            .service(delete("/me/wallets/{address}", Authenticated, wallet_handlers::unlink_wallet))
+            .service(put("/{user_id}/role", Permission(ManageUsers), user_handlers::update_user_role)),
     );
 }
@@ -0,0 +1,461 @@
+use serde::Serialize;
+
+// Ballot: A verified vote as the tally engine sees it
+#[derive(Debug, Clone)]
+pub struct Ballot {
+    pub weight: u64,         // Voting weight proven against the eligibility snapshot
+    pub ranking: Vec<usize>, // Indices into the proposal's choices, most preferred first
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChoiceTotal {
+    pub choice: String,
+    pub votes: u64,
+    pub raw_weight: u64,
+    pub weighted: f64,
+}
+
+// RunoffRound: One round of instant-runoff counting
+#[derive(Debug, Clone, Serialize)]
+pub struct RunoffRound {
+    pub round: usize,
+    pub counts: Vec<ChoiceTotal>, // Continuing choices only
+    pub exhausted: u64,           // Ballots with no continuing choice left
+    pub eliminated: Vec<String>,
+}
+
+// TallyResult: Stored as Tally.results_json. Raw weight totals saturate instead of overflowing; counted
+// ballots never reach the limit, since a snapshot's total weight fits in an i64.
+#[derive(Debug, Clone, Serialize)]
+pub struct TallyResult {
+    pub model: String,
+    pub totals: Vec<ChoiceTotal>,
+    pub total_votes: u64,
+    pub total_raw_weight: u64,
+    pub total_weighted: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rounds: Option<Vec<RunoffRound>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub winner: Option<String>,
+}
+
+/// A voting model, selected by `Proposal.model_enum`.
+pub trait TallyEngine {
+    fn model(&self) -> &'static str;
+
+    /// Voting power a ballot of the given raw weight contributes.
+    fn voting_power(&self, weight: u64) -> f64;
+
+    fn validate(&self, ballot: &Ballot, choice_count: usize) -> Result<(), String> {
+        match ballot.ranking.as_slice() {
+            [choice] if *choice < choice_count => Ok(()),
+            [_] => Err("Choice is out of range".to_string()),
+            _ => Err(format!("A {} ballot selects exactly one choice", self.model())),
+        }
+    }
+
+    fn tally(&self, choices: &[String], ballots: &[Ballot]) -> TallyResult {
+        let mut totals: Vec<ChoiceTotal> = choices
+            .iter()
+            .map(|c| ChoiceTotal { choice: c.clone(), votes: 0, raw_weight: 0, weighted: 0.0 })
+            .collect();
+
+        for ballot in ballots {
+            if let Some(total) = ballot.ranking.first().and_then(|&c| totals.get_mut(c)) {
+                total.votes += 1;
+                total.raw_weight = total.raw_weight.saturating_add(ballot.weight);
+                total.weighted += self.voting_power(ballot.weight);
+            }
+        }
+
+        TallyResult::from_totals(self.model(), totals)
+    }
+
+    /// Whether ballots can be encrypted and summed homomorphically under this model.
+    fn supports_encryption(&self) -> bool {
+        true
+    }
+}
+
+impl TallyResult {
+    /// Result of a single-round count, e.g. from decrypted homomorphic aggregates.
+    pub fn from_totals(model: &str, totals: Vec<ChoiceTotal>) -> Self {
+        TallyResult {
+            model: model.to_string(),
+            total_votes: totals.iter().map(|t| t.votes).sum(),
+            total_raw_weight: totals.iter().map(|t| t.raw_weight).fold(0, u64::saturating_add),
+            total_weighted: totals.iter().map(|t| t.weighted).sum(),
+            totals,
+            rounds: None,
+            winner: None,
+        }
+    }
+}
+
+pub struct TokenWeighted;
+
+impl TallyEngine for TokenWeighted {
+    fn model(&self) -> &'static str {
+        "token-weighted"
+    }
+
+    fn voting_power(&self, weight: u64) -> f64 {
+        weight as f64
+    }
+}
+
+pub struct Quadratic;
+
+impl TallyEngine for Quadratic {
+    fn model(&self) -> &'static str {
+        "quadratic"
+    }
+
+    fn voting_power(&self, weight: u64) -> f64 {
+        (weight as f64).sqrt()
+    }
+}
+
+pub struct OnePersonOneVote;
+
+impl TallyEngine for OnePersonOneVote {
+    fn model(&self) -> &'static str {
+        "one-person-one-vote"
+    }
+
+    fn voting_power(&self, _weight: u64) -> f64 {
+        1.0
+    }
+}
+
+// Instant-runoff: each round counts every ballot for its highest-ranked continuing choice
+// and eliminates the weakest choices until one holds a majority of the continuing ballots.
+pub struct RankedChoice;
+
+impl TallyEngine for RankedChoice {
+    fn model(&self) -> &'static str {
+        "ranked_choice"
+    }
+
+    fn voting_power(&self, _weight: u64) -> f64 {
+        1.0
+    }
+
+    // Elimination rounds need each ballot's full ranking, which a sum can't provide
+    fn supports_encryption(&self) -> bool {
+        false
+    }
+
+    fn validate(&self, ballot: &Ballot, choice_count: usize) -> Result<(), String> {
+        if ballot.ranking.is_empty() {
+            return Err("A ranked_choice ballot ranks at least one choice".to_string());
+        }
+        if ballot.ranking.iter().any(|&c| c >= choice_count) {
+            return Err("Choice is out of range".to_string());
+        }
+        let mut seen = vec![false; choice_count];
+        for &c in &ballot.ranking {
+            if std::mem::replace(&mut seen[c], true) {
+                return Err("A choice may only be ranked once".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn tally(&self, choices: &[String], ballots: &[Ballot]) -> TallyResult {
+        let mut continuing = vec![true; choices.len()];
+        let mut rounds = Vec::new();
+        let mut winner = None;
+        let mut counts = Vec::new();
+
+        while continuing.iter().any(|&c| c) {
+            counts = choices
+                .iter()
+                .map(|c| ChoiceTotal { choice: c.clone(), votes: 0, raw_weight: 0, weighted: 0.0 })
+                .collect::<Vec<_>>();
+            let mut exhausted = 0;
+
+            for ballot in ballots {
+                match ballot.ranking.iter().find(|&&c| continuing.get(c) == Some(&true)) {
+                    Some(&c) => {
+                        counts[c].votes += 1;
+                        counts[c].raw_weight = counts[c].raw_weight.saturating_add(ballot.weight);
+                        counts[c].weighted += self.voting_power(ballot.weight);
+                    }
+                    None => exhausted += 1,
+                }
+            }
+
+            let active: Vec<usize> = (0..choices.len()).filter(|&c| continuing[c]).collect();
+            let active_votes: f64 = active.iter().map(|&c| counts[c].weighted).sum();
+            let leader = active
+                .iter()
+                .copied()
+                .max_by(|&a, &b| counts[a].weighted.total_cmp(&counts[b].weighted));
+
+            let mut eliminated = Vec::new();
+            match leader {
+                Some(l) if counts[l].weighted * 2.0 > active_votes || active.len() == 1 => {
+                    winner = Some(choices[l].clone());
+                }
+                _ => {
+                    // Eliminate every choice tied for last place; if that is all of them the race is tied
+                    let lowest = active.iter().map(|&c| counts[c].weighted).fold(f64::INFINITY, f64::min);
+                    let losers: Vec<usize> = active.iter().copied().filter(|&c| counts[c].weighted == lowest).collect();
+                    if losers.len() < active.len() {
+                        for &c in &losers {
+                            continuing[c] = false;
+                            eliminated.push(choices[c].clone());
+                        }
+                    }
+                }
+            }
+
+            let done = winner.is_some() || eliminated.is_empty();
+            rounds.push(RunoffRound {
+                round: rounds.len() + 1,
+                counts: active.iter().map(|&c| counts[c].clone()).collect(),
+                exhausted,
+                eliminated,
+            });
+            if done {
+                break;
+            }
+        }
+
+        TallyResult {
+            model: self.model().to_string(),
+            total_votes: ballots.len() as u64,
+            total_raw_weight: ballots.iter().map(|b| b.weight).fold(0, u64::saturating_add),
+            total_weighted: ballots.iter().map(|b| self.voting_power(b.weight)).sum(),
+            totals: counts,
+            rounds: Some(rounds),
+            winner,
+        }
+    }
+}
+
+pub fn engine_for(model_enum: &str) -> Option<Box<dyn TallyEngine>> {
+    match model_enum {
+        "token-weighted" => Some(Box::new(TokenWeighted)),
+        "quadratic" => Some(Box::new(Quadratic)),
+        "one-person-one-vote" => Some(Box::new(OnePersonOneVote)),
+        "ranked_choice" => Some(Box::new(RankedChoice)),
+        _ => None,
+    }
+}
+
+// QuorumReport: Stored as Tally.quorum_json so the quorum decision can be audited
+#[derive(Debug, Clone, Serialize)]
+pub struct QuorumReport {
+    pub basis: String,  // "voters" or "weight"
+    pub eligible: u64,  // Denominator taken from the project's eligibility snapshot
+    pub turnout: u64,
+    pub turnout_percentage: f64,
+    pub threshold_percentage: f64,
+    pub reached: bool,
+}
+
+pub fn compute_quorum(
+    basis: &str,
+    threshold_percentage: f64,
+    eligible_voters: u64,
+    eligible_weight: u64,
+    ballots: &[Ballot],
+) -> Option<QuorumReport> {
+    let (eligible, turnout) = match basis {
+        "voters" => (eligible_voters, ballots.len() as u64),
+        "weight" => (eligible_weight, ballots.iter().map(|b| b.weight).fold(0, u64::saturating_add)),
+        _ => return None,
+    };
+
+    let turnout_percentage = if eligible == 0 {
+        0.0
+    } else {
+        turnout as f64 / eligible as f64 * 100.0
+    };
+
+    Some(QuorumReport {
+        basis: basis.to_string(),
+        eligible,
+        turnout,
+        turnout_percentage,
+        threshold_percentage,
+        reached: eligible > 0 && turnout_percentage >= threshold_percentage,
+    })
+}
+
+/// Choices of a proposal; `choices_json` is a JSON array of labels.
+pub fn parse_choices(choices_json: &serde_json::Value) -> Option<Vec<String>> {
+    serde_json::from_value::<Vec<String>>(choices_json.clone())
+        .ok()
+        .filter(|c| !c.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn choices(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|l| l.to_string()).collect()
+    }
+
+    fn ballot(weight: u64, ranking: &[usize]) -> Ballot {
+        Ballot { weight, ranking: ranking.to_vec() }
+    }
+
+    #[test]
+    fn token_weighted_counts_raw_weight() {
+        let ballots = [ballot(10, &[0]), ballot(3, &[1]), ballot(4, &[1])];
+        let result = TokenWeighted.tally(&choices(&["yes", "no"]), &ballots);
+
+        assert_eq!(result.totals[0].votes, 1);
+        assert_eq!(result.totals[0].weighted, 10.0);
+        assert_eq!(result.totals[1].votes, 2);
+        assert_eq!(result.totals[1].raw_weight, 7);
+        assert_eq!(result.totals[1].weighted, 7.0);
+        assert_eq!((result.total_votes, result.total_raw_weight, result.total_weighted), (3, 17, 17.0));
+        assert!(result.rounds.is_none() && result.winner.is_none());
+    }
+
+    #[test]
+    fn quadratic_weights_each_ballot_by_its_square_root() {
+        // One holder of 16 is outvoted by two holders of 9: 4 < 3 + 3
+        let ballots = [ballot(16, &[0]), ballot(9, &[1]), ballot(9, &[1]), ballot(0, &[0])];
+        let result = Quadratic.tally(&choices(&["yes", "no"]), &ballots);
+
+        assert_eq!(result.totals[0].raw_weight, 16);
+        assert_eq!(result.totals[0].weighted, 4.0);
+        assert_eq!(result.totals[1].raw_weight, 18);
+        assert_eq!(result.totals[1].weighted, 6.0);
+        assert_eq!(result.total_weighted, 10.0);
+        assert_eq!(Quadratic.voting_power(2), 2f64.sqrt());
+    }
+
+    #[test]
+    fn raw_weight_totals_saturate_instead_of_overflowing() {
+        let ballots = [ballot(u64::MAX, &[0]), ballot(u64::MAX, &[0]), ballot(1, &[1])];
+        let result = TokenWeighted.tally(&choices(&["yes", "no"]), &ballots);
+        assert_eq!(result.totals[0].raw_weight, u64::MAX);
+        assert_eq!(result.total_raw_weight, u64::MAX);
+
+        let result = RankedChoice.tally(&choices(&["yes", "no"]), &ballots);
+        assert_eq!(result.totals[0].raw_weight, u64::MAX);
+        assert_eq!(result.total_raw_weight, u64::MAX);
+    }
+
+    #[test]
+    fn single_choice_models_accept_exactly_one_choice_in_range() {
+        for engine in [engine_for("token-weighted"), engine_for("quadratic"), engine_for("one-person-one-vote")] {
+            let engine = engine.unwrap();
+            assert!(engine.validate(&ballot(1, &[1]), 2).is_ok());
+            assert!(engine.validate(&ballot(1, &[2]), 2).is_err());
+            assert!(engine.validate(&ballot(1, &[]), 2).is_err());
+            assert!(engine.validate(&ballot(1, &[0, 1]), 2).is_err());
+        }
+    }
+
+    fn eliminated(result: &TallyResult) -> Vec<Vec<String>> {
+        result.rounds.as_ref().unwrap().iter().map(|r| r.eliminated.clone()).collect()
+    }
+
+    #[test]
+    fn ranked_choice_transfers_eliminated_ballots() {
+        // A leads the first round without a majority; C's ballots transfer to B, who then wins
+        let mut ballots = vec![ballot(1, &[0]); 4];
+        ballots.extend(vec![ballot(1, &[1, 2]); 3]);
+        ballots.extend(vec![ballot(1, &[2, 1]); 2]);
+        let result = RankedChoice.tally(&choices(&["A", "B", "C"]), &ballots);
+
+        assert_eq!(eliminated(&result), vec![vec!["C".to_string()], vec![]]);
+        let last = result.rounds.as_ref().unwrap().last().unwrap();
+        assert_eq!(last.counts.iter().map(|c| c.votes).collect::<Vec<_>>(), vec![4, 5]);
+        assert_eq!(result.winner.as_deref(), Some("B"));
+    }
+
+    #[test]
+    fn ranked_choice_eliminates_every_choice_tied_for_last() {
+        let mut ballots = vec![ballot(1, &[0]); 2];
+        ballots.extend(vec![ballot(1, &[3]); 2]);
+        ballots.push(ballot(1, &[1, 0]));
+        ballots.push(ballot(1, &[2, 0]));
+        let result = RankedChoice.tally(&choices(&["A", "B", "C", "D"]), &ballots);
+
+        assert_eq!(eliminated(&result), vec![vec!["B".to_string(), "C".to_string()], vec![]]);
+        assert_eq!(result.winner.as_deref(), Some("A"));
+    }
+
+    #[test]
+    fn ranked_choice_measures_the_majority_against_continuing_ballots() {
+        // Once C is out its ballot is exhausted, so A's 3 of the 5 continuing ballots is a majority
+        let mut ballots = vec![ballot(1, &[0]); 3];
+        ballots.extend(vec![ballot(1, &[1]); 2]);
+        ballots.push(ballot(1, &[2]));
+        let result = RankedChoice.tally(&choices(&["A", "B", "C"]), &ballots);
+
+        let rounds = result.rounds.as_ref().unwrap();
+        assert_eq!(rounds.iter().map(|r| r.exhausted).collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(result.winner.as_deref(), Some("A"));
+        assert_eq!(result.total_votes, 6);
+    }
+
+    #[test]
+    fn ranked_choice_has_no_winner_when_every_choice_ties() {
+        let ballots = [ballot(1, &[0, 1]), ballot(1, &[1, 0]), ballot(1, &[2])];
+        let result = RankedChoice.tally(&choices(&["A", "B", "C"]), &ballots);
+
+        assert_eq!(eliminated(&result), vec![Vec::<String>::new()]);
+        assert!(result.winner.is_none());
+    }
+
+    #[test]
+    fn ranked_choice_counts_one_vote_per_ballot_whatever_its_weight() {
+        let ballots = [ballot(100, &[0]), ballot(1, &[1]), ballot(1, &[1])];
+        let result = RankedChoice.tally(&choices(&["A", "B"]), &ballots);
+
+        assert_eq!(result.winner.as_deref(), Some("B"));
+        assert_eq!(result.total_raw_weight, 102);
+        assert_eq!(result.total_weighted, 3.0);
+    }
+
+    #[test]
+    fn ranked_choice_ballots_rank_each_choice_at_most_once() {
+        assert!(RankedChoice.validate(&ballot(1, &[2, 0]), 3).is_ok());
+        assert!(RankedChoice.validate(&ballot(1, &[]), 3).is_err());
+        assert!(RankedChoice.validate(&ballot(1, &[0, 3]), 3).is_err());
+        assert!(RankedChoice.validate(&ballot(1, &[1, 0, 1]), 3).is_err());
+    }
+
+    #[test]
+    fn voter_quorum_is_reached_at_the_threshold_and_not_below() {
+        let reached = compute_quorum("voters", 50.0, 4, 1000, &[ballot(1, &[0]), ballot(1, &[1])]).unwrap();
+        assert_eq!((reached.eligible, reached.turnout, reached.turnout_percentage), (4, 2, 50.0));
+        assert!(reached.reached);
+
+        let short = compute_quorum("voters", 50.0, 4, 1000, &[ballot(999, &[0])]).unwrap();
+        assert_eq!(short.turnout_percentage, 25.0);
+        assert!(!short.reached);
+    }
+
+    #[test]
+    fn weight_quorum_is_reached_at_the_threshold_and_not_below() {
+        let reached = compute_quorum("weight", 50.0, 2, 10, &[ballot(3, &[0]), ballot(2, &[1])]).unwrap();
+        assert_eq!((reached.eligible, reached.turnout, reached.turnout_percentage), (10, 5, 50.0));
+        assert!(reached.reached);
+
+        // Both eligible voters turned out, but with less than half the weight
+        let short = compute_quorum("weight", 50.0, 2, 10, &[ballot(3, &[0]), ballot(1, &[1])]).unwrap();
+        assert_eq!(short.turnout_percentage, 40.0);
+        assert!(!short.reached);
+    }
+
+    #[test]
+    fn quorum_needs_an_eligible_set_and_a_known_basis() {
+        let empty = compute_quorum("voters", 0.0, 0, 0, &[]).unwrap();
+        assert_eq!(empty.turnout_percentage, 0.0);
+        assert!(!empty.reached);
+
+        assert!(compute_quorum("voters", 0.0, 3, 0, &[]).unwrap().reached);
+        assert!(compute_quorum("turnout", 50.0, 4, 10, &[]).is_none());
+    }
+}
@@ -0,0 +1,216 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use rand::RngCore;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, PgPool};
+use std::time::Duration as StdDuration;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::keyring::Keyring;
+use crate::models::{RefreshToken, User};
+use crate::Claims;
+
+// Access tokens are short-lived; sessions are kept alive by rotating refresh tokens
+const ACCESS_TOKEN_TTL_MINUTES: i64 = 15;
+const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+
+// How often expired denylist entries, refresh tokens and login challenges are deleted
+const PURGE_INTERVAL_SECS: u64 = 3600;
+
+#[derive(Debug, Serialize)]
+pub struct TokenPair {
+    pub token: String, // Access token
+    pub expires_at: NaiveDateTime,
+    pub refresh_token: String,
+    pub refresh_expires_at: NaiveDateTime,
+}
+
+// Refresh tokens are only stored hashed, so a database leak doesn't leak live sessions
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn access_token(keyring: &Keyring, user: &User, wallet_address: &str) -> Result<(String, NaiveDateTime), ApiError> {
+    let now = Utc::now();
+    let expires_at = now + Duration::minutes(ACCESS_TOKEN_TTL_MINUTES);
+    let claims = Claims {
+        user_id: user.id,
+        wallet_address: wallet_address.to_string(),
+        role: user.role,
+        ver: user.token_version,
+        jti: Uuid::new_v4(),
+        iat: now.timestamp() as usize,
+        exp: expires_at.timestamp() as usize,
+    };
+
+    Ok((keyring.sign(&claims)?, expires_at.naive_utc()))
+}
+
+async fn issue_in_family(
+    conn: &mut PgConnection,
+    keyring: &Keyring,
+    user: &User,
+    wallet_address: &str,
+    family_id: Uuid,
+) -> Result<TokenPair, ApiError> {
+    let (token, expires_at) = access_token(keyring, user, wallet_address)?;
+
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let refresh_token = hex::encode(bytes);
+
+    let now = Utc::now();
+    let stored = sqlx::query_as::<_, RefreshToken>(
+        "INSERT INTO refresh_tokens (id, family_id, user_id, wallet_address, token_hash, expires_at, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *"
+    )
+    .bind(Uuid::new_v4())
+    .bind(family_id)
+    .bind(user.id)
+    .bind(wallet_address)
+    .bind(hash_token(&refresh_token))
+    .bind((now + Duration::days(REFRESH_TOKEN_TTL_DAYS)).naive_utc())
+    .bind(now.naive_utc())
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(TokenPair {
+        token,
+        expires_at,
+        refresh_token,
+        refresh_expires_at: stored.expires_at,
+    })
+}
+
+/// Issues an access token for `user` signed in with `wallet_address` and starts a new refresh
+/// token family, e.g. on login.
+pub async fn issue(conn: &mut PgConnection, keyring: &Keyring, user: &User, wallet_address: &str) -> Result<TokenPair, ApiError> {
+    issue_in_family(conn, keyring, user, wallet_address, Uuid::new_v4()).await
+}
+
+/// Exchanges a refresh token for a new pair. Refresh tokens are single use: presenting one that
+/// was already rotated means it was copied, so the whole family is revoked.
+pub async fn rotate(conn: &mut PgConnection, keyring: &Keyring, refresh_token: &str) -> Result<TokenPair, ApiError> {
+    let now = Utc::now().naive_utc();
+    let token_hash = hash_token(refresh_token);
+
+    let current = sqlx::query_as::<_, RefreshToken>(
+        "UPDATE refresh_tokens SET revoked_at = $1 WHERE token_hash = $2 AND revoked_at IS NULL AND expires_at > $1 RETURNING *"
+    )
+    .bind(now)
+    .bind(&token_hash)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let current = match current {
+        Some(t) => t,
+        None => {
+            sqlx::query("UPDATE refresh_tokens SET revoked_at = $1 WHERE revoked_at IS NULL AND family_id IN (SELECT family_id FROM refresh_tokens WHERE token_hash = $2)")
+                .bind(now)
+                .bind(&token_hash)
+                .execute(&mut *conn)
+                .await?;
+            return Err(ApiError::InvalidRefreshToken);
+        }
+    };
+
+    // Re-read the user so the new access token carries their current role
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(current.user_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(ApiError::InvalidRefreshToken)?;
+
+    issue_in_family(conn, keyring, &user, &current.wallet_address, current.family_id).await
+}
+
+/// Revokes the refresh token family `refresh_token` belongs to, if it belongs to `user_id`.
+pub async fn revoke_family(conn: &mut PgConnection, refresh_token: &str, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE refresh_tokens SET revoked_at = $1 WHERE revoked_at IS NULL AND family_id IN (SELECT family_id FROM refresh_tokens WHERE token_hash = $2 AND user_id = $3)")
+        .bind(Utc::now().naive_utc())
+        .bind(hash_token(refresh_token))
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Invalidates every outstanding token of a user, whichever wallet it was issued to: refresh
+/// tokens are revoked and access tokens stop matching the bumped token_version.
+pub async fn revoke_all(conn: &mut PgConnection, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE users SET token_version = token_version + 1 WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query("UPDATE refresh_tokens SET revoked_at = $1 WHERE user_id = $2 AND revoked_at IS NULL")
+        .bind(Utc::now().naive_utc())
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Denylists a single access token until it would have expired anyway.
+pub async fn deny(conn: &mut PgConnection, jti: Uuid, exp: usize) -> Result<(), sqlx::Error> {
+    let expires_at = chrono::DateTime::from_timestamp(exp as i64, 0)
+        .map(|t| t.naive_utc())
+        .unwrap_or_else(|| Utc::now().naive_utc());
+
+    sqlx::query("INSERT INTO revoked_tokens (jti, expires_at) VALUES ($1, $2) ON CONFLICT (jti) DO NOTHING")
+        .bind(jti)
+        .bind(expires_at)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Checks a decoded access token was neither logged out nor invalidated by `revoke_all`, and that
+/// the wallet it was issued to is still linked to the user.
+pub async fn check_access(pool: &PgPool, claims: &Claims) -> Result<(), ApiError> {
+    let version = sqlx::query_scalar::<_, i32>(
+        "SELECT u.token_version FROM users u JOIN wallets w ON w.user_id = u.id WHERE u.id = $1 AND w.address = $2 AND NOT EXISTS (SELECT 1 FROM revoked_tokens WHERE jti = $3)"
+    )
+    .bind(claims.user_id)
+    .bind(&claims.wallet_address)
+    .bind(claims.jti)
+    .fetch_optional(pool)
+    .await?;
+
+    match version {
+        Some(v) if v == claims.ver => Ok(()),
+        _ => Err(ApiError::TokenRevoked),
+    }
+}
+
+pub async fn purge_expired(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let now = Utc::now().naive_utc();
+
+    sqlx::query("DELETE FROM revoked_tokens WHERE expires_at <= $1")
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+    sqlx::query("DELETE FROM refresh_tokens WHERE expires_at <= $1")
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+    // A consumed challenge can't be answered again, so it goes as soon as it is used
+    sqlx::query("DELETE FROM auth_challenges WHERE expires_at <= $1 OR consumed_at IS NOT NULL")
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn run_purger(pool: PgPool) {
+    let mut interval = actix_web::rt::time::interval(StdDuration::from_secs(PURGE_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        if let Err(e) = purge_expired(&pool).await {
+            log::error!("Failed to purge expired tokens and challenges: {}", e);
+        }
+    }
+}
@@ -1,4 +1,230 @@
-pub fn verify_proof(_proof_hash: &str) -> bool {
-    // For now, always return true. Later integrate Miden proof system.
-    true
+use miden_crypto::dsa::rpo_falcon512::{PublicKey, Signature};
+use miden_crypto::hash::rpo::{Rpo256, RpoDigest};
+use miden_crypto::utils::Deserializable;
+use miden_crypto::{Felt, Word};
+use miden_verifier::{verify, Digest, ExecutionProof, Kernel, ProgramInfo, StackInputs, StackOutputs};
+use serde::Serialize;
+use sha2::{Digest as _, Sha256};
+use uuid::Uuid;
+
+use crate::election::POWER_SCALE;
+use crate::tally::TallyResult;
+
+// Proofs generated with a weaker configuration than this are rejected even if they verify.
+pub const MIN_SECURITY_LEVEL: u32 = 96;
+
+// Ballot proofs larger than this are refused before they are decoded or verified.
+pub const MAX_PROOF_BYTES: usize = 256 * 1024;
+
+// ProgramProof: Everything needed to check a STARK proof of the vote-casting or tally program
+pub struct ProgramProof<'a> {
+    pub proof_bytes: &'a [u8],
+    pub program_hash: &'a str,
+    pub public_inputs: &'a [u64],
+    pub stack_outputs: &'a [u64],
+}
+
+// VotePublicInputs: The public inputs of the vote program, in stack order
+#[derive(Debug, Clone, PartialEq)]
+pub struct VotePublicInputs {
+    pub merkle_root: [u64; 4],       // Eligibility root the voter proved membership against
+    pub ballot_commitment: [u64; 4], // RPO hash of the encrypted ballot, binding it to the proof
+    pub proposal_id: [u64; 4],       // Proposal the ballot is cast on, see `proposal_id_word`
+    pub nullifier: [u64; 4],         // RPO(voter_secret || proposal_id), derived inside the program
+}
+
+impl VotePublicInputs {
+    pub const LEN: usize = 16;
+
+    pub fn parse(public_inputs: &[u64]) -> Option<Self> {
+        if public_inputs.len() < Self::LEN {
+            return None;
+        }
+        let word = |i: usize| [public_inputs[i], public_inputs[i + 1], public_inputs[i + 2], public_inputs[i + 3]];
+        Some(VotePublicInputs {
+            merkle_root: word(0),
+            ballot_commitment: word(4),
+            proposal_id: word(8),
+            nullifier: word(12),
+        })
+    }
+}
+
+/// A proposal id as the vote program takes it: its 128 bits as four big-endian 32-bit limbs.
+pub fn proposal_id_word(proposal_id: &Uuid) -> [u64; 4] {
+    let b = proposal_id.as_bytes();
+    let limb = |i: usize| u32::from_be_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]) as u64;
+    [limb(0), limb(4), limb(8), limb(12)]
+}
+
+// TallyPublicInputs: The public inputs of the tally program, in stack order. The server builds them
+// from the bulletin board root it is tallying, so a proof over any other set of ballots fails.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TallyPublicInputs {
+    pub bulletin_root: [u64; 8], // SHA-256 bulletin board root as eight big-endian 32-bit limbs
+    pub proposal_id: [u64; 4],   // See `proposal_id_word`
+    pub tree_size: u64,          // Ballots logged under `bulletin_root`
+}
+
+impl TallyPublicInputs {
+    pub const LEN: usize = 13;
+
+    pub fn new(proposal_id: &Uuid, bulletin_root: &[u8; 32], tree_size: u64) -> Self {
+        let limb = |i: usize| u32::from_be_bytes(bulletin_root[i * 4..i * 4 + 4].try_into().unwrap()) as u64;
+        TallyPublicInputs {
+            bulletin_root: std::array::from_fn(limb),
+            proposal_id: proposal_id_word(proposal_id),
+            tree_size,
+        }
+    }
+
+    pub fn to_ints(&self) -> Vec<u64> {
+        let mut ints = Vec::with_capacity(Self::LEN);
+        ints.extend(self.bulletin_root);
+        ints.extend(self.proposal_id);
+        ints.push(self.tree_size);
+        ints
+    }
+}
+
+/// Stack outputs of the tally program: an RPO commitment to the results, over
+/// [choice count, (votes, raw weight, weighted * POWER_SCALE) per choice, winner index + 1 or 0].
+pub fn results_commitment(result: &TallyResult) -> [u64; 4] {
+    let mut elements = vec![Felt::new(result.totals.len() as u64)];
+    for total in &result.totals {
+        elements.push(Felt::new(total.votes));
+        elements.push(Felt::new(total.raw_weight));
+        elements.push(Felt::new((total.weighted * POWER_SCALE).round() as u64));
+    }
+    let winner = result
+        .winner
+        .as_ref()
+        .and_then(|w| result.totals.iter().position(|t| &t.choice == w))
+        .map_or(0, |i| i as u64 + 1);
+    elements.push(Felt::new(winner));
+
+    crate::merkle::digest_to_ints(&Rpo256::hash_elements(&elements))
+}
+
+// VoteOutputs: The stack outputs of the vote program, [weight, k, choice_1, ..., choice_k]
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoteOutputs {
+    pub weight: u64,       // Weight of the voter's eligibility leaf
+    pub choices: Vec<u64>, // Choice indices, most preferred first
+}
+
+impl VoteOutputs {
+    pub fn parse(stack_outputs: &[u64]) -> Option<Self> {
+        let weight = *stack_outputs.first()?;
+        let count = usize::try_from(*stack_outputs.get(1)?).ok()?;
+        let choices = stack_outputs.get(2..2usize.checked_add(count)?)?.to_vec();
+        Some(VoteOutputs { weight, choices })
+    }
+}
+
+// VerificationResult: What submit_vote persists alongside the ballot
+#[derive(Debug, Clone, Serialize)]
+pub struct VerificationResult {
+    pub verified: bool,
+    pub failure_reason: Option<String>,
+    pub security_level: Option<u32>,
+}
+
+impl VerificationResult {
+    fn rejected(reason: impl Into<String>) -> Self {
+        VerificationResult {
+            verified: false,
+            failure_reason: Some(reason.into()),
+            security_level: None,
+        }
+    }
+}
+
+// ProgramHashes: The programs proofs must be generated for, read and validated once at startup.
+//   VOTE_PROGRAM_HASH=<hex digest>    the vote-casting program every ballot proof runs
+//   TALLY_PROGRAM_HASH=<hex digest>   the tally program every aggregate proof runs
+#[derive(Debug, Clone)]
+pub struct ProgramHashes {
+    pub vote: String,
+    pub tally: String,
+}
+
+impl ProgramHashes {
+    pub fn from_env() -> Result<Self, String> {
+        Ok(ProgramHashes {
+            vote: program_hash_var("VOTE_PROGRAM_HASH")?,
+            tally: program_hash_var("TALLY_PROGRAM_HASH")?,
+        })
+    }
+}
+
+fn program_hash_var(name: &str) -> Result<String, String> {
+    let hash = std::env::var(name).map_err(|_| format!("{} must be set", name))?;
+    Digest::try_from(hash.as_str()).map_err(|e| format!("{} is not a valid program hash: {}", name, e))?;
+    Ok(hash)
+}
+
+/// Content address of a serialized proof, stored as `submissions.proof_hash`.
+pub fn proof_hash(proof_bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(proof_bytes))
+}
+
+pub fn verify_proof(proof: &ProgramProof) -> VerificationResult {
+    let program_hash = match Digest::try_from(proof.program_hash) {
+        Ok(h) => h,
+        Err(e) => return VerificationResult::rejected(format!("Invalid program hash: {}", e)),
+    };
+
+    let stack_inputs = match StackInputs::try_from_ints(proof.public_inputs.iter().copied()) {
+        Ok(i) => i,
+        Err(e) => return VerificationResult::rejected(format!("Invalid public inputs: {}", e)),
+    };
+
+    let stack_outputs = match StackOutputs::try_from_ints(proof.stack_outputs.iter().copied()) {
+        Ok(o) => o,
+        Err(e) => return VerificationResult::rejected(format!("Invalid stack outputs: {}", e)),
+    };
+
+    let execution_proof = match ExecutionProof::from_bytes(proof.proof_bytes) {
+        Ok(p) => p,
+        Err(e) => return VerificationResult::rejected(format!("Malformed proof: {}", e)),
+    };
+
+    let program_info = ProgramInfo::new(program_hash, Kernel::default());
+
+    match verify(program_info, stack_inputs, stack_outputs, execution_proof) {
+        Ok(security_level) if security_level < MIN_SECURITY_LEVEL => VerificationResult {
+            verified: false,
+            failure_reason: Some(format!(
+                "Proof security level {} is below the required {} bits",
+                security_level, MIN_SECURITY_LEVEL
+            )),
+            security_level: Some(security_level),
+        },
+        Ok(security_level) => VerificationResult {
+            verified: true,
+            failure_reason: None,
+            security_level: Some(security_level),
+        },
+        Err(e) => VerificationResult::rejected(format!("Proof verification failed: {}", e)),
+    }
+}
+
+/// The exact text a wallet signs to log in; binding the address stops a signature being replayed for another wallet.
+pub fn challenge_message(wallet_address: &str, nonce: &str) -> String {
+    format!("Sign in to Miden Voting\nWallet: {}\nNonce: {}", wallet_address, nonce)
+}
+
+/// Checks an RPO Falcon512 signature made by a Miden account key over `message`.
+pub fn verify_wallet_signature(public_key: &str, message: &str, signature: &str) -> Result<(), String> {
+    let public_key = RpoDigest::try_from(public_key).map_err(|e| format!("Invalid public key: {}", e))?;
+    let signature_bytes = hex::decode(signature.trim_start_matches("0x")).map_err(|_| "Signature must be hex-encoded".to_string())?;
+    let signature = Signature::read_from_bytes(&signature_bytes).map_err(|e| format!("Malformed signature: {}", e))?;
+
+    let message: Word = Rpo256::hash(message.as_bytes()).into();
+    if PublicKey::new(public_key.into()).verify(message, &signature) {
+        Ok(())
+    } else {
+        Err("Invalid signature".to_string())
+    }
 }
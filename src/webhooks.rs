@@ -0,0 +1,348 @@
+use actix_web::http::{header, Uri};
+use chrono::{NaiveDateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sqlx::{PgConnection, PgPool};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::lifecycle::ProposalState;
+use crate::models::{Webhook, WebhookDelivery};
+
+// Outbound webhooks. Events are queued as webhook_deliveries rows in the transaction that raises them,
+// then POSTed by `run_dispatcher`, which retries failures with exponential backoff. Each request carries
+//   X-Webhook-Event      the event name
+//   X-Webhook-Delivery   the delivery id
+//   X-Webhook-Timestamp  Unix seconds when the request was signed
+//   X-Webhook-Signature  sha256=<hex HMAC-SHA256 over "{timestamp}.{body}" keyed with the webhook secret>
+// Receivers should reject stale timestamps and dedupe on the payload's `id`, which redeliveries keep.
+
+const DISPATCH_INTERVAL_SECS: u64 = 10;
+const DISPATCH_BATCH: i64 = 20;
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+// Deliveries are marked failed after this many attempts
+const MAX_ATTEMPTS: i32 = 8;
+// Retry n waits RETRY_BASE_SECS * 2^(n-1), at most RETRY_MAX_SECS
+const RETRY_BASE_SECS: i64 = 30;
+const RETRY_MAX_SECS: i64 = 3600;
+
+// WebhookPolicy: Where webhooks may point. URLs must resolve only to public addresses, checked when a
+// webhook is created and again before every attempt, so a project admin can't make the server call
+// into its own network.
+//   WEBHOOK_ALLOWED_HOSTS=<host>,...   optional, hosts exempt from the check, e.g. an internal receiver
+#[derive(Debug, Clone, Default)]
+pub struct WebhookPolicy {
+    allowed_hosts: Vec<String>,
+}
+
+impl WebhookPolicy {
+    pub fn from_env() -> Self {
+        let hosts = std::env::var("WEBHOOK_ALLOWED_HOSTS").unwrap_or_default();
+        WebhookPolicy {
+            allowed_hosts: hosts.split(',').map(|h| h.trim().to_ascii_lowercase()).filter(|h| !h.is_empty()).collect(),
+        }
+    }
+
+    /// Checks `url` is an absolute http(s) URL whose host is allowed or resolves only to public addresses.
+    pub async fn check(&self, url: &str) -> Result<(), String> {
+        let uri = url.parse::<Uri>().map_err(|_| "Invalid webhook URL".to_string())?;
+        let host = match (uri.scheme_str(), uri.host()) {
+            (Some("http" | "https"), Some(host)) => host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase(),
+            _ => return Err("Webhook URL must be an absolute http(s) URL".to_string()),
+        };
+        if self.allowed_hosts.contains(&host) {
+            return Ok(());
+        }
+
+        let port = uri.port_u16().unwrap_or(if uri.scheme_str() == Some("https") { 443 } else { 80 });
+        let addresses: Vec<IpAddr> = tokio::net::lookup_host((host.as_str(), port))
+            .await
+            .map_err(|_| format!("Webhook host {} does not resolve", host))?
+            .map(|a| a.ip())
+            .collect();
+        if addresses.is_empty() {
+            return Err(format!("Webhook host {} does not resolve", host));
+        }
+        match addresses.iter().find(|ip| !is_public(ip)) {
+            Some(ip) => Err(format!("Webhook host {} resolves to non-public address {}", host, ip)),
+            None => Ok(()),
+        }
+    }
+}
+
+// Whether an address is globally routable: not loopback, private, link-local, shared, multicast or reserved
+fn is_public(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public_v4(&v4),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: &Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b)) // Shared address space (carrier-grade NAT)
+        || (a == 192 && b == 0 && c == 0) // IETF protocol assignments
+        || (a == 198 && (18..20).contains(&b)) // Benchmarking
+        || a >= 240) // Reserved
+}
+
+fn is_public_v6(ip: &Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || (first & 0xfe00) == 0xfc00 // Unique local
+        || (first & 0xffc0) == 0xfe80 // Link-local
+        || (first == 0x2001 && ip.segments()[1] == 0xdb8) // Documentation
+        || (first == 0x64 && ip.segments()[1] == 0xff9b)) // NAT64, which can reach private IPv4
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    #[serde(rename = "proposal.created")]
+    Created,
+    #[serde(rename = "proposal.closed")]
+    Closed,
+    #[serde(rename = "proposal.tallied")]
+    Tallied,
+    #[serde(rename = "proposal.finalized")]
+    Finalized,
+    #[serde(rename = "proposal.failed")]
+    Failed,
+    #[serde(rename = "proposal.revoked")]
+    Revoked,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::Created => "proposal.created",
+            WebhookEvent::Closed => "proposal.closed",
+            WebhookEvent::Tallied => "proposal.tallied",
+            WebhookEvent::Finalized => "proposal.finalized",
+            WebhookEvent::Failed => "proposal.failed",
+            WebhookEvent::Revoked => "proposal.revoked",
+        }
+    }
+
+    /// The event raised when a proposal enters `state`, if any.
+    pub fn for_state(state: ProposalState) -> Option<WebhookEvent> {
+        match state {
+            ProposalState::Closed => Some(WebhookEvent::Closed),
+            ProposalState::Tallied => Some(WebhookEvent::Tallied),
+            ProposalState::Finalized => Some(WebhookEvent::Finalized),
+            ProposalState::Failed => Some(WebhookEvent::Failed),
+            ProposalState::Revoked => Some(WebhookEvent::Revoked),
+            _ => None,
+        }
+    }
+}
+
+/// Queues `event` for every webhook of `project_id` subscribed to it. `data` becomes the payload's
+/// `data` field. Call it in the transaction making the change, so only events that happened are sent.
+pub async fn enqueue(conn: &mut PgConnection, project_id: Uuid, event: WebhookEvent, data: serde_json::Value) -> Result<(), sqlx::Error> {
+    let event_id = Uuid::new_v4();
+    let now = Utc::now().naive_utc();
+    let payload = serde_json::json!({
+        "id": event_id,
+        "event": event.as_str(),
+        "project_id": project_id,
+        "occurred_at": now,
+        "data": data,
+    });
+
+    sqlx::query(
+        "INSERT INTO webhook_deliveries (id, webhook_id, event_id, event, payload, status, attempts, next_attempt_at, created_at) \
+         SELECT gen_random_uuid(), id, $1, $2, $3, 'pending', 0, $4, $4 FROM webhooks WHERE project_id = $5 AND $2 = ANY(events)"
+    )
+    .bind(event_id)
+    .bind(event.as_str())
+    .bind(payload)
+    .bind(now)
+    .bind(project_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Queues another attempt of a delivery with the same event and payload, whatever the original's status.
+pub async fn redeliver(conn: &mut PgConnection, delivery: &WebhookDelivery) -> Result<WebhookDelivery, sqlx::Error> {
+    let now = Utc::now().naive_utc();
+    sqlx::query_as::<_, WebhookDelivery>(
+        "INSERT INTO webhook_deliveries (id, webhook_id, event_id, event, payload, status, attempts, next_attempt_at, redelivery_of, created_at) \
+         VALUES ($1, $2, $3, $4, $5, 'pending', 0, $6, $7, $6) RETURNING *"
+    )
+    .bind(Uuid::new_v4())
+    .bind(delivery.webhook_id)
+    .bind(delivery.event_id)
+    .bind(&delivery.event)
+    .bind(&delivery.payload)
+    .bind(now)
+    .bind(delivery.id)
+    .fetch_one(&mut *conn)
+    .await
+}
+
+/// The X-Webhook-Signature value for a request body signed at `timestamp`.
+pub fn signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+fn retry_delay(attempts: i32) -> chrono::Duration {
+    let doublings = (attempts - 1).clamp(0, 16) as u32;
+    chrono::Duration::seconds((RETRY_BASE_SECS << doublings).min(RETRY_MAX_SECS))
+}
+
+// Sends one attempt; the endpoint's status on success, else that status (if any) and why it failed
+async fn attempt(client: &awc::Client, policy: &WebhookPolicy, webhook: &Webhook, delivery: &WebhookDelivery) -> Result<i32, (Option<i32>, String)> {
+    // The host may have been repointed since the webhook was created
+    policy.check(&webhook.url).await.map_err(|e| (None, e))?;
+
+    let body = serde_json::to_vec(&delivery.payload).unwrap_or_default();
+    let timestamp = Utc::now().timestamp();
+
+    let response = client
+        .post(&webhook.url)
+        .insert_header((header::CONTENT_TYPE, "application/json"))
+        .insert_header(("X-Webhook-Event", delivery.event.as_str()))
+        .insert_header(("X-Webhook-Delivery", delivery.id.to_string()))
+        .insert_header(("X-Webhook-Timestamp", timestamp.to_string()))
+        .insert_header(("X-Webhook-Signature", signature(&webhook.secret, timestamp, &body)))
+        .send_body(body)
+        .await
+        .map_err(|e| (None, e.to_string()))?;
+
+    let status = response.status();
+    if status.is_success() {
+        Ok(status.as_u16() as i32)
+    } else {
+        Err((Some(status.as_u16() as i32), format!("Endpoint responded {}", status)))
+    }
+}
+
+async fn record(pool: &PgPool, delivery: &WebhookDelivery, outcome: Result<i32, (Option<i32>, String)>, at: NaiveDateTime) -> Result<(), sqlx::Error> {
+    let attempts = delivery.attempts + 1;
+    let (status, response_status, last_error, next_attempt_at, delivered_at) = match outcome {
+        Ok(code) => ("delivered", Some(code), None, None, Some(at)),
+        Err((code, error)) if attempts >= MAX_ATTEMPTS => ("failed", code, Some(error), None, None),
+        Err((code, error)) => ("pending", code, Some(error), Some(at + retry_delay(attempts)), None),
+    };
+
+    sqlx::query(
+        "UPDATE webhook_deliveries SET status = $1, attempts = $2, last_attempt_at = $3, response_status = $4, last_error = $5, next_attempt_at = $6, delivered_at = $7 WHERE id = $8"
+    )
+    .bind(status)
+    .bind(attempts)
+    .bind(at)
+    .bind(response_status)
+    .bind(last_error)
+    .bind(next_attempt_at)
+    .bind(delivered_at)
+    .bind(delivery.id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Sends the deliveries that are due, concurrently, and records each outcome.
+async fn dispatch_due(pool: &PgPool, client: &awc::Client, policy: &WebhookPolicy) -> Result<(), sqlx::Error> {
+    let now = Utc::now().naive_utc();
+    // Claimed by pushing the next attempt past the request timeout, so another dispatcher won't also send them
+    let lease = chrono::Duration::seconds(2 * REQUEST_TIMEOUT_SECS as i64);
+    let due = sqlx::query_as::<_, WebhookDelivery>(
+        "UPDATE webhook_deliveries SET next_attempt_at = $2 WHERE id IN \
+         (SELECT id FROM webhook_deliveries WHERE status = 'pending' AND next_attempt_at <= $1 ORDER BY next_attempt_at LIMIT $3 FOR UPDATE SKIP LOCKED) \
+         RETURNING *"
+    )
+    .bind(now)
+    .bind(now + lease)
+    .bind(DISPATCH_BATCH)
+    .fetch_all(pool)
+    .await?;
+    if due.is_empty() {
+        return Ok(());
+    }
+
+    let webhook_ids: Vec<Uuid> = due.iter().map(|d| d.webhook_id).collect();
+    let webhooks: HashMap<Uuid, Webhook> = sqlx::query_as::<_, Webhook>("SELECT * FROM webhooks WHERE id = ANY($1)")
+        .bind(&webhook_ids)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|w| (w.id, w))
+        .collect();
+
+    let attempts = due.iter().filter_map(|delivery| {
+        let webhook = webhooks.get(&delivery.webhook_id)?;
+        Some(async move { (delivery, attempt(client, policy, webhook, delivery).await) })
+    });
+    for (delivery, outcome) in futures_util::future::join_all(attempts).await {
+        record(pool, delivery, outcome, Utc::now().naive_utc()).await?;
+    }
+    Ok(())
+}
+
+pub async fn run_dispatcher(pool: PgPool, policy: WebhookPolicy) {
+    // Redirects aren't followed: they would lead past the address check
+    let client = awc::Client::builder()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .disable_redirects()
+        .finish();
+    let mut interval = actix_web::rt::time::interval(Duration::from_secs(DISPATCH_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        if let Err(e) = dispatch_due(&pool, &client, &policy).await {
+            log::error!("Failed to dispatch webhook deliveries: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_globally_routable_addresses_are_public() {
+        for ip in ["93.184.216.34", "8.8.8.8", "2606:4700::1111", "::ffff:93.184.216.34"] {
+            assert!(is_public(&ip.parse().unwrap()), "{} is public", ip);
+        }
+        for ip in [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0", "255.255.255.255",
+            "224.0.0.1", "::1", "::", "fd00::1", "fe80::1", "::ffff:127.0.0.1", "::ffff:10.0.0.1", "64:ff9b::a00:1",
+        ] {
+            assert!(!is_public(&ip.parse().unwrap()), "{} is not public", ip);
+        }
+    }
+
+    #[actix_web::test]
+    async fn policy_rejects_urls_reaching_the_local_network_unless_allowed() {
+        let policy = WebhookPolicy::default();
+        assert!(policy.check("http://127.0.0.1:9000/hook").await.is_err());
+        assert!(policy.check("http://[::1]/hook").await.is_err());
+        assert!(policy.check("http://localhost/hook").await.is_err());
+        assert!(policy.check("ftp://93.184.216.34/hook").await.is_err());
+        assert!(policy.check("/hook").await.is_err());
+        assert!(policy.check("https://93.184.216.34/hook").await.is_ok());
+
+        let policy = WebhookPolicy { allowed_hosts: vec!["localhost".to_string()] };
+        assert!(policy.check("http://LOCALHOST:9000/hook").await.is_ok());
+    }
+}